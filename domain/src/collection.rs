@@ -0,0 +1,60 @@
+/// A named, ordered group of media items
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct Collection {
+    pub id: String,
+    pub name: String,
+    pub kind: CollectionKind,
+    /// In the order they're listed
+    pub media_ids: Vec<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CollectionKind {
+    /// Media of the same franchise, like the movies of a series
+    Franchise,
+    /// Hand picked media, like "Watch later"
+    #[default]
+    List,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct CreateCollectionForm {
+    pub name: String,
+    pub kind: CollectionKind,
+    #[serde(default)]
+    pub media_ids: Vec<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct UpdateCollectionForm {
+    pub id: String,
+    pub name: String,
+    pub kind: CollectionKind,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct DeleteCollectionForm {
+    pub id: String,
+}
+
+/// Adds media to a collection. Media that's already in it is moved to `position`.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct AddToCollectionForm {
+    pub id: String,
+    pub media_id: String,
+    /// Index in the collection, the end if `None`
+    pub position: Option<u32>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct RemoveFromCollectionForm {
+    pub id: String,
+    pub media_id: String,
+}
+
+/// Moves a collection to `position` in the list of collections
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct MoveCollectionForm {
+    pub id: String,
+    pub position: u32,
+}
@@ -1,3 +1,5 @@
+use std::time::{Duration, SystemTime};
+
 use crate::MediaMetaData;
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
@@ -7,8 +9,47 @@ pub struct Download {
     pub progress: f32,
     pub needs_file_mapping: bool,
     pub state: DownloadState,
+    /// 1 based position in the download queue. `None` if the download isn't queued.
+    pub queue_position: Option<u32>,
+    /// Total size of the files that are being downloaded
+    pub size: Bytes,
+    pub download_speed: BytesPerSecond,
+    pub upload_speed: BytesPerSecond,
+    /// Estimated time left. `None` if it can't be estimated, e.g. there are no seeds.
+    pub eta: Option<Duration>,
+    /// Number of seeds we're connected to
+    pub seeds: u32,
+    pub added_on: SystemTime,
 }
 
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    serde::Serialize,
+    serde::Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Default,
+)]
+pub struct Bytes(pub u64);
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    serde::Serialize,
+    serde::Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Default,
+)]
+pub struct BytesPerSecond(pub u64);
+
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
 pub enum DownloadState {
     Paused,
@@ -24,3 +65,55 @@ pub struct DownloadForm {
     pub metadata: MediaMetaData,
     pub is_series: bool,
 }
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq, Debug)]
+pub enum QueuePositionChange {
+    Top,
+    Bottom,
+    Up,
+    Down,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct DownloadQueuePositionForm {
+    pub id: Box<str>,
+    pub change: QueuePositionChange,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq, Debug, Default)]
+pub struct DownloadQueueSettings {
+    /// `None` disables queueing, everything downloads at once.
+    pub max_active_downloads: Option<u32>,
+}
+
+/// A finished download, kept after its torrent is removed.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct DownloadHistoryEntry {
+    /// Id of the download this entry was created from
+    pub id: Box<str>,
+    pub title: Box<str>,
+    pub added_on: SystemTime,
+    pub finished_on: SystemTime,
+    pub outcome: DownloadOutcome,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub enum DownloadOutcome {
+    /// Download was moved into the media library
+    Completed { media_id: String },
+    Failed {
+        reason: DownloadFailureReason,
+        /// Human readable details, not meant to be parsed
+        message: String,
+    },
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub enum DownloadFailureReason {
+    /// The torrent client gave up on the download, e.g. its files went missing
+    Torrent,
+    /// The metadata stored alongside the download couldn't be read
+    CorruptMetadata,
+    /// Downloaded files couldn't be moved into the media library
+    CantMove,
+}
@@ -4,6 +4,11 @@ pub fn is_video_file(path: impl AsRef<Path>) -> bool {
     if !path.as_ref().is_file() {
         return false;
     }
+    has_video_extension(path)
+}
+
+/// Like [`is_video_file`], without checking the file system.
+pub fn has_video_extension(path: impl AsRef<Path>) -> bool {
     match path.as_ref().extension().and_then(|os_str| os_str.to_str()) {
         None => false,
         Some(extension) => matches!(extension, "mp4" | "mov" | "mkv" | "ts" | "avi"),
@@ -17,6 +22,6 @@ pub fn is_subtitle_file(path: impl AsRef<Path>) -> bool {
     path.as_ref()
         .extension()
         .and_then(|extension| extension.to_str())
-        .map(|extension| matches!(extension, "srt" | "vtt"))
+        .map(|extension| matches!(extension, "srt" | "vtt" | "ass" | "ssa"))
         .unwrap_or(false)
 }
@@ -0,0 +1,50 @@
+/// How files of an imported folder end up in the library
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TransferMode {
+    Move,
+    /// Leaves the originals in place. The folder has to be on the same file system as the library.
+    #[default]
+    HardLink,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct ImportForm {
+    /// Path of the folder to import, on the server. It has to be inside one of the folders the
+    /// server imports from.
+    pub path: String,
+    #[serde(default)]
+    pub mode: TransferMode,
+    /// Library to import into. Picked by the kind of each item if not set.
+    #[serde(default)]
+    pub library_id: Option<String>,
+    /// Only reports what would be imported
+    pub dry_run: bool,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct ImportedItem {
+    /// Name of the file or folder in the imported folder
+    pub source: String,
+    pub title: String,
+    pub year: Option<u32>,
+    /// `None` for movies
+    pub episode_count: Option<u32>,
+    pub outcome: ImportOutcome,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq, Debug)]
+pub enum ImportOutcome {
+    /// Result of a dry run
+    WouldImport {
+        media_id: String,
+    },
+    Imported {
+        media_id: String,
+    },
+    Skipped {
+        reason: String,
+    },
+    Failed {
+        message: String,
+    },
+}
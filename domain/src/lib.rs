@@ -1,8 +1,13 @@
+pub mod collection;
 mod download;
 pub mod encode_decode;
 pub mod format;
+pub mod import;
 pub mod language;
+pub mod library;
 mod media;
+pub mod metadata;
+pub mod search;
 pub mod series;
 pub mod subtitles;
 
@@ -0,0 +1,29 @@
+use crate::metadata::MediaKind;
+
+/// A named collection of media with its own root folder on the server
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct Library {
+    pub id: String,
+    pub name: String,
+    pub kind: LibraryKind,
+}
+
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+pub enum LibraryKind {
+    #[default]
+    Mixed,
+    Movies,
+    Series,
+}
+
+impl LibraryKind {
+    /// Whether new media of `kind` belongs to libraries of this kind
+    pub fn accepts(&self, kind: &MediaKind) -> bool {
+        matches!(
+            (self, kind),
+            (LibraryKind::Mixed, _)
+                | (LibraryKind::Movies, MediaKind::Movie)
+                | (LibraryKind::Series, MediaKind::Series)
+        )
+    }
+}
@@ -1,9 +1,19 @@
 use crate::{series::EpisodeIdentifier, subtitles::Subtitle};
-use std::{collections::HashMap, path::Path};
+use std::{collections::HashMap, path::Path, time::SystemTime};
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
 pub struct Media {
     pub id: String,
+    /// Id of the library the media is in
+    #[serde(default)]
+    pub library_id: String,
+    /// When the media entered the library
+    #[serde(default)]
+    pub added_on: Option<SystemTime>,
+    /// Path the server serves the cached artwork at, `None` when there is no artwork. The
+    /// source stays in `metadata.thumbnail`.
+    #[serde(default)]
+    pub artwork_url: Option<String>,
     pub metadata: MediaMetaData,
     pub content: MediaContent,
 }
@@ -98,6 +108,16 @@ pub struct MediaPaths {
     pub media: String,
     pub track_name: String,
     pub subtitles: Vec<Subtitle>,
+    /// A representative frame of the video
+    #[serde(default)]
+    pub thumbnail: Option<String>,
+    /// WebVTT track that points every part of the video to a tile in a sprite sheet,
+    /// used for seek previews
+    #[serde(default)]
+    pub thumbnail_track: Option<String>,
+    /// When the episode entered the library. Movies use [`Media::added_on`] instead.
+    #[serde(default)]
+    pub added_on: Option<SystemTime>,
 }
 
 impl MediaPaths {
@@ -111,20 +131,28 @@ impl MediaPaths {
         let subtitles = self
             .subtitles
             .iter()
-            .map(|subtitle| Subtitle {
-                path: prefix
-                    .as_ref()
-                    .join(&subtitle.path)
-                    .to_string_lossy()
-                    .to_string(),
-                ..subtitle.clone()
+            .map(|subtitle| {
+                Subtitle::new(
+                    subtitle.id.clone(),
+                    subtitle.language.clone(),
+                    prefix
+                        .as_ref()
+                        .join(&subtitle.path)
+                        .to_string_lossy()
+                        .to_string(),
+                )
             })
             .collect();
 
+        let add_prefix = |path: &String| prefix.as_ref().join(path).to_string_lossy().to_string();
+
         Self {
             media,
             subtitles,
             track_name: self.track_name.clone(),
+            thumbnail: self.thumbnail.as_ref().map(add_prefix),
+            thumbnail_track: self.thumbnail_track.as_ref().map(add_prefix),
+            added_on: self.added_on,
         }
     }
 
@@ -139,21 +167,34 @@ impl MediaPaths {
             .subtitles
             .iter()
             .map(|subtitle| {
-                Some(Subtitle {
-                    path: subtitle
+                Some(Subtitle::new(
+                    subtitle.id.clone(),
+                    subtitle.language.clone(),
+                    subtitle
                         .path
                         .strip_prefix(prefix.as_ref().to_string_lossy().as_ref())?
                         .trim_start_matches('/')
                         .to_string(),
-                    ..subtitle.clone()
-                })
+                ))
             })
             .collect::<Option<Vec<_>>>()?;
 
+        let strip_prefix = |path: &Option<String>| match path {
+            Some(path) => Some(Some(
+                path.strip_prefix(prefix.as_ref().to_string_lossy().as_ref())?
+                    .trim_start_matches('/')
+                    .to_string(),
+            )),
+            None => Some(None),
+        };
+
         Some(Self {
             subtitles,
             media,
             track_name: self.track_name.clone(),
+            thumbnail: strip_prefix(&self.thumbnail)?,
+            thumbnail_track: strip_prefix(&self.thumbnail_track)?,
+            added_on: self.added_on,
         })
     }
 }
@@ -210,10 +251,94 @@ pub fn is_audio_codec_compatible(audio_codec: &str) -> bool {
     matches!(audio_codec, "ac3" | "aac" | "eac3")
 }
 
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
 pub struct MediaMetaData {
     pub thumbnail: String,
     pub title: String,
+    /// Release year for movies, first air year for series
+    #[serde(default)]
+    pub year: Option<u32>,
+    #[serde(default)]
+    pub overview: Option<String>,
+    #[serde(default)]
+    pub genres: Vec<String>,
+    #[serde(default)]
+    pub external_ids: ExternalIds,
+    /// Season number -> episode number -> episode metadata
+    #[serde(default)]
+    pub episodes: HashMap<u32, HashMap<u32, EpisodeMetaData>>,
+}
+
+/// Ids of the media in metadata providers
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+pub struct ExternalIds {
+    #[serde(default)]
+    pub tmdb: Option<u64>,
+    #[serde(default)]
+    pub imdb: Option<String>,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+pub struct EpisodeMetaData {
+    #[serde(default)]
+    pub title: Option<String>,
+    /// `YYYY-MM-DD`
+    #[serde(default)]
+    pub air_date: Option<String>,
+}
+
+/// Replaces the metadata of a media item
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct EditMediaMetaDataForm {
+    pub media_id: String,
+    pub metadata: MediaMetaData,
+}
+
+/// Replaces the metadata of a single episode, leaving the rest of the series as is
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct EditEpisodeMetaDataForm {
+    pub media_id: String,
+    pub episode: EpisodeIdentifier,
+    pub metadata: EpisodeMetaData,
+}
+
+/// What to remove from the library
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub enum DeleteMediaForm {
+    Media {
+        media_id: String,
+    },
+    Season {
+        media_id: String,
+        season_no: u32,
+    },
+    Episode {
+        media_id: String,
+        episode: EpisodeIdentifier,
+    },
+    /// `path` is the subtitle's path as listed in its `MediaPaths`
+    Subtitle {
+        media_id: String,
+        path: String,
+    },
+}
+
+impl DeleteMediaForm {
+    pub fn media_id(&self) -> &str {
+        match self {
+            DeleteMediaForm::Media { media_id }
+            | DeleteMediaForm::Season { media_id, .. }
+            | DeleteMediaForm::Episode { media_id, .. }
+            | DeleteMediaForm::Subtitle { media_id, .. } => media_id,
+        }
+    }
+}
+
+/// Gives media a new title. Its folder, and with it its id, is named after the title.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct RenameMediaForm {
+    pub media_id: String,
+    pub title: String,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
@@ -231,6 +356,9 @@ pub enum Track {
     },
     Subtitle {
         id: usize,
+        /// Missing in track selections sent by older clients
+        #[serde(default)]
+        codec: String,
         language: Option<crate::language::LanguageCode>,
         external_id: Option<String>,
     },
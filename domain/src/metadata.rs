@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Hash)]
+pub enum MediaKind {
+    Movie,
+    Series,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct MetadataSearchResult<Id> {
+    pub id: Id,
+    pub kind: MediaKind,
+    pub title: String,
+    pub year: Option<u32>,
+    /// URL of the poster image
+    pub poster: Option<String>,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+pub struct MediaDetails {
+    pub title: String,
+    pub overview: Option<String>,
+    /// Release year for movies, first air year for series
+    pub year: Option<u32>,
+    pub genres: Vec<String>,
+    /// Ordered by billing
+    pub cast: Vec<CastMember>,
+    /// Episode runtime for series
+    pub runtime: Option<Duration>,
+    /// URL of the poster image
+    pub poster: Option<String>,
+    /// URL of the backdrop image
+    pub backdrop: Option<String>,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct CastMember {
+    pub name: String,
+    pub character: Option<String>,
+}
+
+pub trait MetadataProvider {
+    type MediaId: serde::Serialize + serde::de::DeserializeOwned;
+    type Error: std::error::Error;
+
+    fn search(
+        &self,
+        query: &str,
+        kind: MediaKind,
+    ) -> impl Future<
+        Output = Result<impl Iterator<Item = MetadataSearchResult<Self::MediaId>>, Self::Error>,
+    >;
+
+    fn details(
+        &self,
+        id: &Self::MediaId,
+        kind: MediaKind,
+    ) -> impl Future<Output = Result<MediaDetails, Self::Error>>;
+}
@@ -0,0 +1,73 @@
+use std::time::SystemTime;
+
+use crate::{Media, language::LanguageCode, metadata::MediaKind, series::EpisodeIdentifier};
+
+/// Query of the library search. Every filter is optional, media has to match all that are set.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+pub struct MediaQuery {
+    /// Words that have to appear in the title, in any order
+    pub text: Option<String>,
+    pub kind: Option<MediaKind>,
+    /// Only media with subtitles in this language. For series, any episode counts.
+    pub subtitle_language: Option<LanguageCode>,
+    /// `true` leaves out media with files that are still being converted to a compatible format,
+    /// `false` only lists those
+    pub compatible: Option<bool>,
+    /// Seconds since the Unix epoch
+    pub added_after: Option<u64>,
+    /// Seconds since the Unix epoch
+    pub added_before: Option<u64>,
+    pub library: Option<String>,
+    #[serde(default)]
+    pub sort: MediaSort,
+    #[serde(default)]
+    pub order: SortOrder,
+    #[serde(default)]
+    pub offset: u32,
+    /// Every match after `offset` if not set
+    pub limit: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+pub enum MediaSort {
+    /// Best matches of `text` first, then by title
+    #[default]
+    Relevance,
+    Title,
+    Year,
+    Added,
+}
+
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+pub struct MediaSearchResults {
+    pub items: Vec<Media>,
+    /// Number of matches before pagination
+    pub total: u32,
+    pub offset: u32,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+pub struct RecentlyAddedQuery {
+    /// Seconds since the Unix epoch. Only media that got something new after this is listed.
+    pub since: Option<u64>,
+    pub library: Option<String>,
+    pub limit: Option<u32>,
+}
+
+/// Media that entered the library recently, or a series that got new episodes
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct RecentlyAdded {
+    pub media: Media,
+    /// When the media or its latest episode was added
+    pub added_on: SystemTime,
+    /// Episodes added after `since` of the query, or the ones that were added last if it's not
+    /// set. Always empty for movies.
+    pub new_episodes: Vec<EpisodeIdentifier>,
+}
@@ -116,6 +116,16 @@ pub mod file_mapping_form_state {
 /// Key is file path
 pub type SeriesFileMapping = HashMap<String, EpisodeIdentifier>;
 
+/// The server's guess for which episode a file in a series download is.
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct FileMappingSuggestion {
+    pub file: String,
+    /// `None` if the file name doesn't look like an episode at all
+    pub episode: Option<EpisodeIdentifier>,
+    /// `false` if a human should double check the guess
+    pub confident: bool,
+}
+
 #[cfg(test)]
 mod tests {
     use std::{collections::HashMap, marker::PhantomData};
@@ -0,0 +1,243 @@
+//! Finds the retiming that lines cues up with the speech in an audio track.
+//!
+//! Every framerate correction between common framerates is tried with every offset up to
+//! [`MAX_OFFSET`]. A retiming scores the time its cues overlap speech minus the time they overlap
+//! silence, so cues that land on silence count against it.
+
+use std::{cmp::Reverse, time::Duration};
+
+use super::track::{Framerate, FramerateCorrection, Retiming, SubtitleTrack};
+
+/// Subtitles further off than this are most likely for another release
+pub const MAX_OFFSET: Duration = Duration::from_secs(60);
+
+const FRAMERATES: [Framerate; 4] = [
+    Framerate::FILM,
+    Framerate::CINEMA,
+    Framerate::PAL,
+    Framerate::NTSC,
+];
+
+/// Speech in an audio track, one flag for each `frame` long slice
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoiceActivity {
+    pub frame: Duration,
+    pub frames: Vec<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Alignment {
+    pub retiming: Retiming,
+    /// Share of the cue time that overlaps speech after the retiming, between 0 and 1
+    pub speech_ratio: f32,
+}
+
+struct Candidate {
+    framerate: Option<FramerateCorrection>,
+    offset_frames: i64,
+    speech_frames: i64,
+    cue_frames: i64,
+}
+
+impl Candidate {
+    fn score(&self) -> i64 {
+        2 * self.speech_frames - self.cue_frames
+    }
+}
+
+/// `None` if no retiming puts the cues on speech more than on silence
+pub fn align(track: &SubtitleTrack, voice_activity: &VoiceActivity) -> Option<Alignment> {
+    let frame = voice_activity.frame.as_nanos();
+    if frame == 0 {
+        return None;
+    }
+
+    // Speech frames before each frame, so the speech in a cue takes a subtraction to count
+    let speech_before: Vec<i64> = std::iter::once(0)
+        .chain(voice_activity.frames.iter().scan(0, |count, is_speech| {
+            *count += i64::from(*is_speech);
+            Some(*count)
+        }))
+        .collect();
+    let frame_count = voice_activity.frames.len() as i64;
+    let max_offset = (MAX_OFFSET.as_nanos() / frame) as i64;
+
+    let framerates = std::iter::once(None).chain(FRAMERATES.iter().flat_map(|subtitle| {
+        FRAMERATES
+            .iter()
+            .filter(move |video| *video != subtitle)
+            .map(|video| {
+                Some(FramerateCorrection {
+                    subtitle: *subtitle,
+                    video: *video,
+                })
+            })
+    }));
+
+    let best = framerates
+        .filter_map(|framerate| {
+            let retiming = Retiming {
+                offset_millis: 0,
+                framerate,
+            };
+            let cues: Vec<(i64, i64)> = track
+                .cues
+                .iter()
+                .filter_map(|cue| {
+                    let start = retiming.apply(cue.start)?.as_nanos() / frame;
+                    let end = retiming.apply(cue.end)?.as_nanos() / frame;
+                    Some((start as i64, end as i64))
+                })
+                .collect();
+            let cue_frames = cues.iter().map(|(start, end)| end - start).sum();
+
+            (-max_offset..=max_offset)
+                .map(|offset_frames| {
+                    let speech_frames = cues
+                        .iter()
+                        .map(|(start, end)| {
+                            let start = (start + offset_frames).clamp(0, frame_count) as usize;
+                            let end = (end + offset_frames).clamp(0, frame_count) as usize;
+                            speech_before[end] - speech_before[start]
+                        })
+                        .sum();
+                    Candidate {
+                        framerate,
+                        offset_frames,
+                        speech_frames,
+                        cue_frames,
+                    }
+                })
+                // Smaller offsets win ties
+                .max_by_key(|candidate| (candidate.score(), Reverse(candidate.offset_frames.abs())))
+        })
+        // The first, uncorrected framerate wins ties
+        .reduce(|best, candidate| match candidate.score() > best.score() {
+            true => candidate,
+            false => best,
+        })
+        .filter(|best| best.score() > 0)?;
+
+    Some(Alignment {
+        retiming: Retiming {
+            offset_millis: (best.offset_frames as i128 * frame as i128 / 1_000_000) as i64,
+            framerate: best.framerate,
+        },
+        speech_ratio: best.speech_frames as f32 / best.cue_frames as f32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{VoiceActivity, align};
+    use crate::subtitles::{
+        Cue, Framerate, FramerateCorrection, Retiming, SubtitleFormat, SubtitleTrack,
+    };
+
+    const FRAME: Duration = Duration::from_millis(10);
+
+    fn track(cues: &[(u64, u64)]) -> SubtitleTrack {
+        SubtitleTrack {
+            format: SubtitleFormat::Srt,
+            header: None,
+            cues: cues
+                .iter()
+                .map(|(start, end)| Cue {
+                    id: None,
+                    start: Duration::from_millis(*start),
+                    end: Duration::from_millis(*end),
+                    text: "Hello".to_string(),
+                    settings: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Speech exactly where the cues of `track` are
+    fn voice_activity(track: &SubtitleTrack, duration: Duration) -> VoiceActivity {
+        let frame_count = (duration.as_millis() / FRAME.as_millis()) as usize;
+        let mut frames = vec![false; frame_count];
+        for cue in &track.cues {
+            let start = (cue.start.as_millis() / FRAME.as_millis()) as usize;
+            let end = (cue.end.as_millis() / FRAME.as_millis()) as usize;
+            frames[start.min(frame_count)..end.min(frame_count)].fill(true);
+        }
+
+        VoiceActivity {
+            frame: FRAME,
+            frames,
+        }
+    }
+
+    fn dialogue() -> Vec<(u64, u64)> {
+        (0..40)
+            .map(|index| {
+                // Uneven gaps so no other offset lines up as well
+                let start = 5_000 + index * 4_000 + (index * index * 37) % 1_500;
+                (start, start + 1_200 + (index * 113) % 900)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_offset() {
+        let mut spoken = track(&dialogue());
+        let subtitles = spoken.clone();
+        spoken.retime(&Retiming {
+            offset_millis: 2_340,
+            framerate: None,
+        });
+
+        let alignment = align(
+            &subtitles,
+            &voice_activity(&spoken, Duration::from_secs(300)),
+        )
+        .unwrap();
+
+        assert_eq!(
+            alignment.retiming,
+            Retiming {
+                offset_millis: 2_340,
+                framerate: None
+            }
+        );
+        assert_eq!(alignment.speech_ratio, 1.0);
+    }
+
+    #[test]
+    fn test_framerate() {
+        let framerate = Some(FramerateCorrection {
+            subtitle: Framerate::PAL,
+            video: Framerate::FILM,
+        });
+        let mut spoken = track(&dialogue());
+        let subtitles = spoken.clone();
+        spoken.retime(&Retiming {
+            offset_millis: -1_000,
+            framerate,
+        });
+
+        let alignment = align(
+            &subtitles,
+            &voice_activity(&spoken, Duration::from_secs(300)),
+        )
+        .unwrap();
+
+        assert_eq!(alignment.retiming.framerate, framerate);
+        assert!((alignment.retiming.offset_millis + 1_000).abs() <= 10);
+        assert!(alignment.speech_ratio > 0.95);
+    }
+
+    #[test]
+    fn test_silence() {
+        let subtitles = track(&dialogue());
+        let silence = VoiceActivity {
+            frame: FRAME,
+            frames: vec![false; 30_000],
+        };
+
+        assert_eq!(align(&subtitles, &silence), None);
+    }
+}
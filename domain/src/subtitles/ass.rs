@@ -0,0 +1,204 @@
+use super::track::{
+    Cue, ParseError, SubtitleFormat, SubtitleTrack, parse_timestamp, split_timestamp,
+};
+
+/// Used when the track wasn't parsed from an ASS file
+const DEFAULT_HEADER: &str = "[Script Info]
+ScriptType: v4.00+
+PlayResX: 384
+PlayResY: 288
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Default,Arial,20,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,2,2,2,10,10,10,1
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text";
+
+/// The header runs until the `Format` line of the `[Events]` section, which names the fields of
+/// dialogue lines. Comments and sections after `[Events]`, like embedded fonts, are dropped.
+pub(super) fn parse(text: &str) -> Result<SubtitleTrack, ParseError> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line));
+
+    let mut header = Vec::new();
+    let mut in_events = false;
+    let fields = loop {
+        let (_, line) = lines.next().ok_or(ParseError::MissingHeader)?;
+        header.push(line);
+
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_events = trimmed.eq_ignore_ascii_case("[Events]");
+        } else if in_events && let Some(format) = trimmed.strip_prefix("Format:") {
+            break fields(format);
+        }
+    };
+    let field_index = |name: &str| {
+        fields
+            .iter()
+            .position(|field| field == name)
+            .ok_or(ParseError::MissingHeader)
+    };
+    let (start_index, end_index, text_index) = (
+        field_index("start")?,
+        field_index("end")?,
+        field_index("text")?,
+    );
+    if text_index != fields.len() - 1 {
+        return Err(ParseError::MissingHeader);
+    }
+
+    let mut cues = Vec::new();
+    for (line_no, line) in lines {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('[') {
+            break;
+        }
+        let Some(dialogue) = trimmed.strip_prefix("Dialogue:") else {
+            continue;
+        };
+
+        let values: Vec<&str> = dialogue.trim_start().splitn(fields.len(), ',').collect();
+        if values.len() != fields.len() {
+            return Err(ParseError::InvalidCue { line: line_no });
+        }
+
+        let invalid_timestamp = ParseError::InvalidTimestamp { line: line_no };
+        let start = parse_timestamp(values[start_index]).ok_or(invalid_timestamp.clone())?;
+        let end = parse_timestamp(values[end_index]).ok_or(invalid_timestamp)?;
+        let settings = values
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| ![start_index, end_index, text_index].contains(index))
+            .map(|(_, value)| *value)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        cues.push(Cue {
+            id: None,
+            start,
+            end,
+            text: values[text_index].replace("\\N", "\n").replace("\\n", "\n"),
+            settings: Some(settings),
+        });
+    }
+
+    Ok(SubtitleTrack {
+        format: SubtitleFormat::Ass,
+        header: Some(header.join("\n")),
+        cues,
+    })
+}
+
+pub(super) fn write(header: Option<&str>, cues: impl Iterator<Item = Cue>) -> String {
+    let header = header.unwrap_or(DEFAULT_HEADER);
+    let fields = header
+        .lines()
+        .next_back()
+        .and_then(|line| line.trim().strip_prefix("Format:"))
+        .map(fields)
+        .expect("Header to end with the format of the events");
+
+    let dialogues: Vec<String> = cues
+        .map(|cue| {
+            let mut settings = cue.settings.as_deref().map(|settings| settings.split(','));
+            let values: Vec<String> = fields
+                .iter()
+                .map(|field| match field.as_str() {
+                    "start" => timestamp(cue.start),
+                    "end" => timestamp(cue.end),
+                    "text" => cue.text.replace('\n', "\\N"),
+                    _ => settings
+                        .as_mut()
+                        .and_then(|settings| settings.next())
+                        .map(str::to_string)
+                        .unwrap_or_else(|| default_value(field).to_string()),
+                })
+                .collect();
+
+            format!("Dialogue: {}", values.join(","))
+        })
+        .collect();
+
+    format!("{header}\n{}\n", dialogues.join("\n"))
+}
+
+/// Lowercase field names of a `Format` line
+fn fields(format: &str) -> Vec<String> {
+    format
+        .split(',')
+        .map(|field| field.trim().to_lowercase())
+        .collect()
+}
+
+fn default_value(field: &str) -> &'static str {
+    match field {
+        "layer" | "marginl" | "marginr" | "marginv" => "0",
+        "style" => "Default",
+        _ => "",
+    }
+}
+
+/// ASS keeps centiseconds and a single digit for hours
+fn timestamp(timestamp: std::time::Duration) -> String {
+    let (hours, minutes, seconds, millis) = split_timestamp(timestamp);
+    format!("{hours}:{minutes:02}:{seconds:02}.{:02}", millis / 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::subtitles::{ParseError, Styling, SubtitleFormat, SubtitleTrack};
+
+    const ASS: &str = "[Script Info]
+Title: Example
+ScriptType: v4.00+
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Default,Arial,20,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,2,2,2,10,10,10,1
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Dialogue: 0,0:00:01.00,0:00:02.50,Default,Obi-Wan,0,0,0,,Hello there.
+Dialogue: 1,1:00:03.00,1:00:04.04,Default,,0,0,0,,{\\pos(10,20)\\i1}General Kenobi!{\\i0}\\NYou are a bold one, aren't you?
+";
+
+    #[test]
+    fn test_round_trip() {
+        let track = SubtitleTrack::parse(ASS, SubtitleFormat::Ass).unwrap();
+
+        assert_eq!(track.cues.len(), 2);
+        assert_eq!(track.cues[1].start, Duration::from_secs(3_603));
+        assert_eq!(track.cues[1].end, Duration::from_millis(3_604_040));
+        assert_eq!(
+            track.cues[0].settings.as_deref(),
+            Some("0,Default,Obi-Wan,0,0,0,")
+        );
+        assert_eq!(
+            track.cues[1].plain_text(SubtitleFormat::Ass),
+            "General Kenobi!\nYou are a bold one, aren't you?"
+        );
+
+        assert_eq!(track.write(SubtitleFormat::Ass, Styling::Preserve), ASS);
+    }
+
+    #[test]
+    fn test_missing_events() {
+        assert_eq!(
+            SubtitleTrack::parse("[Script Info]\nTitle: Example\n", SubtitleFormat::Ass),
+            Err(ParseError::MissingHeader)
+        );
+        assert_eq!(
+            SubtitleTrack::parse(
+                "[Events]\nFormat: Start, End, Text\nDialogue: 0:00:01.00",
+                SubtitleFormat::Ass
+            ),
+            Err(ParseError::InvalidCue { line: 3 })
+        );
+    }
+}
@@ -0,0 +1,56 @@
+//! Subtitles from the internet are often in the legacy code page of their language rather than
+//! UTF-8, e.g. Windows-1254 for Turkish or Windows-1251 for Russian.
+
+use encoding_rs::{Encoding, UTF_8};
+
+/// Files with a byte order mark, or that are valid UTF-8 are taken as they are. Anything else is
+/// guessed from its content.
+pub fn detect(bytes: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return UTF_8;
+    }
+
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(bytes, true);
+    detector.guess(None, false)
+}
+
+/// Decodes subtitle bytes in any encoding. The byte order mark is dropped and invalid sequences are
+/// replaced.
+pub fn decode(bytes: &[u8]) -> String {
+    let (text, _, _) = detect(bytes).decode(bytes);
+    text.into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use encoding_rs::{UTF_16LE, WINDOWS_1251, WINDOWS_1254};
+
+    use super::{decode, detect};
+
+    #[test]
+    fn test_decode() {
+        let text = "1\n00:00:01,000 --> 00:00:02,000\nHello there.\n";
+        assert_eq!(decode(text.as_bytes()), text);
+
+        let russian = "1\n00:00:01,000 --> 00:00:02,000\nПривет. Как дела? Это очень хороший день для прогулки по городу.\n";
+        let (bytes, _, _) = WINDOWS_1251.encode(russian);
+        assert_eq!(detect(&bytes), WINDOWS_1251);
+        assert_eq!(decode(&bytes), russian);
+
+        let turkish = "1\n00:00:01,000 --> 00:00:02,000\nGüneşli bir günde ağaçların altında oturduk ve çay içtik, çok güzeldi.\n";
+        let (bytes, _, _) = WINDOWS_1254.encode(turkish);
+        assert_eq!(decode(&bytes), turkish);
+
+        // UTF-16 is only recognized by its byte order mark
+        let bytes: Vec<u8> = [0xff, 0xfe]
+            .into_iter()
+            .chain(text.encode_utf16().flat_map(u16::to_le_bytes))
+            .collect();
+        assert_eq!(detect(&bytes), UTF_16LE);
+        assert_eq!(decode(&bytes), text);
+    }
+}
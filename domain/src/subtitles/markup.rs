@@ -0,0 +1,227 @@
+//! SRT and WebVTT style text with HTML like tags, ASS with override blocks like `{\i1}`.
+//! Only bold, italic and underline can be carried between the two.
+
+use super::track::{Styling, SubtitleFormat};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Emphasis {
+    Bold,
+    Italic,
+    Underline,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    Text(String),
+    Open(Emphasis),
+    Close(Emphasis),
+}
+
+pub(super) fn convert(
+    text: &str,
+    from: SubtitleFormat,
+    to: SubtitleFormat,
+    styling: Styling,
+) -> String {
+    if from == to && styling == Styling::Preserve {
+        return text.to_string();
+    }
+
+    tokens(text, from)
+        .into_iter()
+        .filter(|token| styling == Styling::Preserve || matches!(token, Token::Text(_)))
+        .map(|token| match (token, to) {
+            (Token::Text(text), SubtitleFormat::WebVtt) => escape(&text),
+            (Token::Text(text), _) => text,
+            (Token::Open(emphasis), SubtitleFormat::Ass) => format!("{{\\{}1}}", letter(emphasis)),
+            (Token::Close(emphasis), SubtitleFormat::Ass) => format!("{{\\{}0}}", letter(emphasis)),
+            (Token::Open(emphasis), _) => format!("<{}>", letter(emphasis)),
+            (Token::Close(emphasis), _) => format!("</{}>", letter(emphasis)),
+        })
+        .collect()
+}
+
+/// Text without any markup or escapes
+pub(super) fn plain_text(text: &str, format: SubtitleFormat) -> String {
+    tokens(text, format)
+        .into_iter()
+        .filter_map(|token| match token {
+            Token::Text(text) => Some(text),
+            _ => None,
+        })
+        .collect()
+}
+
+fn tokens(text: &str, format: SubtitleFormat) -> Vec<Token> {
+    match format {
+        SubtitleFormat::Srt => html_tokens(text, false),
+        SubtitleFormat::WebVtt => html_tokens(text, true),
+        SubtitleFormat::Ass => ass_tokens(text),
+    }
+}
+
+fn letter(emphasis: Emphasis) -> char {
+    match emphasis {
+        Emphasis::Bold => 'b',
+        Emphasis::Italic => 'i',
+        Emphasis::Underline => 'u',
+    }
+}
+
+fn emphasis(letter: &str) -> Option<Emphasis> {
+    match letter {
+        "b" => Some(Emphasis::Bold),
+        "i" => Some(Emphasis::Italic),
+        "u" => Some(Emphasis::Underline),
+        _ => None,
+    }
+}
+
+/// WebVTT escapes `&`, `<` and `>` in text, SRT players show them as they are
+fn html_tokens(text: &str, is_escaped: bool) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let Some((before, after)) = rest.split_once('<') else {
+            push_text(&mut tokens, rest, is_escaped);
+            break;
+        };
+        push_text(&mut tokens, before, is_escaped);
+
+        let Some((tag, after)) = after.split_once('>') else {
+            // Not a tag after all
+            push_text(&mut tokens, &format!("<{after}"), is_escaped);
+            break;
+        };
+        rest = after;
+
+        let (is_closing, tag) = match tag.trim().strip_prefix('/') {
+            Some(tag) => (true, tag),
+            None => (false, tag.trim()),
+        };
+        // Classes and annotations like `<c.yellow>` or `<v Speaker>` are ignored
+        let name = tag
+            .split(['.', ' '])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        if let Some(emphasis) = emphasis(&name) {
+            tokens.push(match is_closing {
+                true => Token::Close(emphasis),
+                false => Token::Open(emphasis),
+            });
+        }
+    }
+
+    tokens
+}
+
+fn push_text(tokens: &mut Vec<Token>, text: &str, is_escaped: bool) {
+    if text.is_empty() {
+        return;
+    }
+
+    let text = match is_escaped {
+        true => unescape(text),
+        false => text.to_string(),
+    };
+    match tokens.last_mut() {
+        Some(Token::Text(last)) => last.push_str(&text),
+        _ => tokens.push(Token::Text(text)),
+    }
+}
+
+fn ass_tokens(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let Some((before, after)) = rest.split_once('{') else {
+            push_text(&mut tokens, &rest.replace("\\h", " "), false);
+            break;
+        };
+        push_text(&mut tokens, &before.replace("\\h", " "), false);
+
+        let Some((block, after)) = after.split_once('}') else {
+            break;
+        };
+        rest = after;
+
+        for tag in block.split('\\') {
+            let Some(emphasis) = tag.get(..1).and_then(emphasis) else {
+                continue;
+            };
+            // `\b` also takes font weights, `\b700` is bold as well
+            match &tag[1..] {
+                "" | "0" => tokens.push(Token::Close(emphasis)),
+                value if value.chars().all(|char| char.is_ascii_digit()) => {
+                    tokens.push(Token::Open(emphasis))
+                }
+                // Other tags that start with the same letter, like `\blur` or `\iclip`
+                _ => {}
+            }
+        }
+    }
+
+    tokens
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", "\u{a0}")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::convert;
+    use crate::subtitles::{Styling, SubtitleFormat};
+
+    #[test]
+    fn test_convert() {
+        assert_eq!(
+            convert(
+                "<i>Hi</i> <font color=\"red\">you</font> & me",
+                SubtitleFormat::Srt,
+                SubtitleFormat::WebVtt,
+                Styling::Preserve
+            ),
+            "<i>Hi</i> you &amp; me"
+        );
+        assert_eq!(
+            convert(
+                "<v Bob><b>Hi</b> &lt;3",
+                SubtitleFormat::WebVtt,
+                SubtitleFormat::Ass,
+                Styling::Preserve
+            ),
+            "{\\b1}Hi{\\b0} <3"
+        );
+        assert_eq!(
+            convert(
+                "{\\an8\\i1\\blur2}Hi{\\i}\\hthere",
+                SubtitleFormat::Ass,
+                SubtitleFormat::Srt,
+                Styling::Preserve
+            ),
+            "<i>Hi</i> there"
+        );
+        assert_eq!(
+            convert(
+                "<u>Hi</u> 1 < 2",
+                SubtitleFormat::Srt,
+                SubtitleFormat::Srt,
+                Styling::Strip
+            ),
+            "Hi 1 < 2"
+        );
+    }
+}
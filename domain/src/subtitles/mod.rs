@@ -0,0 +1,284 @@
+use std::{collections::HashMap, path::Path, time::SystemTime};
+
+use crate::{language::LanguageCode, series::EpisodeIdentifier};
+
+pub mod align;
+mod ass;
+pub mod encoding;
+mod markup;
+mod ranking;
+mod srt;
+mod track;
+mod vtt;
+
+pub use ranking::{rank, release_similarity};
+pub use track::{
+    Cue, Framerate, FramerateCorrection, ParseError, Retiming, Styling, SubtitleFormat,
+    SubtitleTrack,
+};
+
+/// The server expects this form for subtitle search requests
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct SubtitleSearchForm {
+    pub media_id: String,
+    pub language_code: LanguageCode,
+    pub episode_identifiers: Option<Vec<EpisodeIdentifier>>,
+    #[serde(default)]
+    pub filters: SubtitleSearchFilters,
+}
+
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+pub enum HearingImpairedFilter {
+    #[default]
+    Include,
+    Exclude,
+    Only,
+}
+
+/// Applied to the results of every provider. Results of providers that don't know about a
+/// property, e.g. whether the uploader is trusted, count as not having it.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+pub struct SubtitleSearchFilters {
+    pub hearing_impaired: HearingImpairedFilter,
+    pub trusted_only: bool,
+    pub hd_only: bool,
+    pub exclude_machine_translated: bool,
+}
+
+impl SubtitleSearchFilters {
+    pub fn matches<Id>(&self, option: &SubtitleDownloadOption<Id>) -> bool {
+        let metadata = &option.metadata;
+        let hearing_impaired = match self.hearing_impaired {
+            HearingImpairedFilter::Include => true,
+            HearingImpairedFilter::Exclude => !metadata.hearing_impaired,
+            HearingImpairedFilter::Only => metadata.hearing_impaired,
+        };
+
+        hearing_impaired
+            && (!self.trusted_only || metadata.from_trusted)
+            && (!self.hd_only || metadata.hd)
+            && (!self.exclude_machine_translated || !metadata.machine_translated)
+    }
+}
+
+/// Returned by the search endpoint
+pub type SubtitleSearchResponse = Vec<Vec<SubtitleDownloadOption<usize>>>;
+
+/// The server expects this form for subtitle download requests
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct SubtitleDownloadForm {
+    pub media_id: String,
+    pub language_code: LanguageCode,
+    pub selections: Vec<SubtitleSelection>,
+}
+
+/// Returned by the download endpoint
+pub type SubtitleDownloadResponse = HashMap<usize, Result<(), SubtitleDownloadError>>;
+
+/// The server expects this form to write a retimed copy of a stored subtitle
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct SubtitleSyncForm {
+    pub media_id: String,
+    /// Path of the subtitle in the media library
+    pub subtitle_path: String,
+    pub retiming: Retiming,
+}
+
+/// The server expects this form to queue a stored subtitle to be synced against the audio of
+/// its media
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct SubtitleAutoSyncForm {
+    pub media_id: String,
+    /// Path of the subtitle in the media library
+    pub subtitle_path: String,
+}
+
+/// Returned by the quota endpoint
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct SubtitleQuotaResponse {
+    /// `None` until OpenSubtitles reports it, usually after the first download
+    pub open_subtitles: Option<SubtitleQuota>,
+    /// Downloads count against a user account instead of the anonymous quota everyone shares
+    pub logged_in: bool,
+}
+
+/// Downloads left with a provider, as it last reported them
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct SubtitleQuota {
+    pub remaining: u32,
+    /// Per quota period, `None` if the provider didn't tell
+    pub allowed: Option<u32>,
+    /// When `remaining` is reset, `None` if the provider didn't tell
+    pub resets_at: Option<SystemTime>,
+}
+
+impl SubtitleQuota {
+    /// A quota that doesn't say when it resets isn't trusted to still be used up
+    pub fn is_exhausted(&self, now: SystemTime) -> bool {
+        self.remaining == 0 && self.resets_at.is_some_and(|resets_at| now < resets_at)
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub enum SubtitleSelection {
+    Series {
+        subtitle_id: usize,
+        episode_identifier: EpisodeIdentifier,
+    },
+    Movie {
+        subtitle_id: usize,
+    },
+}
+
+impl SubtitleSelection {
+    pub fn subtitle_id(&self) -> &usize {
+        match self {
+            SubtitleSelection::Series { subtitle_id, .. } => subtitle_id,
+            SubtitleSelection::Movie { subtitle_id } => subtitle_id,
+        }
+    }
+
+    pub fn episode_identifier(&self) -> Option<&EpisodeIdentifier> {
+        match self {
+            SubtitleSelection::Series {
+                episode_identifier, ..
+            } => Some(episode_identifier),
+            SubtitleSelection::Movie { .. } => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct Subtitle {
+    pub id: String,
+    pub language: LanguageCode,
+    /// The file as it's stored
+    pub path: String,
+    /// The subtitle converted to WebVTT by the server
+    #[serde(default)]
+    pub webvtt_path: String,
+    /// The subtitle converted to SRT by the server
+    #[serde(default)]
+    pub srt_path: String,
+}
+
+impl Subtitle {
+    pub fn new(id: String, language: LanguageCode, path: String) -> Self {
+        Self {
+            webvtt_path: converted_path(&path, SubtitleFormat::WebVtt),
+            srt_path: converted_path(&path, SubtitleFormat::Srt),
+            id,
+            language,
+            path,
+        }
+    }
+}
+
+/// Where the server serves the subtitle at `path` converted to `format`
+pub fn converted_path(path: &str, format: SubtitleFormat) -> String {
+    format!("subtitles/{}/{path}", format.extension())
+}
+
+/// How an embedded subtitle track is kept when its media is prepared
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub enum SubtitleCodecKind {
+    /// Plain or lightly formatted text, kept as SRT
+    Text,
+    /// ASS/SSA, kept as it is so positioning and styling survive
+    Styled,
+    /// Images like PGS or VobSub. They can't be turned into text, so they are kept in a Matroska
+    /// sidecar instead of the subtitle library.
+    Bitmap,
+}
+
+impl SubtitleCodecKind {
+    /// Takes an ffprobe codec name, `None` for codecs we don't know how to keep
+    pub fn from_codec(codec: &str) -> Option<Self> {
+        match codec {
+            "subrip" | "srt" | "mov_text" | "webvtt" | "text" | "microdvd" | "subviewer" => {
+                Some(Self::Text)
+            }
+            "ass" | "ssa" => Some(Self::Styled),
+            "hdmv_pgs_subtitle" | "dvd_subtitle" | "dvb_subtitle" | "xsub" => Some(Self::Bitmap),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Text => "srt",
+            Self::Styled => "ass",
+            Self::Bitmap => "mks",
+        }
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub enum SubtitleDownloadError {
+    SubtitleAlreadyExists,
+    DownloadQuotaReached,
+    InternalFileSystemError,
+    NonExistentSubtitle,
+    /// The provider couldn't be reached or sent something unexpected
+    ProviderError,
+}
+
+pub trait SubtitleProvider {
+    type SubtitleId: serde::Serialize + serde::de::DeserializeOwned;
+    type Error: std::error::Error;
+
+    /// `media_file` is the local file the subtitles are for. Providers that can find subtitles by
+    /// the file itself prefer those over matches on `query`.
+    fn search(
+        &self,
+        query: &str,
+        language: LanguageCode,
+        episode: Option<EpisodeIdentifier>,
+        media_file: Option<&Path>,
+    ) -> impl Future<
+        Output = Result<
+            impl Iterator<Item = SubtitleDownloadOption<Self::SubtitleId>>,
+            Self::Error,
+        >,
+    >;
+
+    fn download(&self, id: &Self::SubtitleId) -> impl Future<Output = Result<String, Self::Error>>;
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SubtitleDownloadOption<Id> {
+    pub id: Id,
+    pub title: String,
+    pub download_count: usize,
+    pub language: LanguageCode,
+    /// Made for the exact media file that was searched with
+    #[serde(default)]
+    pub hash_match: bool,
+    #[serde(default)]
+    pub metadata: SubtitleMetadata,
+}
+
+/// What providers know about a subtitle besides its name. Anything a provider doesn't share is
+/// left at its default.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+pub struct SubtitleMetadata {
+    /// Release the subtitle was timed for, e.g. `Movie.2006.720p.BluRay.x264-GROUP`
+    pub release: Option<String>,
+    pub hearing_impaired: bool,
+    /// Made for a HD release
+    pub hd: bool,
+    pub framerate: Option<Framerate>,
+    /// Average user rating out of 100, `None` without any votes
+    pub rating: Option<u8>,
+    /// Uploaded by someone the provider trusts
+    pub from_trusted: bool,
+    pub machine_translated: bool,
+}
+
+impl std::fmt::Display for SubtitleDownloadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#?}", self)
+    }
+}
+
+impl std::error::Error for SubtitleDownloadError {}
@@ -0,0 +1,142 @@
+//! Orders subtitle search results by how likely they are to fit the local file.
+//!
+//! Subtitles made for the exact file always come first. The rest are scored by how much their
+//! release name shares with ours, with a bonus for trusted uploaders and a small one for
+//! popular subtitles.
+
+use std::collections::HashSet;
+
+use super::SubtitleDownloadOption;
+
+/// About as much as a release that shares a quarter of its name with ours
+const TRUSTED_BONUS: f32 = 0.25;
+/// Per order of magnitude of downloads, 10000 downloads are worth as much as a trusted uploader
+const POPULARITY_WEIGHT: f32 = 0.0625;
+/// Left out of name comparisons, providers name subtitles after files or releases
+const SUBTITLE_EXTENSIONS: [&str; 5] = ["srt", "vtt", "ass", "ssa", "sub"];
+
+/// Best fits first, ties keep their order
+pub fn rank<Id>(
+    options: impl IntoIterator<Item = SubtitleDownloadOption<Id>>,
+    release: &str,
+) -> Vec<SubtitleDownloadOption<Id>> {
+    let release = tokens(release);
+    let mut scored: Vec<_> = options
+        .into_iter()
+        .map(|option| (score(&option, &release), option))
+        .collect();
+
+    scored.sort_by(|(score, option), (other_score, other)| {
+        other
+            .hash_match
+            .cmp(&option.hash_match)
+            .then(other_score.total_cmp(score))
+    });
+
+    scored.into_iter().map(|(_, option)| option).collect()
+}
+
+/// Share of the words in both names that are in each of them, from 0 to 1. Separators, case and
+/// subtitle extensions are ignored, `Movie.2006.720p-GROUP.srt` and `movie 2006 720p group`
+/// match completely.
+pub fn release_similarity(release: &str, other: &str) -> f32 {
+    jaccard(&tokens(release), &tokens(other))
+}
+
+fn score<Id>(option: &SubtitleDownloadOption<Id>, release: &HashSet<String>) -> f32 {
+    let similarity = std::iter::once(option.title.as_str())
+        .chain(option.metadata.release.as_deref())
+        .map(|name| jaccard(&tokens(name), release))
+        .fold(0.0, f32::max);
+    let trusted = match option.metadata.from_trusted {
+        true => TRUSTED_BONUS,
+        false => 0.0,
+    };
+    let popularity = (option.download_count as f32 + 1.0).log10() * POPULARITY_WEIGHT;
+
+    similarity + trusted + popularity
+}
+
+fn tokens(name: &str) -> HashSet<String> {
+    name.split(|char: char| !char.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_lowercase)
+        .filter(|token| !SUBTITLE_EXTENSIONS.contains(&token.as_str()))
+        .collect()
+}
+
+fn jaccard(tokens: &HashSet<String>, other: &HashSet<String>) -> f32 {
+    let union = tokens.union(other).count();
+    if union == 0 {
+        return 0.0;
+    }
+
+    tokens.intersection(other).count() as f32 / union as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        language::LanguageCode,
+        subtitles::{SubtitleDownloadOption, SubtitleMetadata},
+    };
+
+    use super::{rank, release_similarity};
+
+    fn option(id: usize, title: &str, download_count: usize) -> SubtitleDownloadOption<usize> {
+        SubtitleDownloadOption {
+            id,
+            title: title.to_string(),
+            download_count,
+            language: LanguageCode::English,
+            hash_match: false,
+            metadata: SubtitleMetadata::default(),
+        }
+    }
+
+    #[test]
+    fn test_release_similarity() {
+        assert_eq!(
+            release_similarity("Movie.2006.720p-GROUP.srt", "movie 2006 720p group"),
+            1.0
+        );
+        assert_eq!(
+            release_similarity("Movie.2006.720p-GROUP", "Movie.2006.1080p-GROUP"),
+            0.6
+        );
+        assert_eq!(release_similarity("", "Movie"), 0.0);
+    }
+
+    #[test]
+    fn test_rank() {
+        let ranked = rank(
+            [
+                option(1, "Movie.2006.1080p.WEB-OTHER.srt", 5_000),
+                option(2, "Movie.2006.720p.BluRay-GROUP.srt", 10),
+                SubtitleDownloadOption {
+                    metadata: SubtitleMetadata {
+                        from_trusted: true,
+                        ..Default::default()
+                    },
+                    ..option(3, "Movie.2006.1080p.WEB-OTHER.srt", 10)
+                },
+                SubtitleDownloadOption {
+                    hash_match: true,
+                    ..option(4, "Movie.2006.DVDRip.srt", 0)
+                },
+                // The release name fits better than the file name
+                SubtitleDownloadOption {
+                    metadata: SubtitleMetadata {
+                        release: Some("Movie 2006 720p BluRay-GROUP".to_string()),
+                        ..Default::default()
+                    },
+                    ..option(5, "movie_en.srt", 0)
+                },
+            ],
+            "Movie.2006.720p.BluRay-GROUP",
+        );
+
+        let ids: Vec<_> = ranked.iter().map(|option| option.id).collect();
+        assert_eq!(ids, [4, 2, 5, 3, 1]);
+    }
+}
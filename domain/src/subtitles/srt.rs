@@ -0,0 +1,125 @@
+use super::track::{
+    Cue, ParseError, SubtitleFormat, SubtitleTrack, blocks, is_timing, parse_timing,
+    split_timestamp,
+};
+
+pub(super) fn parse(text: &str) -> Result<SubtitleTrack, ParseError> {
+    let cues = blocks(text)
+        .into_iter()
+        .map(|block| {
+            let mut lines = block.into_iter().peekable();
+
+            // The counter is optional, cues are numbered again when they're written
+            if lines.peek().is_some_and(|(_, line)| !is_timing(line)) {
+                let (line_no, _) = lines.next().expect("Block to have a line");
+                if lines.peek().is_none() {
+                    return Err(ParseError::InvalidCue { line: line_no });
+                }
+            }
+
+            let (start, end, _) = parse_timing(lines.next().expect("Block to have a line"))?;
+            let text = lines.map(|(_, line)| line).collect::<Vec<_>>().join("\n");
+
+            Ok(Cue {
+                id: None,
+                start,
+                end,
+                text,
+                settings: None,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(SubtitleTrack {
+        format: SubtitleFormat::Srt,
+        header: None,
+        cues,
+    })
+}
+
+pub(super) fn write(cues: impl Iterator<Item = Cue>) -> String {
+    let blocks: Vec<String> = cues
+        .enumerate()
+        .map(|(index, cue)| {
+            format!(
+                "{}\n{} --> {}\n{}",
+                index + 1,
+                timestamp(cue.start),
+                timestamp(cue.end),
+                cue.text
+            )
+        })
+        .collect();
+
+    format!("{}\n", blocks.join("\n\n"))
+}
+
+fn timestamp(timestamp: std::time::Duration) -> String {
+    let (hours, minutes, seconds, millis) = split_timestamp(timestamp);
+    format!("{hours:02}:{minutes:02}:{seconds:02},{millis:03}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::subtitles::{ParseError, Styling, SubtitleFormat, SubtitleTrack};
+
+    const SRT: &str = "1
+00:00:01,000 --> 00:00:02,500
+Hello there.
+
+2
+00:00:03,000 --> 01:00:04,040
+<i>General Kenobi!</i>
+You are a <b>bold</b> one.
+";
+
+    #[test]
+    fn test_round_trip() {
+        let track = SubtitleTrack::parse(SRT, SubtitleFormat::Srt).unwrap();
+
+        assert_eq!(track.cues.len(), 2);
+        assert_eq!(track.cues[0].start, Duration::from_secs(1));
+        assert_eq!(track.cues[0].end, Duration::from_millis(2_500));
+        assert_eq!(track.cues[1].end, Duration::from_millis(3_604_040));
+        assert_eq!(
+            track.cues[1].text,
+            "<i>General Kenobi!</i>\nYou are a <b>bold</b> one."
+        );
+        assert_eq!(
+            track.cues[1].plain_text(SubtitleFormat::Srt),
+            "General Kenobi!\nYou are a bold one."
+        );
+
+        assert_eq!(track.write(SubtitleFormat::Srt, Styling::Preserve), SRT);
+    }
+
+    #[test]
+    fn test_messy_input() {
+        // No counters, Windows line endings, a byte order mark, extra blank lines and sloppy
+        // timestamps
+        let track = SubtitleTrack::parse(
+            "\u{feff}00:00:01,000 --> 00:00:02,000\r\nOne\r\n\r\n\r\n0:0:3.0 -> 00:00:04:000\r\nTwo\r\n",
+            SubtitleFormat::Srt,
+        )
+        .unwrap();
+
+        assert_eq!(
+            track.write(SubtitleFormat::Srt, Styling::Preserve),
+            "1\n00:00:01,000 --> 00:00:02,000\nOne\n\n2\n00:00:03,000 --> 00:00:04,000\nTwo\n"
+        );
+    }
+
+    #[test]
+    fn test_invalid_timestamp() {
+        assert_eq!(
+            SubtitleTrack::parse("1\n00:00:01,000 --> soon\nHello", SubtitleFormat::Srt),
+            Err(ParseError::InvalidTimestamp { line: 2 })
+        );
+        assert_eq!(
+            SubtitleTrack::parse("1\n\n2\n00:00:01,000 --> 00:00:02,000", SubtitleFormat::Srt),
+            Err(ParseError::InvalidCue { line: 1 })
+        );
+    }
+}
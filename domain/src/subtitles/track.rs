@@ -0,0 +1,424 @@
+use std::time::Duration;
+
+use super::{ass, markup, srt, vtt};
+
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Hash)]
+pub enum SubtitleFormat {
+    Srt,
+    WebVtt,
+    /// Advanced SubStation Alpha, and the older SubStation Alpha it extends
+    Ass,
+}
+
+impl SubtitleFormat {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "srt" => Some(Self::Srt),
+            "vtt" => Some(Self::WebVtt),
+            "ass" | "ssa" => Some(Self::Ass),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Srt => "srt",
+            Self::WebVtt => "vtt",
+            Self::Ass => "ass",
+        }
+    }
+}
+
+/// What to do with italics, colors, positioning and the like when writing a track
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+pub enum Styling {
+    /// Kept as is in the same format. Bold, italic and underline survive a conversion to another
+    /// format, the rest is dropped.
+    #[default]
+    Preserve,
+    Strip,
+}
+
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct Framerate {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl Framerate {
+    /// 23.976 fps
+    pub const FILM: Self = Self::new(24_000, 1_001);
+    pub const CINEMA: Self = Self::new(24, 1);
+    pub const PAL: Self = Self::new(25, 1);
+    /// 29.97 fps
+    pub const NTSC: Self = Self::new(30_000, 1_001);
+
+    pub const fn new(numerator: u32, denominator: u32) -> Self {
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.numerator > 0 && self.denominator > 0
+    }
+
+    /// Providers round rates like 23.976, those are matched to the exact rate they stand for
+    pub fn from_fps(fps: f64) -> Option<Self> {
+        if !fps.is_finite() || fps <= 0.0 {
+            return None;
+        }
+
+        let known = [Self::FILM, Self::CINEMA, Self::PAL, Self::NTSC]
+            .into_iter()
+            .find(|framerate| (framerate.fps() - fps).abs() < 0.01);
+        Some(known.unwrap_or(Self::new((fps * 1000.0).round() as u32, 1000)))
+    }
+
+    pub fn fps(&self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+}
+
+/// For subtitles that were timed for a release with a different framerate, e.g. a 25 fps PAL
+/// release of a 23.976 fps movie
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct FramerateCorrection {
+    pub subtitle: Framerate,
+    pub video: Framerate,
+}
+
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+pub struct Retiming {
+    /// Added to every cue after the framerate correction. Negative values show subtitles earlier.
+    pub offset_millis: i64,
+    pub framerate: Option<FramerateCorrection>,
+}
+
+impl Retiming {
+    pub fn is_valid(&self) -> bool {
+        self.framerate
+            .is_none_or(|framerate| framerate.subtitle.is_valid() && framerate.video.is_valid())
+    }
+
+    pub(super) fn apply(&self, timestamp: Duration) -> Option<Duration> {
+        let timestamp = match self.framerate {
+            // Cues are tied to frames, which last longer or shorter in the video
+            Some(FramerateCorrection { subtitle, video }) => {
+                let nanos =
+                    timestamp.as_nanos() * subtitle.numerator as u128 * video.denominator as u128
+                        / (subtitle.denominator as u128 * video.numerator as u128);
+                Duration::from_nanos(u64::try_from(nanos).ok()?)
+            }
+            None => timestamp,
+        };
+
+        let offset = Duration::from_millis(self.offset_millis.unsigned_abs());
+        match self.offset_millis.is_negative() {
+            true => timestamp.checked_sub(offset),
+            false => timestamp.checked_add(offset),
+        }
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct Cue {
+    /// WebVTT cue identifier. SRT cues are numbered when they're written.
+    pub id: Option<String>,
+    pub start: Duration,
+    pub end: Duration,
+    /// Lines are separated by `\n`. Markup is kept in the syntax of the format it was parsed from.
+    pub text: String,
+    /// WebVTT cue settings, or the fields of an ASS dialogue line other than the times and text
+    pub settings: Option<String>,
+}
+
+impl Cue {
+    /// Text without any markup. `format` is the one the cue was parsed from.
+    pub fn plain_text(&self, format: SubtitleFormat) -> String {
+        markup::plain_text(&self.text, format)
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct SubtitleTrack {
+    /// Format the track was parsed from
+    pub format: SubtitleFormat,
+    /// Everything before the first cue that's needed to write the track again in the same
+    /// format, like WebVTT style blocks or ASS script info and styles
+    pub header: Option<String>,
+    pub cues: Vec<Cue>,
+}
+
+impl SubtitleTrack {
+    pub fn parse(text: &str, format: SubtitleFormat) -> Result<Self, ParseError> {
+        // Byte order marks and Windows line endings are common in downloaded subtitles
+        let text = text
+            .trim_start_matches('\u{feff}')
+            .replace("\r\n", "\n")
+            .replace('\r', "\n");
+
+        match format {
+            SubtitleFormat::Srt => srt::parse(&text),
+            SubtitleFormat::WebVtt => vtt::parse(&text),
+            SubtitleFormat::Ass => ass::parse(&text),
+        }
+    }
+
+    /// Cues that would end before the video starts are dropped, ones that start before it are
+    /// cut
+    pub fn retime(&mut self, retiming: &Retiming) {
+        self.cues.retain_mut(|cue| {
+            let Some(end) = retiming.apply(cue.end).filter(|end| !end.is_zero()) else {
+                return false;
+            };
+            cue.start = retiming.apply(cue.start).unwrap_or_default();
+            cue.end = end;
+            true
+        });
+    }
+
+    /// The header and cue settings are only kept when `format` is the one the track was parsed
+    /// from
+    pub fn write(&self, format: SubtitleFormat, styling: Styling) -> String {
+        let same_format = format == self.format;
+        let header = self.header.as_deref().filter(|_| same_format);
+        let cues = self.cues.iter().map(|cue| Cue {
+            id: cue.id.clone().filter(|_| same_format),
+            text: markup::convert(&cue.text, self.format, format, styling),
+            settings: cue.settings.clone().filter(|_| same_format),
+            ..*cue
+        });
+
+        match format {
+            SubtitleFormat::Srt => srt::write(cues),
+            SubtitleFormat::WebVtt => vtt::write(header, cues),
+            SubtitleFormat::Ass => ass::write(header, cues),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// WebVTT files have to start with `WEBVTT`, ASS files need an `[Events]` section with a
+    /// `Format` line
+    MissingHeader,
+    /// Lines start from 1
+    InvalidTimestamp {
+        line: usize,
+    },
+    InvalidCue {
+        line: usize,
+    },
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::MissingHeader => write!(f, "Subtitle file has no valid header"),
+            ParseError::InvalidTimestamp { line } => write!(f, "Invalid timestamp at line {line}"),
+            ParseError::InvalidCue { line } => write!(f, "Invalid cue at line {line}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses `[hh:]mm:ss.fff`. The fraction can be separated with `,`, `.` or `:` and have any
+/// number of digits, ASS uses centiseconds. Spaces and missing leading zeros are tolerated.
+pub(super) fn parse_timestamp(timestamp: &str) -> Option<Duration> {
+    let timestamp: String = timestamp
+        .chars()
+        .filter(|char| !char.is_whitespace())
+        .collect();
+    let (time, fraction) = match timestamp.split_once(['.', ',']) {
+        Some((time, fraction)) => (time, fraction),
+        // `00:00:01:000`
+        None if timestamp.matches(':').count() == 3 => timestamp.rsplit_once(':')?,
+        None => (timestamp.as_str(), "0"),
+    };
+
+    let mut parts = time.rsplit(':');
+    let seconds: u64 = parts.next()?.parse().ok()?;
+    let minutes: u64 = parts.next()?.parse().ok()?;
+    let hours: u64 = match parts.next() {
+        Some(hours) => hours.parse().ok()?,
+        None => 0,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+
+    if fraction.is_empty() || !fraction.chars().all(|char| char.is_ascii_digit()) {
+        return None;
+    }
+    // Only the first 9 digits fit in nanoseconds
+    let fraction = &fraction[..fraction.len().min(9)];
+    let nanos = fraction.parse::<u32>().ok()? * 10u32.pow(9 - fraction.len() as u32);
+
+    Some(Duration::new(hours * 3600 + minutes * 60 + seconds, nanos))
+}
+
+/// `hh:mm:ss` and the milliseconds
+pub(super) fn split_timestamp(timestamp: Duration) -> (u64, u64, u64, u32) {
+    let seconds = timestamp.as_secs();
+    (
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        timestamp.subsec_millis(),
+    )
+}
+
+/// Splits text into blocks separated by blank lines. Every line comes with its line number.
+pub(super) fn blocks(text: &str) -> Vec<Vec<(usize, &str)>> {
+    let mut blocks = Vec::new();
+    let mut block = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            if !block.is_empty() {
+                blocks.push(std::mem::take(&mut block));
+            }
+            continue;
+        }
+        block.push((index + 1, line));
+    }
+    if !block.is_empty() {
+        blocks.push(block);
+    }
+
+    blocks
+}
+
+pub(super) fn is_timing(line: &str) -> bool {
+    line.contains("->")
+}
+
+/// Parses `start --> end`, returning anything that comes after the end time
+pub(super) fn parse_timing(
+    (line_no, line): (usize, &str),
+) -> Result<(Duration, Duration, &str), ParseError> {
+    let invalid_timestamp = ParseError::InvalidTimestamp { line: line_no };
+
+    // Some tools write `->` or `--->`
+    let (start, rest) = line.split_once("->").ok_or(invalid_timestamp.clone())?;
+    let start = start.trim_end_matches('-');
+    let rest = rest.trim_start_matches('>').trim_start();
+    let (end, rest) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+
+    let start = parse_timestamp(start).ok_or(invalid_timestamp.clone())?;
+    let end = parse_timestamp(end).ok_or(invalid_timestamp)?;
+
+    Ok((start, end, rest.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{
+        Framerate, FramerateCorrection, Retiming, Styling, SubtitleFormat, SubtitleTrack,
+        parse_timestamp,
+    };
+
+    #[test]
+    fn test_convert_between_formats() {
+        let srt = "1\n00:00:01,000 --> 00:00:02,500\n<i>Hello</i> & goodbye\nSecond line\n";
+        let track = SubtitleTrack::parse(srt, SubtitleFormat::Srt).unwrap();
+
+        let vtt = track.write(SubtitleFormat::WebVtt, Styling::Preserve);
+        assert_eq!(
+            vtt,
+            "WEBVTT\n\n00:00:01.000 --> 00:00:02.500\n<i>Hello</i> &amp; goodbye\nSecond line\n"
+        );
+
+        let ass = track.write(SubtitleFormat::Ass, Styling::Preserve);
+        assert!(ass.ends_with(
+            "Dialogue: 0,0:00:01.00,0:00:02.50,Default,,0,0,0,,{\\i1}Hello{\\i0} & goodbye\\NSecond line\n"
+        ));
+
+        // Converting back gives the same cues
+        for (text, format) in [(vtt, SubtitleFormat::WebVtt), (ass, SubtitleFormat::Ass)] {
+            let converted = SubtitleTrack::parse(&text, format).unwrap();
+            assert_eq!(converted.write(SubtitleFormat::Srt, Styling::Preserve), srt);
+        }
+
+        assert_eq!(
+            track.write(SubtitleFormat::Srt, Styling::Strip),
+            "1\n00:00:01,000 --> 00:00:02,500\nHello & goodbye\nSecond line\n"
+        );
+    }
+
+    #[test]
+    fn test_retime() {
+        let srt = "1\n00:00:00,500 --> 00:00:01,000\nGone\n\n2\n00:00:01,000 --> 00:00:03,000\nCut\n\n3\n00:01:00,000 --> 00:01:02,000\nShifted\n";
+        let mut track = SubtitleTrack::parse(srt, SubtitleFormat::Srt).unwrap();
+
+        track.retime(&Retiming {
+            offset_millis: -1_500,
+            framerate: None,
+        });
+        assert_eq!(
+            track.write(SubtitleFormat::Srt, Styling::Preserve),
+            "1\n00:00:00,000 --> 00:00:01,500\nCut\n\n2\n00:00:58,500 --> 00:01:00,500\nShifted\n"
+        );
+
+        // 25 fps subtitles on a 23.976 fps video show up later
+        let mut track = SubtitleTrack::parse(srt, SubtitleFormat::Srt).unwrap();
+        track.retime(&Retiming {
+            offset_millis: 0,
+            framerate: Some(FramerateCorrection {
+                subtitle: Framerate::PAL,
+                video: Framerate::FILM,
+            }),
+        });
+        assert_eq!(track.cues[2].start, Duration::from_micros(62_562_500));
+    }
+
+    #[test]
+    fn test_framerate_from_fps() {
+        assert_eq!(Framerate::from_fps(23.976), Some(Framerate::FILM));
+        assert_eq!(Framerate::from_fps(23.98), Some(Framerate::FILM));
+        assert_eq!(Framerate::from_fps(24.0), Some(Framerate::CINEMA));
+        assert_eq!(
+            Framerate::from_fps(50.0),
+            Some(Framerate::new(50_000, 1000))
+        );
+        assert_eq!(Framerate::from_fps(0.0), None);
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(
+            parse_timestamp("01:02:03,456"),
+            Some(Duration::from_millis(3_723_456))
+        );
+        assert_eq!(
+            parse_timestamp("02:03.456"),
+            Some(Duration::from_millis(123_456))
+        );
+        assert_eq!(
+            parse_timestamp("0:00:01.50"),
+            Some(Duration::from_millis(1_500))
+        );
+        // Common mistakes
+        assert_eq!(
+            parse_timestamp("0:1:2.5"),
+            Some(Duration::from_millis(62_500))
+        );
+        assert_eq!(
+            parse_timestamp(" 00:00:01 , 500"),
+            Some(Duration::from_millis(1_500))
+        );
+        assert_eq!(
+            parse_timestamp("00:00:01:250"),
+            Some(Duration::from_millis(1_250))
+        );
+
+        assert_eq!(parse_timestamp("12"), None);
+        assert_eq!(parse_timestamp("00:01:02."), None);
+        assert_eq!(parse_timestamp("00:aa:02.000"), None);
+    }
+}
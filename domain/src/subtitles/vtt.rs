@@ -0,0 +1,173 @@
+use super::track::{
+    Cue, ParseError, SubtitleFormat, SubtitleTrack, blocks, is_timing, parse_timing,
+    split_timestamp,
+};
+
+/// Blocks that can only appear before the first cue
+const HEADER_BLOCKS: [&str; 3] = ["NOTE", "STYLE", "REGION"];
+
+pub(super) fn parse(text: &str) -> Result<SubtitleTrack, ParseError> {
+    let mut blocks = blocks(text).into_iter();
+
+    let header_block = blocks.next().ok_or(ParseError::MissingHeader)?;
+    let is_valid_header = header_block.first().is_some_and(|(_, line)| {
+        line.strip_prefix("WEBVTT")
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t']))
+    });
+    if !is_valid_header {
+        return Err(ParseError::MissingHeader);
+    }
+
+    let mut header = vec![join(&header_block)];
+    let mut cues = Vec::new();
+
+    for block in blocks {
+        let (first_line_no, first_line) = block[0];
+        let is_header_block = HEADER_BLOCKS.iter().any(|name| {
+            first_line
+                .strip_prefix(name)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t']))
+        });
+
+        if is_header_block {
+            // Comments between cues are dropped
+            if cues.is_empty() {
+                header.push(join(&block));
+            }
+            continue;
+        }
+
+        let mut lines = block.into_iter();
+        let id = match is_timing(first_line) {
+            true => None,
+            false => {
+                lines.next();
+                Some(first_line.to_string())
+            }
+        };
+
+        let timing = lines.next().ok_or(ParseError::InvalidCue {
+            line: first_line_no,
+        })?;
+        let (start, end, settings) = parse_timing(timing)?;
+        let text = lines.map(|(_, line)| line).collect::<Vec<_>>().join("\n");
+
+        cues.push(Cue {
+            id,
+            start,
+            end,
+            text,
+            settings: (!settings.is_empty()).then(|| settings.to_string()),
+        });
+    }
+
+    Ok(SubtitleTrack {
+        format: SubtitleFormat::WebVtt,
+        header: Some(header.join("\n\n")),
+        cues,
+    })
+}
+
+pub(super) fn write(header: Option<&str>, cues: impl Iterator<Item = Cue>) -> String {
+    let blocks: Vec<String> = std::iter::once(header.unwrap_or("WEBVTT").to_string())
+        .chain(cues.map(|cue| {
+            let id = cue.id.map(|id| format!("{id}\n")).unwrap_or_default();
+            let settings = cue
+                .settings
+                .map(|settings| format!(" {settings}"))
+                .unwrap_or_default();
+
+            format!(
+                "{id}{} --> {}{settings}\n{}",
+                timestamp(cue.start),
+                timestamp(cue.end),
+                cue.text
+            )
+        }))
+        .collect();
+
+    format!("{}\n", blocks.join("\n\n"))
+}
+
+fn join(block: &[(usize, &str)]) -> String {
+    block
+        .iter()
+        .map(|(_, line)| *line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn timestamp(timestamp: std::time::Duration) -> String {
+    let (hours, minutes, seconds, millis) = split_timestamp(timestamp);
+    format!("{hours:02}:{minutes:02}:{seconds:02}.{millis:03}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::subtitles::{ParseError, Styling, SubtitleFormat, SubtitleTrack};
+
+    const VTT: &str = "WEBVTT - Some title
+
+STYLE
+::cue {
+  color: yellow;
+}
+
+intro
+00:00:01.000 --> 00:00:02.500 align:start line:10%
+<v Obi-Wan>Hello there.
+
+00:00:03.000 --> 01:00:04.040
+<i>General Kenobi!</i> &amp; friends
+";
+
+    #[test]
+    fn test_round_trip() {
+        let track = SubtitleTrack::parse(VTT, SubtitleFormat::WebVtt).unwrap();
+
+        assert_eq!(track.cues.len(), 2);
+        assert_eq!(track.cues[0].id.as_deref(), Some("intro"));
+        assert_eq!(
+            track.cues[0].settings.as_deref(),
+            Some("align:start line:10%")
+        );
+        assert_eq!(track.cues[1].start, Duration::from_secs(3));
+        assert_eq!(
+            track.cues[1].plain_text(SubtitleFormat::WebVtt),
+            "General Kenobi! & friends"
+        );
+
+        assert_eq!(track.write(SubtitleFormat::WebVtt, Styling::Preserve), VTT);
+    }
+
+    #[test]
+    fn test_short_timestamps_and_notes() {
+        let track = SubtitleTrack::parse(
+            "WEBVTT\n\n00:01.000 --> 00:02.000\nOne\n\nNOTE dropped\n\n00:03.000 --> 00:04.000\nTwo",
+            SubtitleFormat::WebVtt,
+        )
+        .unwrap();
+
+        assert_eq!(
+            track.write(SubtitleFormat::WebVtt, Styling::Preserve),
+            "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\nOne\n\n00:00:03.000 --> 00:00:04.000\nTwo\n"
+        );
+    }
+
+    #[test]
+    fn test_missing_header() {
+        assert_eq!(
+            SubtitleTrack::parse("00:01.000 --> 00:02.000\nOne", SubtitleFormat::WebVtt),
+            Err(ParseError::MissingHeader)
+        );
+        assert_eq!(
+            SubtitleTrack::parse(
+                "WEBVTTX\n\n00:01.000 --> 00:02.000\nOne",
+                SubtitleFormat::WebVtt
+            ),
+            Err(ParseError::MissingHeader)
+        );
+    }
+}
@@ -47,6 +47,7 @@ impl TrackExt for domain::Track {
                 id,
                 language,
                 external_id,
+                ..
             } => TrackSelection::Subtitle {
                 input_path: path,
                 track_id: id,
@@ -334,6 +335,7 @@ mod tests {
                 },
                 Track::Subtitle {
                     id: 2,
+                    codec: "mov_text".to_string(),
                     language: Some(LanguageCode::English),
                     external_id: None,
                 },
@@ -378,16 +380,19 @@ mod tests {
                 },
                 Track::Subtitle {
                     id: 2,
+                    codec: "mov_text".to_string(),
                     language: Some(LanguageCode::English),
                     external_id: Some("sub_ext_001".to_string()),
                 },
                 Track::Subtitle {
                     id: 3,
+                    codec: "mov_text".to_string(),
                     language: Some(LanguageCode::French),
                     external_id: Some("sub_ext_002".to_string()),
                 },
                 Track::Subtitle {
                     id: 4,
+                    codec: "mov_text".to_string(),
                     language: None,
                     external_id: Some("sub_ext_003".to_string()),
                 },
@@ -487,11 +492,13 @@ mod tests {
                 },
                 Track::Subtitle {
                     id: 2,
+                    codec: "mov_text".to_string(),
                     language: Some(LanguageCode::English),
                     external_id: Some("ext_eng_001".to_string()),
                 },
                 Track::Subtitle {
                     id: 3,
+                    codec: "mov_text".to_string(),
                     language: Some(LanguageCode::French),
                     external_id: Some("ext_fra_002".to_string()),
                 },
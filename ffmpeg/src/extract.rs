@@ -1,19 +1,33 @@
+use domain::subtitles::SubtitleCodecKind;
+
+/// Writes each subtitle track to its output in a way that suits its kind. Text becomes SRT, ASS
+/// stays ASS and bitmaps are copied into a Matroska file.
 pub async fn extract_tracks(
     media_path: String,
-    track_mapping: Vec<(usize, String)>,
+    track_mapping: Vec<(usize, SubtitleCodecKind, String)>,
 ) -> super::Result<()> {
     let input_args = ["-i".to_string(), media_path];
-    let mapping_args = track_mapping.into_iter().flat_map(|(id, output)| {
+    let mapping_args = track_mapping.into_iter().flat_map(|(id, kind, output)| {
+        let (codec, format) = match kind {
+            SubtitleCodecKind::Text => ("srt", None),
+            SubtitleCodecKind::Styled => ("ass", None),
+            // Muxer can't be guessed from `.mks`
+            SubtitleCodecKind::Bitmap => ("copy", Some("matroska")),
+        };
+
         [
             // Select the track
-            "-map".to_string(),
-            format!("0:{id}"),
-            // Use the `text` codec
-            "-c:s".to_string(),
-            "text".to_string(),
+            Some("-map".to_string()),
+            Some(format!("0:{id}")),
+            Some("-c:s".to_string()),
+            Some(codec.to_string()),
+            format.map(|_| "-f".to_string()),
+            format.map(str::to_string),
             // Output
-            output,
+            Some(output),
         ]
+        .into_iter()
+        .flatten()
     });
 
     let args = input_args
@@ -31,6 +45,8 @@ pub async fn extract_tracks(
 mod tests {
     use std::path::PathBuf;
 
+    use domain::subtitles::SubtitleCodecKind;
+
     use crate::extract_tracks;
 
     fn fixtures_path() -> PathBuf {
@@ -45,7 +61,7 @@ mod tests {
 
         extract_tracks(
             input.to_string_lossy().to_string(),
-            vec![(3, output.clone())],
+            vec![(3, SubtitleCodecKind::Text, output.clone())],
         )
         .await
         .unwrap();
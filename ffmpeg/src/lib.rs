@@ -1,12 +1,16 @@
 mod encode;
 mod extract;
 mod spawn;
+mod thumbnail;
 mod track;
+mod voice;
 
 pub use encode::{TrackExt, TrackSelection, encode_video};
 
 pub use extract::extract_tracks;
+pub use thumbnail::{SpriteSheet, extract_frame, generate_sprite_sheet, get_duration};
 pub use track::get_tracks;
+pub use voice::detect_voice_activity;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -19,7 +23,7 @@ pub enum Error {
     #[error("ffmpeg/ffprobe produced unexpected output: '{0}'")]
     UnexpectedOutput(String),
     #[error("Couldn't get tracks: '{0}'")]
-    CouldntGetTracks(track::dto::Error),
+    CouldntGetTracks(Box<track::dto::Error>),
 }
 
 pub type Result<T> = core::result::Result<T, Error>;
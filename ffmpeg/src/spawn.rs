@@ -1,5 +1,6 @@
 use log::debug;
 use std::{ffi::OsStr, process::Stdio};
+use tokio::io::AsyncReadExt;
 
 /// Run `ffmpeg`
 ///
@@ -70,3 +71,50 @@ pub(super) async fn ffprobe(
 
     Ok(result.stdout.into_iter().map(|byte| byte as char).collect())
 }
+
+/// Run `ffmpeg` and hand its output to `read` as it arrives
+///
+/// Used for outputs that are too large to keep in memory, like decoded audio.
+///
+/// # Errors
+/// Same as [`ffmpeg`].
+pub(super) async fn ffmpeg_stream(
+    args: impl IntoIterator<Item = impl AsRef<OsStr>> + std::fmt::Debug,
+    mut read: impl FnMut(&[u8]),
+) -> crate::Result<()> {
+    debug!("Calling ffmpeg with: {args:#?}");
+
+    let mut child = tokio::process::Command::new("ffmpeg")
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|err| crate::Error::CouldntSpawn(err.to_string()))?;
+
+    let mut stdout = child.stdout.take().ok_or(crate::Error::MissingOutput)?;
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read_count = stdout
+            .read(&mut buffer)
+            .await
+            .map_err(|err| crate::Error::UnexpectedOutput(err.to_string()))?;
+        if read_count == 0 {
+            break;
+        }
+        read(&buffer[..read_count]);
+    }
+
+    let result = child
+        .wait_with_output()
+        .await
+        .map_err(|err| crate::Error::CouldntSpawn(err.to_string()))?;
+
+    if !result.status.success() {
+        let message: String = result.stderr.into_iter().map(|byte| byte as char).collect();
+
+        return Err(crate::Error::NonZeroExit(message));
+    }
+
+    Ok(())
+}
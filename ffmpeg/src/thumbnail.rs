@@ -0,0 +1,222 @@
+use std::{path::Path, time::Duration};
+
+use crate::spawn::{ffmpeg, ffprobe};
+
+/// Width of a representative frame, height follows the aspect ratio
+const FRAME_WIDTH: u32 = 640;
+
+/// Layout of a seek preview sprite sheet. Tiles are laid out row by row, one every `interval`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpriteSheet {
+    pub interval: Duration,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub columns: u32,
+    pub rows: u32,
+}
+
+impl SpriteSheet {
+    const MIN_INTERVAL: Duration = Duration::from_secs(10);
+    /// Keeps the sheet small enough to load quickly for long movies
+    const MAX_TILES: u32 = 200;
+    const COLUMNS: u32 = 10;
+
+    pub fn for_duration(duration: Duration) -> Self {
+        let interval = Self::MIN_INTERVAL.max(Duration::from_secs(
+            duration.as_secs().div_ceil(Self::MAX_TILES as u64),
+        ));
+        let tiles = (duration.as_secs().div_ceil(interval.as_secs()) as u32).max(1);
+
+        Self {
+            interval,
+            tile_width: 160,
+            tile_height: 90,
+            columns: Self::COLUMNS.min(tiles),
+            rows: tiles.div_ceil(Self::COLUMNS),
+        }
+    }
+
+    /// WebVTT thumbnail track with one cue per tile, pointing at the tile with a media fragment.
+    /// `sprite_sheet_url` is relative to the track.
+    pub fn to_webvtt(&self, sprite_sheet_url: &str, duration: Duration) -> String {
+        let mut track = String::from("WEBVTT\n");
+
+        let mut start = Duration::ZERO;
+        let mut tile = 0;
+        while start < duration && tile < self.columns * self.rows {
+            let end = (start + self.interval).min(duration);
+            let x = (tile % self.columns) * self.tile_width;
+            let y = (tile / self.columns) * self.tile_height;
+
+            track.push_str(&format!(
+                "\n{} --> {}\n{sprite_sheet_url}#xywh={x},{y},{},{}\n",
+                format_timestamp(start),
+                format_timestamp(end),
+                self.tile_width,
+                self.tile_height,
+            ));
+
+            start = end;
+            tile += 1;
+        }
+
+        track
+    }
+}
+
+fn format_timestamp(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+/// Duration of the whole file rather than a single track
+pub async fn get_duration(media_file: impl AsRef<Path>) -> crate::Result<Duration> {
+    let result_string = ffprobe([
+        "-v",
+        "error",
+        "-show_entries",
+        "format=duration",
+        // Print only the value
+        "-of",
+        "default=noprint_wrappers=1:nokey=1",
+        &media_file.as_ref().as_os_str().to_string_lossy(),
+    ])
+    .await?;
+
+    result_string
+        .trim()
+        .parse::<f64>()
+        .map(Duration::from_secs_f64)
+        .map_err(|_| crate::Error::UnexpectedOutput(result_string))
+}
+
+/// Saves the frame at `at` as a JPEG
+pub async fn extract_frame(
+    media_file: impl AsRef<Path>,
+    at: Duration,
+    output: impl AsRef<Path>,
+) -> crate::Result<()> {
+    ffmpeg([
+        // Seeking before the input is a lot faster
+        "-ss".to_string(),
+        format!("{:.3}", at.as_secs_f64()),
+        "-i".to_string(),
+        media_file.as_ref().to_string_lossy().to_string(),
+        "-frames:v".to_string(),
+        "1".to_string(),
+        "-vf".to_string(),
+        format!("scale={FRAME_WIDTH}:-2"),
+        "-y".to_string(),
+        output.as_ref().to_string_lossy().to_string(),
+    ])
+    .await?;
+
+    Ok(())
+}
+
+/// Renders a single JPEG with a tile every `sprite_sheet.interval`
+pub async fn generate_sprite_sheet(
+    media_file: impl AsRef<Path>,
+    sprite_sheet: &SpriteSheet,
+    output: impl AsRef<Path>,
+) -> crate::Result<()> {
+    let SpriteSheet {
+        interval,
+        tile_width,
+        tile_height,
+        columns,
+        rows,
+    } = sprite_sheet;
+
+    // Letterbox every frame so tiles line up with the WebVTT track
+    let filter = format!(
+        "fps=1/{},scale={tile_width}:{tile_height}:force_original_aspect_ratio=decrease,pad={tile_width}:{tile_height}:(ow-iw)/2:(oh-ih)/2,tile={columns}x{rows}",
+        interval.as_secs()
+    );
+
+    ffmpeg([
+        "-i".to_string(),
+        media_file.as_ref().to_string_lossy().to_string(),
+        "-vf".to_string(),
+        filter,
+        "-an".to_string(),
+        "-frames:v".to_string(),
+        "1".to_string(),
+        "-y".to_string(),
+        output.as_ref().to_string_lossy().to_string(),
+    ])
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, time::Duration};
+
+    use super::{SpriteSheet, extract_frame, generate_sprite_sheet, get_duration};
+
+    fn fixtures_path() -> PathBuf {
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures").into()
+    }
+
+    #[test]
+    fn test_sprite_sheet_layout() {
+        let short = SpriteSheet::for_duration(Duration::from_secs(25));
+        assert_eq!(short.interval, Duration::from_secs(10));
+        assert_eq!((short.columns, short.rows), (3, 1));
+
+        // Two hours can't fit 10 second tiles
+        let long = SpriteSheet::for_duration(Duration::from_secs(2 * 60 * 60));
+        assert_eq!(long.interval, Duration::from_secs(36));
+        assert_eq!((long.columns, long.rows), (10, 20));
+    }
+
+    #[test]
+    fn test_webvtt() {
+        let duration = Duration::from_millis(25_500);
+        let track = SpriteSheet::for_duration(duration).to_webvtt("sprite.jpg", duration);
+
+        assert_eq!(
+            track,
+            "WEBVTT
+
+00:00:00.000 --> 00:00:10.000
+sprite.jpg#xywh=0,0,160,90
+
+00:00:10.000 --> 00:00:20.000
+sprite.jpg#xywh=160,0,160,90
+
+00:00:20.000 --> 00:00:25.500
+sprite.jpg#xywh=320,0,160,90
+"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_thumbnails() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = fixtures_path().join("h264_aac_nosub.mp4");
+
+        let duration = get_duration(&input).await.unwrap();
+        assert_eq!(duration.as_secs(), 2);
+
+        let frame = dir.path().join("frame.jpg");
+        extract_frame(&input, Duration::from_secs(1), &frame)
+            .await
+            .unwrap();
+        assert!(frame.exists());
+
+        let sprite = dir.path().join("sprite.jpg");
+        generate_sprite_sheet(&input, &SpriteSheet::for_duration(duration), &sprite)
+            .await
+            .unwrap();
+        assert!(sprite.exists());
+    }
+}
@@ -46,7 +46,7 @@ pub mod dto {
 
     impl From<Error> for crate::Error {
         fn from(val: Error) -> Self {
-            crate::Error::CouldntGetTracks(val)
+            crate::Error::CouldntGetTracks(Box::new(val))
         }
     }
 
@@ -102,6 +102,10 @@ pub mod dto {
                 }),
                 "subtitle" => Ok(domain::Track::Subtitle {
                     id: self.index,
+                    codec: self
+                        .codec_name
+                        .clone()
+                        .ok_or_else(|| Error::NoCodec(self.clone()))?,
                     language: self.tags.clone().and_then(|tags| tags.language).and_then(
                         |tag_string| {
                             domain::language::LanguageCode::try_from(tag_string.as_str()).ok()
@@ -192,6 +196,7 @@ mod tests {
                     },
                     Track::Subtitle {
                         id: 2,
+                        codec: "mov_text".to_string(),
                         language: Some(LanguageCode::English),
                         external_id: None,
                     },
@@ -223,16 +228,19 @@ mod tests {
                     },
                     Track::Subtitle {
                         id: 2,
+                        codec: "mov_text".to_string(),
                         language: Some(LanguageCode::English),
                         external_id: Some("sub_ext_001".to_string()),
                     },
                     Track::Subtitle {
                         id: 3,
+                        codec: "mov_text".to_string(),
                         language: Some(LanguageCode::French),
                         external_id: Some("sub_ext_002".to_string()),
                     },
                     Track::Subtitle {
                         id: 4,
+                        codec: "mov_text".to_string(),
                         language: None,
                         external_id: Some("sub_ext_003".to_string()),
                     },
@@ -0,0 +1,172 @@
+//! Energy based voice activity detection.
+//!
+//! Audio is band passed to the range of speech, then every frame that is louder than the noise
+//! floor of the track by [`SPEECH_THRESHOLD_DB`] counts as speech. It can't tell speech from loud
+//! music or effects, which is good enough to line subtitles up with dialogue.
+
+use std::{path::Path, time::Duration};
+
+use domain::subtitles::align::VoiceActivity;
+
+use crate::spawn::ffmpeg_stream;
+
+const SAMPLE_RATE: u32 = 8_000;
+const FRAME: Duration = Duration::from_millis(10);
+const SAMPLES_PER_FRAME: usize = (SAMPLE_RATE / 100) as usize;
+const SPEECH_THRESHOLD_DB: f32 = 9.0;
+/// Quietest share of frames that make up the noise floor
+const NOISE_FLOOR_PERCENTILE: f32 = 0.1;
+/// Pauses between words shorter than this still count as speech
+const HANGOVER_FRAMES: usize = 20;
+
+/// Detects speech in the default audio track of `media_file`
+pub async fn detect_voice_activity(media_file: impl AsRef<Path>) -> crate::Result<VoiceActivity> {
+    let mut detector = Detector::default();
+
+    ffmpeg_stream(
+        [
+            "-nostdin".to_string(),
+            "-v".to_string(),
+            "error".to_string(),
+            "-i".to_string(),
+            media_file.as_ref().to_string_lossy().to_string(),
+            "-map".to_string(),
+            "0:a:0".to_string(),
+            "-ac".to_string(),
+            "1".to_string(),
+            "-ar".to_string(),
+            SAMPLE_RATE.to_string(),
+            "-af".to_string(),
+            "highpass=f=200,lowpass=f=3400".to_string(),
+            // Raw 16 bit samples to stdout
+            "-f".to_string(),
+            "s16le".to_string(),
+            "-".to_string(),
+        ],
+        |bytes| detector.push(bytes),
+    )
+    .await?;
+
+    if detector.energies.is_empty() {
+        return Err(crate::Error::MissingOutput);
+    }
+
+    Ok(detector.finish())
+}
+
+#[derive(Default)]
+struct Detector {
+    /// Loudness of each frame in dB
+    energies: Vec<f32>,
+    frame_sum: f64,
+    frame_samples: usize,
+    /// Low byte of a sample that was split between two reads
+    leftover: Option<u8>,
+}
+
+impl Detector {
+    fn push(&mut self, bytes: &[u8]) {
+        let mut bytes = bytes.iter().copied();
+
+        if let Some(low) = self.leftover.take() {
+            match bytes.next() {
+                Some(high) => self.push_sample(i16::from_le_bytes([low, high])),
+                None => {
+                    self.leftover = Some(low);
+                    return;
+                }
+            }
+        }
+
+        loop {
+            match (bytes.next(), bytes.next()) {
+                (Some(low), Some(high)) => self.push_sample(i16::from_le_bytes([low, high])),
+                (Some(low), None) => {
+                    self.leftover = Some(low);
+                    break;
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn push_sample(&mut self, sample: i16) {
+        let sample = f64::from(sample) / f64::from(i16::MAX);
+        self.frame_sum += sample * sample;
+        self.frame_samples += 1;
+
+        if self.frame_samples == SAMPLES_PER_FRAME {
+            let mean_square = self.frame_sum / SAMPLES_PER_FRAME as f64;
+            // Keeps digital silence from going to negative infinity
+            self.energies
+                .push((10.0 * (mean_square + 1e-10).log10()) as f32);
+            self.frame_sum = 0.0;
+            self.frame_samples = 0;
+        }
+    }
+
+    fn finish(self) -> VoiceActivity {
+        let noise_floor = {
+            let mut sorted = self.energies.clone();
+            sorted.sort_by(f32::total_cmp);
+            sorted[((sorted.len() - 1) as f32 * NOISE_FLOOR_PERCENTILE) as usize]
+        };
+
+        let mut frames = vec![false; self.energies.len()];
+        let mut hangover = 0;
+        for (frame, energy) in frames.iter_mut().zip(self.energies) {
+            if energy > noise_floor + SPEECH_THRESHOLD_DB {
+                *frame = true;
+                hangover = HANGOVER_FRAMES;
+            } else if hangover > 0 {
+                *frame = true;
+                hangover -= 1;
+            }
+        }
+
+        VoiceActivity {
+            frame: FRAME,
+            frames,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Detector, HANGOVER_FRAMES, SAMPLE_RATE, SAMPLES_PER_FRAME};
+
+    #[test]
+    fn detects_loud_frames() {
+        // A second of quiet noise with a tone between 0.3 and 0.5 seconds
+        let samples = (0..SAMPLE_RATE as usize).map(|index| {
+            let noise = if index % 2 == 0 { 30 } else { -30 };
+            let time = index as f32 / SAMPLE_RATE as f32;
+            match (0.3..0.5).contains(&time) {
+                true => (8_000.0 * (time * 440.0 * std::f32::consts::TAU).sin()) as i16,
+                false => noise,
+            }
+        });
+        let bytes: Vec<u8> = samples.flat_map(i16::to_le_bytes).collect();
+
+        let mut detector = Detector::default();
+        // Odd chunks split samples between reads
+        for chunk in bytes.chunks(333) {
+            detector.push(chunk);
+        }
+        let voice_activity = detector.finish();
+
+        assert_eq!(
+            voice_activity.frames.len(),
+            SAMPLE_RATE as usize / SAMPLES_PER_FRAME
+        );
+        let speech: Vec<usize> = voice_activity
+            .frames
+            .iter()
+            .enumerate()
+            .filter(|(_, is_speech)| **is_speech)
+            .map(|(index, _)| index)
+            .collect();
+        assert_eq!(speech.first(), Some(&30));
+        assert_eq!(speech.last(), Some(&(49 + HANGOVER_FRAMES)));
+    }
+}
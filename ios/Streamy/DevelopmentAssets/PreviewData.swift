@@ -1,5 +1,15 @@
 import SharedTypes
 
 class PreviewData {
+    static func download(id: String, title: String, progress: Float, needsFileMapping: Bool, state: DownloadState) -> DownloadSummary {
+        DownloadSummary(
+            download: Download(id: id, title: title, progress: progress, needs_file_mapping: needsFileMapping, state: state, queue_position: nil, size: .init(value: 1_400_000_000), download_speed: .init(value: 2_100_000), upload_speed: .init(value: 0), eta: .init(secs: 3840, nanos: 0), seeds: 12, added_on: .init(secs_since_epoch: 1_760_000_000, nanos_since_epoch: 0)),
+            size: "1.4 GB",
+            download_speed: "2.1 MB/s",
+            upload_speed: "0 B/s",
+            eta: "1h 4m"
+        )
+    }
+
     static let idiocracyMedia = Media(id: "idiocracy", metadata: .init(thumbnail: "https://www.themoviedb.org/t/p/w1280/k75tEyoPbPlfHSKakJBOR5dx1Dp.jpg", title: "Idiocracy"), content: .movie(.init(media: "", track_name: "", subtitles: [])))
 }
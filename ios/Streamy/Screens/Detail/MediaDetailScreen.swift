@@ -67,7 +67,7 @@ struct MediaDetailScreen: View {
         }
         .labelStyle(.titleAndIcon)
         .background {
-            AsyncImage(url: URL(string: media.metadata.thumbnail)) { image in
+            AsyncImage(url: URL(string: media.artwork_url ?? media.metadata.thumbnail)) { image in
                 image.image?
                     .resizable(resizingMode: .stretch)
                     .aspectRatio(contentMode: .fill)
@@ -58,7 +58,7 @@ struct ListScreen: View {
                     ForEach(filteredItems ?? [], id: \.id) { mediaItem in
                         NavigationLink(value: Screen.detail(mediaItem)) {
                             VStack(alignment: .leading) {
-                                AsyncImage(url: URL(string: mediaItem.metadata.thumbnail)) { image in
+                                AsyncImage(url: URL(string: mediaItem.artwork_url ?? mediaItem.metadata.thumbnail)) { image in
                                     image
                                         .resizable()
                                 } placeholder: {
@@ -3,7 +3,17 @@ import SwiftUI
 
 struct DownloadItem: View {
     @EnvironmentObject var core: Core
-    var data: Download
+    var summary: DownloadSummary
+
+    var data: Download {
+        summary.download
+    }
+
+    var details: String {
+        [summary.size, "↓ \(summary.download_speed)", summary.eta]
+            .compactMap { $0 }
+            .joined(separator: " · ")
+    }
 
     var body: some View {
         VStack(alignment: .leading) {
@@ -13,6 +23,9 @@ struct DownloadItem: View {
                 ProgressView(value: data.progress)
                 Text("\(String(format: "%.0f", data.progress * 100))%")
             }
+            Text(details)
+                .font(.caption)
+                .foregroundStyle(.secondary)
             if data.needs_file_mapping {
                 Button {
                     core.navigationObserver?.push(screen: .serverFileMapping(data.id))
@@ -80,10 +93,10 @@ struct DownloadStateBadge: View {
 #Preview {
     Form {
         DownloadItem(
-            data: Download(id: "24389729skjl", title: "Big Buck Bunny", progress: 0.6, needs_file_mapping: false, state: .inProgress),
+            summary: PreviewData.download(id: "24389729skjl", title: "Big Buck Bunny", progress: 0.6, needsFileMapping: false, state: .inProgress),
         )
         DownloadItem(
-            data: Download(id: "24389729skjl", title: "Big Buck Bunny", progress: 1.0, needs_file_mapping: true, state: .complete),
+            summary: PreviewData.download(id: "24389729skjl", title: "Big Buck Bunny", progress: 1.0, needsFileMapping: true, state: .complete),
         )
     }
 }
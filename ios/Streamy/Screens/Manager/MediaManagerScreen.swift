@@ -3,10 +3,10 @@ import SwiftUI
 
 struct MediaManagerScreen: View {
     @EnvironmentObject var core: Core
-    var overrideDownloads: [Download]?
+    var overrideDownloads: [DownloadSummary]?
     var overrideMediaItems: MediaItems?
 
-    var downloads: [Download] {
+    var downloads: [DownloadSummary] {
         overrideDownloads ?? core.view.downloads
     }
 
@@ -39,8 +39,8 @@ struct MediaManagerScreen: View {
                 NavigationLink(value: Screen.addDownload) {
                     Label("Download Media", systemImage: "square.and.arrow.down")
                 }
-                ForEach(downloads, id: \.id) { download in
-                    DownloadItem(data: download)
+                ForEach(downloads, id: \.download.id) { download in
+                    DownloadItem(summary: download)
                 }
             }
             Section {
@@ -75,9 +75,9 @@ struct MediaManagerScreen: View {
 #Preview {
     MediaManagerScreen(
         overrideDownloads: [
-            Download(id: "sdlkfjvs", title: "Big Buck Bunny", progress: 0.2, needs_file_mapping: true, state: .inProgress),
-            Download(id: "my movie", title: "My Movie", progress: 0.7, needs_file_mapping: true, state: .inProgress),
-            Download(id: "skjvlk", title: "Skibbidy Toilet", progress: 0.0, needs_file_mapping: false, state: .paused),
+            PreviewData.download(id: "sdlkfjvs", title: "Big Buck Bunny", progress: 0.2, needsFileMapping: true, state: .inProgress),
+            PreviewData.download(id: "my movie", title: "My Movie", progress: 0.7, needsFileMapping: true, state: .inProgress),
+            PreviewData.download(id: "skjvlk", title: "Skibbidy Toilet", progress: 0.0, needsFileMapping: false, state: .paused),
         ], overrideMediaItems: .success(data: ["Idiocracy": .init(id: "Idiocracy", metadata: .init(thumbnail: "https://www.themoviedb.org/t/p/w1280/k75tEyoPbPlfHSKakJBOR5dx1Dp.jpg", title: "Idiocracy"), content: .movie(.init(media: "", track_name: "", subtitles: [])))]),
     ).environmentObject(Core.shared)
 }
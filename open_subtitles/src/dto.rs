@@ -37,6 +37,9 @@ pub struct Attributes {
     pub nb_cd: usize,
     pub slug: String,
     pub machine_translated: bool,
+    /// Only sent when searching with a `moviehash`
+    #[serde(default)]
+    pub moviehash_match: bool,
     pub release: String,
     pub legacy_subtitle_id: Option<u64>,
     pub legacy_uploader_id: Option<u64>,
@@ -61,12 +64,56 @@ pub(super) struct DownloadResponse {
     pub link: String,
     pub file_name: String,
     pub requests: u32,
-    pub remaining: u32,
+    pub remaining: i64,
     pub message: String,
     pub reset_time: String,
     pub reset_time_utc: String,
 }
 
+/// Sent with a 406 once the quota is used up. `remaining` goes below 0 with every try after that.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct QuotaReachedResponse {
+    pub requests: u32,
+    pub remaining: i64,
+    pub message: String,
+    pub reset_time_utc: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct LoginForm<'a> {
+    pub username: &'a str,
+    pub password: &'a str,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct LoginResponse {
+    pub user: User,
+    /// Host to send the following requests to, VIP users get their own
+    pub base_url: String,
+    /// A JWT that's valid for 24 hours
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct User {
+    pub allowed_downloads: u32,
+    pub level: String,
+    pub user_id: u64,
+    pub vip: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct UserInfoResponse {
+    pub data: UserInfo,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct UserInfo {
+    pub allowed_downloads: u32,
+    pub downloads_count: u32,
+    pub remaining_downloads: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenSubtitlesError {
     pub status: Option<usize>,
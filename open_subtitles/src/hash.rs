@@ -0,0 +1,89 @@
+//! The OpenSubtitles file hash: the file size plus the sums of the first and last 64 KiB, read
+//! as little endian 64 bit words.
+
+use std::{io::SeekFrom, path::Path};
+
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+const CHUNK_SIZE: u64 = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MovieHash {
+    pub hash: u64,
+    pub size: u64,
+}
+
+impl std::fmt::Display for MovieHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.hash)
+    }
+}
+
+pub async fn movie_hash(path: &Path) -> std::io::Result<MovieHash> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let size = file.metadata().await?.len();
+    if size < CHUNK_SIZE * 2 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "File is too small to hash",
+        ));
+    }
+
+    let mut hash = size;
+    let mut chunk = vec![0; CHUNK_SIZE as usize];
+    for offset in [0, size - CHUNK_SIZE] {
+        file.seek(SeekFrom::Start(offset)).await?;
+        file.read_exact(&mut chunk).await?;
+        hash = chunk
+            .as_chunks::<8>()
+            .0
+            .iter()
+            .map(|word| u64::from_le_bytes(*word))
+            .fold(hash, u64::wrapping_add);
+    }
+
+    Ok(MovieHash { hash, size })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CHUNK_SIZE, movie_hash};
+
+    #[tokio::test]
+    async fn test_movie_hash() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("movie.mkv");
+        // First chunk is all 1s, last one all 2s, the middle isn't read
+        let mut contents = vec![0x01; CHUNK_SIZE as usize];
+        contents.extend(vec![0xff; 1000]);
+        contents.extend(vec![0x02; CHUNK_SIZE as usize]);
+        tokio::fs::write(&path, &contents).await.unwrap();
+
+        let hash = movie_hash(&path).await.unwrap();
+        assert_eq!(hash.to_string(), "60606060606263e8");
+        assert_eq!(hash.size, 132_072);
+
+        tokio::fs::write(&path, b"too small").await.unwrap();
+        assert!(movie_hash(&path).await.is_err());
+    }
+
+    /// The test file OpenSubtitles publishes along with its hash
+    #[tokio::test]
+    #[ignore = "downloads the reference file"]
+    async fn test_reference_movie_hash() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("breakdance.avi");
+        let contents = reqwest::get("http://www.opensubtitles.org/addons/avi/breakdance.avi")
+            .await
+            .and_then(|response| response.error_for_status())
+            .unwrap()
+            .bytes()
+            .await
+            .unwrap();
+        tokio::fs::write(&path, &contents).await.unwrap();
+
+        let hash = movie_hash(&path).await.unwrap();
+        assert_eq!(hash.to_string(), "8e245d9679d31e12");
+        assert_eq!(hash.size, 12_909_756);
+    }
+}
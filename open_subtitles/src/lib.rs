@@ -1,47 +1,239 @@
 mod dto;
-
-use std::{str::FromStr, sync::LazyLock};
+mod hash;
+mod quota;
+
+use std::{
+    path::Path,
+    str::FromStr,
+    sync::{Arc, LazyLock, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
 
 use domain::{
     series::EpisodeIdentifier,
-    subtitles::{SubtitleDownloadOption, SubtitleProvider},
+    subtitles::{
+        Framerate, SubtitleDownloadOption, SubtitleMetadata, SubtitleProvider, SubtitleQuota,
+    },
 };
-use log::info;
+use log::{info, warn};
+use reqwest::StatusCode;
 
-use dto::{DownloadForm, DownloadResponse, OpenSubtitlesError, OpenSubtitlesSubtitleResponse};
+use dto::{
+    DownloadForm, DownloadResponse, LoginForm, LoginResponse, OpenSubtitlesError,
+    OpenSubtitlesSubtitleResponse, QuotaReachedResponse, UserInfoResponse,
+};
+pub use hash::{MovieHash, movie_hash};
 
-const API_KEY: &str = include_str!("../../open_subtitles_api_key");
 static OPEN_SUBTITLES_BASE_URL: LazyLock<reqwest::Url> = LazyLock::new(|| {
     reqwest::Url::parse("https://api.opensubtitles.com/api/v1/")
         .expect("Open Subtitles base url should be valid")
 });
-static DEFAULT_HEADERS: LazyLock<reqwest::header::HeaderMap> = LazyLock::new(|| {
-    let mut headers = reqwest::header::HeaderMap::new();
-    headers.insert(
-        reqwest::header::USER_AGENT,
-        "Streamy v0.0.1".parse().unwrap(),
-    );
-    headers.insert("Api-Key", API_KEY.trim_end().parse().unwrap());
-    headers.insert(reqwest::header::ACCEPT, "application/json".parse().unwrap());
-    headers
-});
+/// Tokens are valid for 24 hours, they're renewed a bit before that
+const TOKEN_LIFETIME: Duration = Duration::from_secs(23 * 60 * 60);
 
-#[derive(Debug, Clone)]
-pub struct OpenSubtitlesClient {
-    http_client: reqwest::Client,
+#[derive(Clone)]
+struct Credentials {
+    username: String,
+    password: String,
 }
 
-impl Default for OpenSubtitlesClient {
-    fn default() -> Self {
-        Self::new()
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .finish_non_exhaustive()
     }
 }
 
+#[derive(Debug, Clone)]
+struct Session {
+    token: String,
+    base_url: reqwest::Url,
+    logged_in_at: Instant,
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenSubtitlesClient {
+    http_client: reqwest::Client,
+    api_key: String,
+    credentials: Option<Credentials>,
+    /// Shared by clones, so concurrent downloads log in once
+    session: Arc<tokio::sync::Mutex<Option<Session>>>,
+    quota: Arc<Mutex<Option<SubtitleQuota>>>,
+}
+
 impl OpenSubtitlesClient {
-    pub fn new() -> Self {
+    pub fn new(api_key: impl Into<String>) -> Self {
         Self {
             http_client: reqwest::Client::new(),
+            api_key: api_key.into(),
+            credentials: None,
+            session: Default::default(),
+            quota: Default::default(),
+        }
+    }
+
+    /// Downloads count against the user's quota instead of the anonymous one of the API key
+    pub fn with_login(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.credentials = Some(Credentials {
+            username: username.into(),
+            password: password.into(),
+        });
+        self
+    }
+
+    pub fn is_logged_in(&self) -> bool {
+        self.credentials.is_some()
+    }
+
+    /// The quota from the last download, without asking the API
+    pub fn known_quota(&self) -> Option<SubtitleQuota> {
+        self.quota
+            .lock()
+            .expect("Quota lock to not be poisoned")
+            .clone()
+    }
+
+    /// Asks the API for the quota of the user if nothing was downloaded since it last reset.
+    /// Anonymous quotas are only known after a download.
+    pub async fn quota(&self) -> Result<Option<SubtitleQuota>> {
+        let known_quota = self.known_quota();
+        if let Some(quota) = &known_quota
+            && !quota::is_stale(quota, SystemTime::now())
+        {
+            return Ok(known_quota);
+        }
+        let Some(session) = self.session().await? else {
+            return Ok(known_quota);
+        };
+
+        let user_info_string = self
+            .http_client
+            .get(session.base_url.join("infos/user")?)
+            .headers(self.headers()?)
+            .bearer_auth(&session.token)
+            .send()
+            .await?
+            .text()
+            .await?;
+
+        Self::check_api_error(&user_info_string)?;
+
+        let user_info: UserInfoResponse = serde_json::from_str(&user_info_string)?;
+        // The user info doesn't say when the quota resets
+        let quota = quota::quota(
+            user_info.data.remaining_downloads,
+            Some(user_info.data.allowed_downloads),
+            None,
+            SystemTime::now(),
+        );
+        self.set_quota(quota.clone());
+
+        Ok(Some(quota))
+    }
+
+    fn set_quota(&self, quota: SubtitleQuota) {
+        *self.quota.lock().expect("Quota lock to not be poisoned") = Some(quota);
+    }
+
+    fn headers(&self) -> Result<reqwest::header::HeaderMap> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            reqwest::header::USER_AGENT,
+            reqwest::header::HeaderValue::from_static("Streamy v0.0.1"),
+        );
+        headers.insert("Api-Key", self.api_key.trim().parse()?);
+        headers.insert(
+            reqwest::header::ACCEPT,
+            reqwest::header::HeaderValue::from_static("application/json"),
+        );
+        Ok(headers)
+    }
+
+    /// Logs in if there are credentials and the last token is about to expire
+    async fn session(&self) -> Result<Option<Session>> {
+        let Some(credentials) = &self.credentials else {
+            return Ok(None);
+        };
+
+        let mut session = self.session.lock().await;
+        if let Some(session) = session.as_ref()
+            && session.logged_in_at.elapsed() < TOKEN_LIFETIME
+        {
+            return Ok(Some(session.clone()));
         }
+
+        let login_form = LoginForm {
+            username: &credentials.username,
+            password: &credentials.password,
+        };
+        let login_response_string = self
+            .http_client
+            .post(OPEN_SUBTITLES_BASE_URL.join("login")?)
+            .headers(self.headers()?)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_string(&login_form)?)
+            .send()
+            .await?
+            .text()
+            .await?;
+
+        Self::check_api_error(&login_response_string)?;
+
+        let login_response: LoginResponse = serde_json::from_str(&login_response_string)?;
+        info!(
+            "Open Subtitles: Logged in as {}, {} downloads a day",
+            credentials.username, login_response.user.allowed_downloads
+        );
+
+        let new_session = Session {
+            token: login_response.token,
+            base_url: reqwest::Url::parse(&format!("https://{}/api/v1/", login_response.base_url))?,
+            logged_in_at: Instant::now(),
+        };
+        *session = Some(new_session.clone());
+
+        Ok(Some(new_session))
+    }
+
+    async fn request_download_link(&self, file_id: usize) -> Result<DownloadResponse> {
+        let session = self.session().await?;
+        let base_url = session
+            .as_ref()
+            .map_or(&*OPEN_SUBTITLES_BASE_URL, |session| &session.base_url);
+
+        let mut request = self
+            .http_client
+            .post(base_url.join("download")?)
+            .form(&DownloadForm { file_id })
+            .headers(self.headers()?);
+        if let Some(session) = &session {
+            request = request.bearer_auth(&session.token);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        let download_link_response_string = response.text().await?;
+
+        match status {
+            StatusCode::UNAUTHORIZED => return Err(Error::Unauthorized),
+            StatusCode::NOT_ACCEPTABLE => {
+                let quota_response: QuotaReachedResponse =
+                    serde_json::from_str(&download_link_response_string)?;
+                warn!("Open Subtitles: {}", quota_response.message.trim());
+                self.set_quota(quota::quota(
+                    quota_response.remaining,
+                    None,
+                    Some(&quota_response.reset_time_utc),
+                    SystemTime::now(),
+                ));
+                return Err(Error::DownloadQuotaReached);
+            }
+            _ => {}
+        }
+
+        Self::check_api_error(&download_link_response_string)?;
+
+        Ok(serde_json::from_str(&download_link_response_string)?)
     }
 
     fn check_api_error(api_response_str: &str) -> Result<()> {
@@ -64,10 +256,28 @@ impl SubtitleProvider for OpenSubtitlesClient {
         query: &str,
         language: domain::language::LanguageCode,
         episode: Option<domain::series::EpisodeIdentifier>,
+        media_file: Option<&Path>,
     ) -> std::result::Result<
         impl Iterator<Item = SubtitleDownloadOption<Self::SubtitleId>>,
         Self::Error,
     > {
+        let movie_hash = match media_file {
+            Some(media_file) => movie_hash(media_file)
+                .await
+                .inspect_err(|err| {
+                    warn!(
+                        "Can't hash {}, searching by name only. {err}",
+                        media_file.display()
+                    )
+                })
+                .ok(),
+            None => None,
+        };
+        // Parameters have to be sorted, otherwise the API redirects to the sorted URL
+        let moviehash = movie_hash
+            .map(|movie_hash| format!("moviehash={movie_hash}"))
+            .unwrap_or_default();
+
         let search_url = {
             let mut url = OPEN_SUBTITLES_BASE_URL
                 .join("subtitles")
@@ -85,20 +295,28 @@ impl SubtitleProvider for OpenSubtitlesClient {
                     "ai_translated=exclude",
                     &format!("episode_number={episode_no}"),
                     &format!("languages={}", language.to_iso639_1()),
+                    &moviehash,
                     "order_by=attributes%2Edownload_count",
                     &format!("query={encoded_title}"),
                     &format!("season_number={season_no}"),
                     "type=episode",
                 ]
+                .into_iter()
+                .filter(|param| !param.is_empty())
+                .collect::<Vec<_>>()
                 .join("&"),
                 // Movie
                 None => [
                     "ai_translated=exclude",
                     &format!("languages={}", language.to_iso639_1()),
+                    &moviehash,
                     "order_by=attributes%2Edownload_count",
                     &format!("query={encoded_title}"),
                     "type=movie",
                 ]
+                .into_iter()
+                .filter(|param| !param.is_empty())
+                .collect::<Vec<_>>()
                 .join("&"),
             };
             url.set_query(Some(&query_string));
@@ -108,7 +326,7 @@ impl SubtitleProvider for OpenSubtitlesClient {
         let result_string = self
             .http_client
             .get(search_url)
-            .headers(DEFAULT_HEADERS.clone())
+            .headers(self.headers()?)
             .send()
             .await?
             .text()
@@ -118,71 +336,96 @@ impl SubtitleProvider for OpenSubtitlesClient {
 
         let result: OpenSubtitlesSubtitleResponse = serde_json::from_str(&result_string)?;
 
-        Ok(result
-            .data
-            .into_iter()
-            .map(move |mut open_subtitles_subtitle| {
-                let files = std::mem::take(&mut open_subtitles_subtitle.attributes.files);
-                let first_file = files
-                    .into_iter()
-                    .next()
-                    .expect("There should be at least one file");
-
-                crate::SubtitleDownloadOption {
-                    id: first_file.file_id,
-                    title: first_file.file_name,
-                    download_count: open_subtitles_subtitle.attributes.download_count,
-                    language: language.clone(),
-                }
-            }))
+        // Subtitles made for this exact file first, the rest stay most downloaded first
+        let mut data = result.data.into_vec();
+        data.sort_by_key(|subtitle| !subtitle.attributes.moviehash_match);
+
+        Ok(data.into_iter().map(move |open_subtitles_subtitle| {
+            let attributes = open_subtitles_subtitle.attributes;
+            let first_file = attributes
+                .files
+                .into_iter()
+                .next()
+                .expect("There should be at least one file");
+
+            crate::SubtitleDownloadOption {
+                id: first_file.file_id,
+                title: first_file.file_name,
+                download_count: attributes.download_count,
+                language: language.clone(),
+                hash_match: attributes.moviehash_match,
+                metadata: SubtitleMetadata {
+                    release: (!attributes.release.is_empty()).then_some(attributes.release),
+                    hearing_impaired: attributes.hearing_impaired,
+                    hd: attributes.hd,
+                    framerate: Framerate::from_fps(attributes.fps),
+                    // Ratings are out of 10
+                    rating: (attributes.votes > 0)
+                        .then(|| (attributes.ratings * 10.0).clamp(0.0, 100.0).round() as u8),
+                    from_trusted: attributes.from_trusted,
+                    machine_translated: attributes.machine_translated,
+                },
+            }
+        }))
     }
 
     async fn download(&self, id: &Self::SubtitleId) -> core::result::Result<String, Self::Error> {
-        let url = OPEN_SUBTITLES_BASE_URL
-            .join("download")
-            .expect("download URL has to be valid");
-
-        let download_link_response_string = self
-            .http_client
-            .post(url)
-            .form(&DownloadForm { file_id: *id })
-            .headers(DEFAULT_HEADERS.clone())
-            .send()
-            .await?
-            .text()
-            .await?;
-
-        Self::check_api_error(&download_link_response_string)?;
-
-        let download_response: DownloadResponse =
-            serde_json::from_str(&download_link_response_string)?;
+        let download_response = match self.request_download_link(*id).await {
+            // Tokens can be revoked before they expire
+            Err(Error::Unauthorized) if self.is_logged_in() => {
+                self.session.lock().await.take();
+                self.request_download_link(*id).await?
+            }
+            result => result?,
+        };
 
         info!(
             "Open Subtitles: Remaining subtitle download limit: {}. Message: {}",
             download_response.remaining, download_response.message,
         );
+        self.set_quota(quota::quota(
+            download_response.remaining,
+            Some(download_response.requests + download_response.remaining.max(0) as u32),
+            Some(&download_response.reset_time_utc),
+            SystemTime::now(),
+        ));
 
         let download_url = reqwest::Url::from_str(&download_response.link)?;
 
-        let subtitle_text = self
+        // Subtitles are often in a legacy code page, which the response doesn't mention
+        let subtitle_bytes = self
             .http_client
             .get(download_url)
-            .headers(DEFAULT_HEADERS.clone())
+            .headers(self.headers()?)
             .send()
             .await?
-            .text()
+            .bytes()
             .await?;
 
-        Ok(subtitle_text)
+        Ok(domain::subtitles::encoding::decode(&subtitle_bytes))
     }
 }
 
 #[derive(Debug)]
 pub enum Error {
-    RequestError { inner: reqwest::Error },
-    OpenSubtitlesAPIError { inner: OpenSubtitlesError },
-    OpenSubtitlesJSONParsingError { inner: serde_json::Error },
-    OpenSubtitlesInvalidURLError { inner: url::ParseError },
+    RequestError {
+        inner: reqwest::Error,
+    },
+    OpenSubtitlesAPIError {
+        inner: OpenSubtitlesError,
+    },
+    OpenSubtitlesJSONParsingError {
+        inner: serde_json::Error,
+    },
+    OpenSubtitlesInvalidURLError {
+        inner: url::ParseError,
+    },
+    InvalidApiKey {
+        inner: reqwest::header::InvalidHeaderValue,
+    },
+    /// The token was rejected, or a login is needed
+    Unauthorized,
+    DownloadQuotaReached,
 }
 
 type Result<T> = core::result::Result<T, Error>;
@@ -212,6 +455,12 @@ impl From<serde_json::Error> for Error {
     }
 }
 
+impl From<reqwest::header::InvalidHeaderValue> for Error {
+    fn from(value: reqwest::header::InvalidHeaderValue) -> Self {
+        Self::InvalidApiKey { inner: value }
+    }
+}
+
 impl From<url::ParseError> for Error {
     fn from(value: url::ParseError) -> Self {
         Self::OpenSubtitlesInvalidURLError { inner: value }
@@ -224,12 +473,20 @@ mod tests {
 
     use crate::{OpenSubtitlesClient, SubtitleProvider};
 
+    /// Same key as the server, from the environment or a file in the repository root
+    fn client() -> OpenSubtitlesClient {
+        let api_key = std::env::var("OPEN_SUBTITLES_API_KEY")
+            .or_else(|_| std::fs::read_to_string("../open_subtitles_api_key"))
+            .expect("OpenSubtitles API key to be set");
+        OpenSubtitlesClient::new(api_key)
+    }
+
     #[tokio::test]
     async fn get_movie_subtitles() {
-        let client = OpenSubtitlesClient::new();
+        let client = client();
 
         let result: Vec<_> = client
-            .search("Idiocracy", LanguageCode::Turkish, None)
+            .search("Idiocracy", LanguageCode::Turkish, None, None)
             .await
             .unwrap()
             .collect();
@@ -241,7 +498,7 @@ mod tests {
 
     #[tokio::test]
     async fn get_series_subtitles() {
-        let client = OpenSubtitlesClient::new();
+        let client = client();
 
         let result: Vec<_> = client
             .search(
@@ -251,6 +508,7 @@ mod tests {
                     season_no: 1,
                     episode_no: 1,
                 }),
+                None,
             )
             .await
             .unwrap()
@@ -263,7 +521,7 @@ mod tests {
 
     #[tokio::test]
     async fn search_and_download_subtitles() {
-        let client = OpenSubtitlesClient::new();
+        let client = client();
 
         let search_result: Vec<_> = client
             .search(
@@ -273,6 +531,7 @@ mod tests {
                     season_no: 1,
                     episode_no: 1,
                 }),
+                None,
             )
             .await
             .unwrap()
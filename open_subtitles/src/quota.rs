@@ -0,0 +1,114 @@
+//! Download quota as OpenSubtitles reports it after every download, so the next download can be
+//! refused without asking the API.
+
+use std::time::{Duration, SystemTime};
+
+use domain::subtitles::SubtitleQuota;
+
+/// Quotas are daily, one without a readable reset time is trusted for a day after it's reported
+const QUOTA_LIFETIME: Duration = Duration::from_secs(24 * 60 * 60);
+
+pub(super) fn quota(
+    remaining: i64,
+    allowed: Option<u32>,
+    reset_time_utc: Option<&str>,
+    now: SystemTime,
+) -> SubtitleQuota {
+    SubtitleQuota {
+        remaining: remaining.clamp(0, u32::MAX as i64) as u32,
+        allowed,
+        resets_at: Some(
+            reset_time_utc
+                .and_then(parse_utc)
+                .unwrap_or(now + QUOTA_LIFETIME),
+        ),
+    }
+}
+
+/// The quota has been reset since it was reported, it has to be asked for again
+pub(super) fn is_stale(quota: &SubtitleQuota, now: SystemTime) -> bool {
+    quota.resets_at.is_none_or(|resets_at| now >= resets_at)
+}
+
+/// Reset times look like `2022-01-30T06:00:51.000Z`
+fn parse_utc(text: &str) -> Option<SystemTime> {
+    let text = text.strip_suffix('Z')?;
+    let (date, time) = text.split_once('T')?;
+    let time = time.split_once('.').map_or(time, |(time, _)| time);
+
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let mut time = time.splitn(3, ':').map(str::parse::<u64>);
+    let (hours, minutes, seconds) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hours > 23 || minutes > 59 {
+        return None;
+    }
+
+    let days = u64::try_from(days_from_civil(year, month, day)).ok()?;
+    let seconds = days * 86_400 + hours * 3_600 + minutes * 60 + seconds;
+    Some(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+/// Days since 1970-01-01 in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // Years start in March, so the leap day is the last day of the year
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use domain::subtitles::SubtitleQuota;
+
+    use super::{QUOTA_LIFETIME, is_stale, parse_utc, quota};
+
+    #[test]
+    fn test_parse_utc() {
+        assert_eq!(
+            parse_utc("2022-01-30T06:00:51.000Z"),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_643_522_451))
+        );
+        assert_eq!(
+            parse_utc("2024-02-29T00:00:00Z"),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_709_164_800))
+        );
+        assert_eq!(parse_utc("07 hours and 29 minutes"), None);
+        assert_eq!(parse_utc("2022-13-30T06:00:51.000Z"), None);
+    }
+
+    #[test]
+    fn test_quota() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_643_500_000);
+        let reported = quota(-1, Some(20), Some("2022-01-30T06:00:51.000Z"), now);
+        assert_eq!(reported.remaining, 0);
+
+        let resets_at = reported.resets_at.unwrap();
+        assert!(reported.is_exhausted(resets_at - Duration::from_secs(1)));
+        assert!(!reported.is_exhausted(resets_at));
+        assert!(!is_stale(&reported, now));
+        assert!(is_stale(&reported, resets_at));
+
+        // Without a reset time the quota expires a day after it was reported
+        for reset_time_utc in [None, Some("07 hours and 29 minutes")] {
+            let reported = quota(0, Some(20), reset_time_utc, now);
+            assert_eq!(reported.resets_at, Some(now + QUOTA_LIFETIME));
+            assert!(reported.is_exhausted(now));
+            assert!(!reported.is_exhausted(now + QUOTA_LIFETIME));
+        }
+
+        let unknown = SubtitleQuota {
+            remaining: 0,
+            allowed: None,
+            resets_at: None,
+        };
+        assert!(!unknown.is_exhausted(now));
+        assert!(is_stale(&unknown, now));
+    }
+}
@@ -0,0 +1,49 @@
+use axum::{
+    extract,
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use log::warn;
+
+use super::{ArtworkSource, ArtworkVariant};
+use crate::State;
+
+pub async fn get_artwork(
+    extract::State(state): State,
+    extract::Path((media_id, variant)): extract::Path<(String, ArtworkVariant)>,
+) -> axum::response::Result<impl IntoResponse> {
+    let (media_folder, source) = state
+        .media_signal_watcher
+        .data
+        .borrow()
+        .iter()
+        .find(|media| media.id == media_id)
+        .and_then(|media| {
+            Some((
+                state.libraries.media_folder(media)?,
+                ArtworkSource::of(media, &state.libraries)?,
+            ))
+        })
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let artwork = super::get_variant(&media_folder, &source, variant)
+        .await
+        .map_err(|err| {
+            warn!("Couldn't serve artwork of {media_id}. Reason: {err}");
+            match err {
+                super::Error::InvalidUrl(_) => StatusCode::NOT_FOUND,
+                super::Error::CantDownload(_) | super::Error::NotAnImage(_) => {
+                    StatusCode::BAD_GATEWAY
+                }
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        })?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "image/jpeg"),
+            (header::CACHE_CONTROL, "max-age=86400"),
+        ],
+        artwork,
+    ))
+}
@@ -0,0 +1,406 @@
+//! Keeps a local copy of media artwork so clients don't have to reach the remote thumbnail,
+//! and renders resized variants of it.
+
+#[allow(clippy::result_large_err)]
+pub mod handlers;
+
+use std::{
+    io::Cursor,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use domain::{Media, MediaContent};
+use image::{DynamicImage, ImageFormat, imageops::FilterType};
+use log::info;
+
+use crate::library::Libraries;
+
+/// The downloaded artwork, as it was served by the remote
+const ORIGINAL_FILE_NAME: &str = "artwork";
+
+static HTTP_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(reqwest::Client::new);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtworkVariant {
+    /// Detail screens
+    Poster,
+    /// Grids and lists
+    Card,
+    /// 16:9 crop for headers and players
+    Backdrop,
+}
+
+impl ArtworkVariant {
+    const ALL: [ArtworkVariant; 3] = [Self::Poster, Self::Card, Self::Backdrop];
+
+    fn name(&self) -> &'static str {
+        match self {
+            ArtworkVariant::Poster => "poster",
+            ArtworkVariant::Card => "card",
+            ArtworkVariant::Backdrop => "backdrop",
+        }
+    }
+
+    fn path(&self, media_folder: &Path) -> PathBuf {
+        media_folder.join(format!("{ORIGINAL_FILE_NAME}.{}.jpg", self.name()))
+    }
+
+    fn render(&self, image: &DynamicImage) -> DynamicImage {
+        match self {
+            ArtworkVariant::Poster => image.resize(500, 750, FilterType::Lanczos3),
+            ArtworkVariant::Card => image.resize(342, 513, FilterType::Triangle),
+            ArtworkVariant::Backdrop => image.resize_to_fill(1280, 720, FilterType::Lanczos3),
+        }
+    }
+}
+
+/// Route that serves `variant` of the media's artwork
+pub fn artwork_url_path(media_id: &str, variant: ArtworkVariant) -> String {
+    format!("/artwork/{media_id}/{}", variant.name())
+}
+
+/// The thumbnail to store when `media` is edited. Clients may send back the artwork route they
+/// were served, that keeps the stored source. Routes of other media are refused, the artwork
+/// would be downloaded from this server.
+pub fn source_thumbnail(media: &Media, thumbnail: String) -> Result<String> {
+    match artwork_route_media_id(&thumbnail) {
+        None => Ok(thumbnail),
+        Some(media_id) if media_id == media.id => Ok(media.metadata.thumbnail.clone()),
+        Some(_) => Err(Error::InvalidUrl(thumbnail)),
+    }
+}
+
+/// Media id of an artwork route, given as a path or as a full URL
+fn artwork_route_media_id(url: &str) -> Option<String> {
+    let path = match reqwest::Url::parse(url) {
+        Ok(url) => url.path().to_string(),
+        Err(_) => url.to_string(),
+    };
+    let mut segments = path.strip_prefix('/')?.split('/');
+    let (Some("artwork"), Some(media_id), Some(variant), None) = (
+        segments.next(),
+        segments.next(),
+        segments.next(),
+        segments.next(),
+    ) else {
+        return None;
+    };
+
+    ArtworkVariant::ALL
+        .iter()
+        .any(|known| known.name() == variant)
+        .then(|| media_id.to_string())
+}
+
+/// Where the artwork comes from when the media folder doesn't have a copy yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArtworkSource {
+    Remote(String),
+    /// A frame extracted from the video, for media that came without a thumbnail URL
+    Frame(PathBuf),
+}
+
+impl ArtworkSource {
+    pub fn of(media: &Media, libraries: &Libraries) -> Option<Self> {
+        if !media.metadata.thumbnail.is_empty() {
+            return Some(Self::Remote(media.metadata.thumbnail.clone()));
+        }
+
+        representative_frame(&media.content)
+            .and_then(|frame| libraries.resolve(frame))
+            .map(Self::Frame)
+    }
+}
+
+/// The movie's frame, or the frame of the first episode that has one
+fn representative_frame(content: &MediaContent) -> Option<&String> {
+    match content {
+        MediaContent::Movie(paths) => paths.thumbnail.as_ref(),
+        MediaContent::Series(seasons) => {
+            let mut season_nos: Vec<_> = seasons.keys().collect();
+            season_nos.sort();
+
+            season_nos.into_iter().find_map(|season_no| {
+                let season = &seasons[season_no];
+                let mut episode_nos: Vec<_> = season.keys().collect();
+                episode_nos.sort();
+
+                episode_nos
+                    .into_iter()
+                    .find_map(|episode_no| season[episode_no].thumbnail.as_ref())
+            })
+        }
+    }
+}
+
+/// Points clients at the local copy, the thumbnail keeps the source URL
+pub fn with_artwork_url(mut media: Media) -> Media {
+    if !media.metadata.thumbnail.is_empty() || representative_frame(&media.content).is_some() {
+        media.artwork_url = Some(artwork_url_path(&media.id, ArtworkVariant::Poster));
+    }
+
+    media
+}
+
+/// Downloads the artwork at `url` into the media folder, replacing the previous one.
+pub async fn download(media_folder: &Path, url: &str) -> Result<()> {
+    let url = reqwest::Url::parse(url).map_err(|_| Error::InvalidUrl(url.to_string()))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(Error::InvalidUrl(url.to_string()));
+    }
+
+    let bytes = HTTP_CLIENT
+        .get(url.clone())
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(Error::CantDownload)?
+        .bytes()
+        .await
+        .map_err(Error::CantDownload)?;
+
+    // Don't keep error pages around
+    image::guess_format(&bytes).map_err(Error::NotAnImage)?;
+
+    let original_path = media_folder.join(ORIGINAL_FILE_NAME);
+    let temporary_path = original_path.with_extension("tmp");
+    tokio::fs::write(&temporary_path, &bytes)
+        .await
+        .map_err(Error::CantWrite)?;
+    tokio::fs::rename(&temporary_path, &original_path)
+        .await
+        .map_err(Error::CantWrite)?;
+
+    clear_variants(media_folder).await;
+    info!("Saved artwork from {url} to {}", original_path.display());
+
+    Ok(())
+}
+
+/// Forgets the local copy, so the artwork is taken from its source again on the next request.
+pub async fn clear(media_folder: &Path) {
+    let _ = tokio::fs::remove_file(media_folder.join(ORIGINAL_FILE_NAME)).await;
+    clear_variants(media_folder).await;
+}
+
+/// Removes the resized variants so they get rendered again from the current artwork.
+pub async fn clear_variants(media_folder: &Path) {
+    for variant in ArtworkVariant::ALL {
+        let _ = tokio::fs::remove_file(variant.path(media_folder)).await;
+    }
+}
+
+/// Returns the JPEG encoded variant. Renders and caches it on first use, taking the artwork
+/// from `source` if the media folder doesn't have a copy yet.
+pub async fn get_variant(
+    media_folder: &Path,
+    source: &ArtworkSource,
+    variant: ArtworkVariant,
+) -> Result<Vec<u8>> {
+    let variant_path = variant.path(media_folder);
+    if let Ok(cached) = tokio::fs::read(&variant_path).await {
+        return Ok(cached);
+    }
+
+    let original_path = media_folder.join(ORIGINAL_FILE_NAME);
+    if !tokio::fs::try_exists(&original_path).await.unwrap_or(false) {
+        match source {
+            ArtworkSource::Remote(url) => download(media_folder, url).await?,
+            ArtworkSource::Frame(frame) => {
+                tokio::fs::copy(frame, &original_path)
+                    .await
+                    .map_err(Error::CantRead)?;
+            }
+        }
+    }
+
+    let original = tokio::fs::read(&original_path)
+        .await
+        .map_err(Error::CantRead)?;
+
+    let rendered = tokio::task::spawn_blocking(move || render(&original, variant))
+        .await
+        .expect("Rendering artwork panicked")?;
+
+    tokio::fs::write(&variant_path, &rendered)
+        .await
+        .map_err(Error::CantWrite)?;
+
+    Ok(rendered)
+}
+
+fn render(original: &[u8], variant: ArtworkVariant) -> Result<Vec<u8>> {
+    let image = image::load_from_memory(original).map_err(Error::NotAnImage)?;
+    // JPEG has no alpha channel
+    let rendered = DynamicImage::ImageRgb8(variant.render(&image).into_rgb8());
+
+    let mut encoded = Cursor::new(Vec::new());
+    rendered
+        .write_to(&mut encoded, ImageFormat::Jpeg)
+        .map_err(Error::CantEncode)?;
+
+    Ok(encoded.into_inner())
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Artwork URL {0} is not valid")]
+    InvalidUrl(String),
+    #[error("Can't download artwork. {0}")]
+    CantDownload(reqwest::Error),
+    #[error("Artwork is not an image. {0}")]
+    NotAnImage(image::ImageError),
+    #[error("Can't encode artwork. {0}")]
+    CantEncode(image::ImageError),
+    #[error("Can't read artwork. {0}")]
+    CantRead(std::io::Error),
+    #[error("Can't write artwork. {0}")]
+    CantWrite(std::io::Error),
+}
+
+type Result<T> = core::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use domain::{Media, MediaContent, MediaMetaData, MediaPaths};
+    use image::{DynamicImage, GenericImageView, ImageFormat, RgbaImage};
+
+    use crate::library::Libraries;
+
+    use super::{
+        ArtworkSource, ArtworkVariant, ORIGINAL_FILE_NAME, get_variant, source_thumbnail,
+        with_artwork_url,
+    };
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut encoded = Cursor::new(Vec::new());
+        DynamicImage::ImageRgba8(RgbaImage::new(width, height))
+            .write_to(&mut encoded, ImageFormat::Png)
+            .unwrap();
+        encoded.into_inner()
+    }
+
+    #[tokio::test]
+    async fn test_variants() {
+        let tmp = tempfile::tempdir().unwrap();
+        tokio::fs::write(tmp.path().join(ORIGINAL_FILE_NAME), png(1000, 1500))
+            .await
+            .unwrap();
+
+        for (variant, dimensions) in [
+            (ArtworkVariant::Poster, (500, 750)),
+            (ArtworkVariant::Card, (342, 513)),
+            (ArtworkVariant::Backdrop, (1280, 720)),
+        ] {
+            // There is a local copy, so the source is never used
+            let rendered = get_variant(tmp.path(), &ArtworkSource::Remote(String::new()), variant)
+                .await
+                .unwrap();
+            let image = image::load_from_memory(&rendered).unwrap();
+
+            assert_eq!(image::guess_format(&rendered).unwrap(), ImageFormat::Jpeg);
+            assert_eq!(image.dimensions(), dimensions);
+            assert!(variant.path(tmp.path()).exists());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejects_non_http_url() {
+        let tmp = tempfile::tempdir().unwrap();
+
+        assert!(matches!(
+            get_variant(
+                tmp.path(),
+                &ArtworkSource::Remote("file:///etc/passwd".to_string()),
+                ArtworkVariant::Poster
+            )
+            .await,
+            Err(super::Error::InvalidUrl(_))
+        ));
+    }
+
+    #[test]
+    fn test_source_thumbnail() {
+        let media = Media {
+            id: "My_Movie".to_string(),
+            library_id: "media".to_string(),
+            added_on: None,
+            artwork_url: None,
+            metadata: MediaMetaData {
+                thumbnail: "https://image.com/poster.jpg".to_string(),
+                title: "My Movie".to_string(),
+                ..Default::default()
+            },
+            content: MediaContent::Movie(MediaPaths::default()),
+        };
+        let source = |thumbnail: &str| source_thumbnail(&media, thumbnail.to_string()).ok();
+
+        assert_eq!(
+            source("https://image.com/other.jpg").as_deref(),
+            Some("https://image.com/other.jpg")
+        );
+        assert_eq!(
+            source("http://192.168.1.2:3000/artwork/My_Movie/card").as_deref(),
+            Some("https://image.com/poster.jpg")
+        );
+        assert_eq!(source("/artwork/Other_Movie/poster"), None);
+        // Not one of our routes
+        assert_eq!(
+            source("https://image.com/artwork/poster.jpg").as_deref(),
+            Some("https://image.com/artwork/poster.jpg")
+        );
+    }
+
+    #[test]
+    fn test_artwork_url() {
+        let libraries = Libraries::new(Vec::new(), "media");
+        let media = Media {
+            id: "My_Movie".to_string(),
+            library_id: "media".to_string(),
+            added_on: None,
+            artwork_url: None,
+            metadata: MediaMetaData {
+                thumbnail: "https://image.com/poster.jpg".to_string(),
+                title: "My Movie".to_string(),
+                ..Default::default()
+            },
+            content: MediaContent::Movie(MediaPaths {
+                media: "media/My_Movie/My_Movie.mp4".to_string(),
+                subtitles: Vec::new(),
+                track_name: "My Movie".to_string(),
+                ..Default::default()
+            }),
+        };
+
+        let served = with_artwork_url(media.clone());
+        assert_eq!(
+            served.artwork_url.as_deref(),
+            Some("/artwork/My_Movie/poster")
+        );
+        assert_eq!(served.metadata.thumbnail, "https://image.com/poster.jpg");
+
+        // No thumbnail URL and no frame to fall back to
+        let mut media = media;
+        media.metadata.thumbnail = String::new();
+        assert_eq!(ArtworkSource::of(&media, &libraries), None);
+        assert_eq!(with_artwork_url(media.clone()).artwork_url, None);
+
+        if let MediaContent::Movie(paths) = &mut media.content {
+            paths.thumbnail = Some("media/My_Movie/thumbnails/My_Movie.jpg".to_string());
+        }
+        assert_eq!(
+            ArtworkSource::of(&media, &libraries),
+            Some(ArtworkSource::Frame(
+                "media/My_Movie/thumbnails/My_Movie.jpg".into()
+            ))
+        );
+        assert_eq!(
+            with_artwork_url(media).artwork_url.as_deref(),
+            Some("/artwork/My_Movie/poster")
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use axum::{Json, extract, http::StatusCode};
+use domain::collection::{
+    AddToCollectionForm, Collection, CreateCollectionForm, DeleteCollectionForm,
+    MoveCollectionForm, RemoveFromCollectionForm, UpdateCollectionForm,
+};
+
+use super::State;
+use crate::service::collections::{CollectionOperation, CollectionSignal, Error};
+
+/// Media that's no longer in the library is left out
+pub async fn get_collections(extract::State(state): State) -> Json<Box<[Collection]>> {
+    let media_library = state.media_signal_watcher.data.borrow().clone();
+    let collections = state
+        .collections_signal_watcher
+        .data
+        .borrow()
+        .iter()
+        .cloned()
+        .map(|mut collection| {
+            collection
+                .media_ids
+                .retain(|media_id| media_library.iter().any(|media| &media.id == media_id));
+            collection
+        })
+        .collect();
+
+    Json(collections)
+}
+
+pub async fn create_collection(
+    extract::State(state): State,
+    Json(form): Json<CreateCollectionForm>,
+) -> axum::response::Result<Json<Collection>> {
+    for media_id in &form.media_ids {
+        ensure_media_exists(&state, media_id)?;
+    }
+
+    send(&state, CollectionOperation::Create(form))
+        .await
+        .map(Json)
+}
+
+pub async fn update_collection(
+    extract::State(state): State,
+    Json(form): Json<UpdateCollectionForm>,
+) -> axum::response::Result<()> {
+    send(&state, CollectionOperation::Update(form)).await?;
+    Ok(())
+}
+
+pub async fn delete_collection(
+    extract::State(state): State,
+    Json(form): Json<DeleteCollectionForm>,
+) -> axum::response::Result<()> {
+    send(&state, CollectionOperation::Delete(form)).await?;
+    Ok(())
+}
+
+pub async fn add_to_collection(
+    extract::State(state): State,
+    Json(form): Json<AddToCollectionForm>,
+) -> axum::response::Result<()> {
+    ensure_media_exists(&state, &form.media_id)?;
+
+    send(&state, CollectionOperation::AddMedia(form)).await?;
+    Ok(())
+}
+
+pub async fn remove_from_collection(
+    extract::State(state): State,
+    Json(form): Json<RemoveFromCollectionForm>,
+) -> axum::response::Result<()> {
+    send(&state, CollectionOperation::RemoveMedia(form)).await?;
+    Ok(())
+}
+
+pub async fn move_collection(
+    extract::State(state): State,
+    Json(form): Json<MoveCollectionForm>,
+) -> axum::response::Result<()> {
+    send(&state, CollectionOperation::Move(form)).await?;
+    Ok(())
+}
+
+fn ensure_media_exists(state: &crate::AppState, media_id: &str) -> axum::response::Result<()> {
+    state
+        .media_signal_watcher
+        .data
+        .borrow()
+        .iter()
+        .any(|media| media.id == media_id)
+        .then_some(())
+        .ok_or_else(|| StatusCode::NOT_FOUND.into())
+}
+
+async fn send(
+    state: &crate::AppState,
+    operation: CollectionOperation,
+) -> axum::response::Result<Collection> {
+    let (result_sender, result_receiver) = tokio::sync::oneshot::channel();
+
+    state
+        .collections_signal_watcher
+        .signal_sender
+        .send(CollectionSignal {
+            operation,
+            result_sender,
+        })
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    result_receiver
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(|err| match err {
+            Error::NotFound(_) => StatusCode::NOT_FOUND.into(),
+            Error::EmptyName => StatusCode::BAD_REQUEST.into(),
+            _ => StatusCode::INTERNAL_SERVER_ERROR.into(),
+        })
+}
@@ -4,7 +4,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use domain::{Media, MediaContent, MediaMetaData};
+use domain::{Media, MediaContent, MediaMetaData, series::EpisodeIdentifier};
 use log::{error, warn};
 use tokio::{fs::OpenOptions, io::AsyncReadExt};
 
@@ -82,7 +82,7 @@ pub(crate) async fn crawl_folder(
         if stripped_content.is_none() {
             warn!(
                 "Couldn't strip prefix of media named {}. Ignoring it.",
-                &media.metadata.title
+                media.metadata.title
             );
         }
 
@@ -104,11 +104,35 @@ async fn crawl_media(
         .map(|last| last.as_os_str().to_string_lossy().into())
         .ok_or_else(|| Error::CantReadDir(path.as_ref().into()))?;
     let metadata = crawl_metadata(&path).await?;
-    let (content, to_prepare) = crawl_media_content(id.clone(), &path).await?;
+    let (mut content, to_prepare) = crawl_media_content(id.clone(), &path).await?;
+
+    let ingest = crate::ingest_file::read(&path).await;
+    if let Some(MediaContent::Series(seasons)) = &mut content {
+        for (season_no, season) in seasons.iter_mut() {
+            for (episode_no, paths) in season.iter_mut() {
+                paths.added_on = ingest.episode(&EpisodeIdentifier {
+                    season_no: *season_no,
+                    episode_no: *episode_no,
+                });
+            }
+        }
+    }
+    // The media folder is created when the media is moved into the library
+    let added_on = match ingest.added_on {
+        Some(added_on) => Some(added_on),
+        None => tokio::fs::metadata(&path)
+            .await
+            .ok()
+            .and_then(|folder| folder.created().or_else(|_| folder.modified()).ok()),
+    };
 
     let result = (
         content.map(|content| Media {
             id,
+            // Filled in by the media service, which knows the library
+            library_id: String::new(),
+            added_on,
+            artwork_url: None,
             metadata,
             content,
         }),
@@ -145,7 +169,7 @@ async fn crawl_metadata(path: impl AsRef<Path>) -> Result<MediaMetaData> {
     let metadata_string = {
         let mut metadata_file = OpenOptions::new()
             .read(true)
-            .open(path.as_ref().join("meta.json"))
+            .open(path.as_ref().join(crate::metadata_file::METADATA_FILE_NAME))
             .await
             .map_err(|_| Error::NoMetadata)?;
 
@@ -157,7 +181,14 @@ async fn crawl_metadata(path: impl AsRef<Path>) -> Result<MediaMetaData> {
         string
     };
 
-    Ok(serde_json::from_str(&metadata_string)?)
+    crate::metadata_file::parse(&metadata_string)
+        .map(|parsed| parsed.metadata)
+        .map_err(|err| match err {
+            crate::metadata_file::Error::CantParse(_) => Error::CorruptedMetadata,
+            crate::metadata_file::Error::InvalidVersion(version) => {
+                Error::UnsupportedMetadataVersion(version)
+            }
+        })
 }
 
 fn get_numeric_content(string: &str) -> Option<u32> {
@@ -202,6 +233,8 @@ pub enum Error {
     CorruptedMetadata,
     #[error("Couldn't read metadata file")]
     CantReadMetadata,
+    #[error("Unsupported metadata version {0}")]
+    UnsupportedMetadataVersion(String),
     #[error("No media content")]
     NoMediaContent,
     #[error("Couldn't read dir {0:#?}")]
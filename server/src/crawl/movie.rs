@@ -37,10 +37,16 @@ pub(super) async fn crawl_movie(
     let track_name =
         domain::encode_decode::decode_url_safe(file_stem).unwrap_or_else(|_| file_stem.to_string());
 
+    let (thumbnail, thumbnail_track) =
+        crate::thumbnails::ThumbnailPaths::existing(&movie_path).await;
+
     let media_paths = domain::MediaPaths {
         media: movie_path.clone(),
         subtitles,
         track_name,
+        thumbnail,
+        thumbnail_track,
+        added_on: None,
     };
 
     if crate::prepare::needs_to_be_prepared(&media_paths)
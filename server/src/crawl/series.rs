@@ -70,7 +70,7 @@ async fn crawl_season(
             let Some(episode_no) = episode_no else {
                 error!(
                     "Subtitle at {} has no episode no. Ignoring it.",
-                    &subtitle.path
+                    subtitle.path
                 );
                 return None;
             };
@@ -148,10 +148,16 @@ async fn extract_episode(
 
     let track_name = get_episode_track_name(file_stem).unwrap_or_else(|| file_stem.to_string());
 
+    let (thumbnail, thumbnail_track) = crate::thumbnails::ThumbnailPaths::existing(&path).await;
+
     let media_paths = domain::MediaPaths {
         subtitles,
         media: path_string,
         track_name,
+        thumbnail,
+        thumbnail_track,
+        // Filled in from the ingest record once the whole series is crawled
+        added_on: None,
     };
 
     if crate::prepare::needs_to_be_prepared(&media_paths)
@@ -33,11 +33,11 @@ pub async fn crawl_subtitles(
 
             Some((
                 episode_no,
-                Subtitle {
-                    language,
+                Subtitle::new(
                     id,
-                    path: subtitle_file.path().to_string_lossy().to_string(),
-                },
+                    language,
+                    subtitle_file.path().to_string_lossy().to_string(),
+                ),
             ))
         })
         .collect();
@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use tokio::fs::DirEntry;
 
@@ -14,3 +14,42 @@ pub(crate) async fn fully_read_dir(
 
     Ok(result.into_iter())
 }
+
+/// Resolves `path` relative to `root`, making sure the result is strictly inside `root`.
+/// Symlinks and `..` components are resolved, so the path has to exist.
+pub(crate) async fn contained_path(
+    root: impl AsRef<Path>,
+    path: impl AsRef<Path>,
+) -> Option<PathBuf> {
+    let root = tokio::fs::canonicalize(root).await.ok()?;
+    let resolved = tokio::fs::canonicalize(root.join(path)).await.ok()?;
+
+    (resolved != root && resolved.starts_with(&root)).then_some(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::contained_path;
+
+    #[tokio::test]
+    async fn test_contained_path() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("media");
+        tokio::fs::create_dir_all(root.join("My_Movie"))
+            .await
+            .unwrap();
+        tokio::fs::create_dir_all(tmp.path().join("outside"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            contained_path(&root, "My_Movie").await,
+            Some(root.canonicalize().unwrap().join("My_Movie"))
+        );
+        assert_eq!(contained_path(&root, "").await, None);
+        assert_eq!(contained_path(&root, "My_Movie/..").await, None);
+        assert_eq!(contained_path(&root, "../outside").await, None);
+        assert_eq!(contained_path(&root, "/").await, None);
+        assert_eq!(contained_path(&root, "Missing").await, None);
+    }
+}
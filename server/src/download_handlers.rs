@@ -1,9 +1,15 @@
 use super::State;
 use axum::{Json, extract, http::StatusCode};
-use domain::{Download, DownloadForm, DownloadState, series::EditSeriesFileMappingForm};
+use domain::{
+    Download, DownloadForm, DownloadHistoryEntry, DownloadQueuePositionForm, DownloadQueueSettings,
+    DownloadState,
+    series::{EditSeriesFileMappingForm, FileMappingSuggestion},
+};
 use log::error;
 use torrent::{TorrentExtra, qbittorrent_client::QBittorrentClientMessage};
 
+use crate::service::history::HistorySignal;
+
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct TorrentContentsQuery {
     id: Box<str>,
@@ -37,6 +43,35 @@ pub async fn get_torrent_contents(
     ))
 }
 
+pub async fn get_file_mapping_suggestions(
+    extract::State(state): State,
+    extract::Query(query): extract::Query<TorrentContentsQuery>,
+) -> axum::response::Result<Json<Box<[FileMappingSuggestion]>>> {
+    let (result_sender, result_receiver) = tokio::sync::oneshot::channel();
+
+    state
+        .download_signal_watcher
+        .signal_sender
+        .send(QBittorrentClientMessage::GetTorrentContents {
+            id: query.id,
+            result_sender,
+        })
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let contents = result_receiver
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+
+    Ok(Json(
+        crate::file_mapping::suggest_file_mapping(
+            contents.iter().map(|contents| contents.name.as_ref()),
+        )
+        .into(),
+    ))
+}
+
 pub async fn get_downloads(extract::State(state): State) -> Json<Box<[Download]>> {
     let (result_sender, result_receiver) = tokio::sync::oneshot::channel();
 
@@ -52,21 +87,24 @@ pub async fn get_downloads(extract::State(state): State) -> Json<Box<[Download]>
 
     let processing_list = state.processing_list_watcher.data.borrow();
 
-    Json(
-        state
-            .download_signal_watcher
-            .data
-            .borrow()
-            .iter()
-            .map(|torrent| torrent.clone().into())
-            .map(|mut download: Download| {
-                if processing_list.contains(&download.id) {
-                    download.state = DownloadState::Processing;
-                }
-                download
-            })
-            .collect(),
-    )
+    let mut downloads: Box<[Download]> = state
+        .download_signal_watcher
+        .data
+        .borrow()
+        .iter()
+        .map(|torrent| torrent.clone().into())
+        .map(|mut download: Download| {
+            if processing_list.contains(&download.id) {
+                download.state = DownloadState::Processing;
+            }
+            download
+        })
+        .collect();
+
+    // Queued downloads first, in queue order. The rest keep qBittorrent's order.
+    downloads.sort_by_key(|download| download.queue_position.unwrap_or(u32::MAX));
+
+    Json(downloads)
 }
 
 pub async fn add_download(
@@ -200,6 +238,105 @@ pub async fn update_file_mapping(
     Ok(())
 }
 
+pub async fn set_queue_position(
+    extract::State(state): State,
+    Json(form): Json<DownloadQueuePositionForm>,
+) -> axum::response::Result<()> {
+    let (result_sender, result_receiver) = tokio::sync::oneshot::channel();
+
+    state
+        .download_signal_watcher
+        .signal_sender
+        .send(QBittorrentClientMessage::SetQueuePosition {
+            id: form.id,
+            change: form.change,
+            result_sender,
+        })
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    result_receiver
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(|err| match err {
+            torrent::QBittorrentWebApiError::QueueingDisabled(_) => StatusCode::CONFLICT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+    Ok(())
+}
+
+pub async fn get_queue_settings(
+    extract::State(state): State,
+) -> axum::response::Result<Json<DownloadQueueSettings>> {
+    let (result_sender, result_receiver) = tokio::sync::oneshot::channel();
+
+    state
+        .download_signal_watcher
+        .signal_sender
+        .send(QBittorrentClientMessage::GetMaxActiveDownloads { result_sender })
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let max_active_downloads = result_receiver
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(DownloadQueueSettings {
+        max_active_downloads,
+    }))
+}
+
+pub async fn set_queue_settings(
+    extract::State(state): State,
+    Json(settings): Json<DownloadQueueSettings>,
+) -> axum::response::Result<()> {
+    if settings.max_active_downloads == Some(0) {
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+
+    let (result_sender, result_receiver) = tokio::sync::oneshot::channel();
+
+    state
+        .download_signal_watcher
+        .signal_sender
+        .send(QBittorrentClientMessage::SetMaxActiveDownloads {
+            max_active_downloads: settings.max_active_downloads,
+            result_sender,
+        })
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    result_receiver
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(())
+}
+
+/// Newest entries first
+pub async fn get_download_history(
+    extract::State(state): State,
+) -> Json<Box<[DownloadHistoryEntry]>> {
+    let mut history: Box<[DownloadHistoryEntry]> =
+        state.download_history_watcher.data.borrow().clone();
+    history.sort_by_key(|entry| std::cmp::Reverse(entry.finished_on));
+
+    Json(history)
+}
+
+pub async fn clear_download_history(extract::State(state): State) -> axum::response::Result<()> {
+    state
+        .download_history_watcher
+        .signal_sender
+        .send(HistorySignal::Clear)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(())
+}
+
 pub async fn pause_download() -> axum::response::Result<()> {
     todo!()
 }
@@ -0,0 +1,331 @@
+//! Guesses which episode each file in a series download is.
+
+use std::{
+    collections::HashMap,
+    path::{Component, Path},
+    sync::LazyLock,
+};
+
+use domain::series::{EpisodeIdentifier, FileMappingSuggestion, SeriesFileMapping};
+use regex::Regex;
+
+/// `S01E02`, `s01.e02`, and multi episode files like `S01E01E02` or `S01E01-E02`
+static SEASON_EPISODE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(?:^|[^a-z0-9])s(\d{1,2})[ ._-]?e(\d{1,3})((?:[ ._-]?e\d{1,3})*)(?:[^0-9]|$)")
+        .expect("Invalid regex supplied")
+});
+
+/// `1x02`. Episode has to be 2 digits so codecs like `5.1x264` don't match.
+static CROSS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(?:^|[^a-z0-9])(\d{1,2})x(\d{2})(?:[^0-9]|$)").expect("Invalid regex supplied")
+});
+
+/// Folders like `Season 1`, `S01` or `Show.Season.2.1080p`
+static SEASON_FOLDER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(?:^|[ ._\-\[])(?:season|s)[ ._-]?(\d{1,2})(?:$|[ ._\-\]])")
+        .expect("Invalid regex supplied")
+});
+
+/// `E05`, `Ep 5`, `Episode 05`, `- 05 -` or a leading `05 - Title`
+static EPISODE_ONLY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(?:(?:^|[ ._\-\[])(?:e|ep|episode)[ ._]?(\d{1,3})|^(\d{1,3})(?:[ ._\-]|$)|[ ._]-[ ._](\d{1,3}))(?:v\d)?(?:$|[ ._\-\]\[(])",
+    )
+    .expect("Invalid regex supplied")
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParsedEpisode {
+    /// Both season and episode numbers were found. Multi episode files list every episode.
+    Seasonal {
+        season_no: u32,
+        episode_nos: Vec<u32>,
+    },
+    /// Only an episode number was found, counting from the first episode of the series
+    Absolute { episode_no: u32 },
+}
+
+impl ParsedEpisode {
+    /// Files with absolute numbering are mapped to the first season.
+    pub fn identifier(&self) -> EpisodeIdentifier {
+        match self {
+            ParsedEpisode::Seasonal {
+                season_no,
+                episode_nos,
+            } => EpisodeIdentifier {
+                season_no: *season_no,
+                episode_no: episode_nos[0],
+            },
+            ParsedEpisode::Absolute { episode_no } => EpisodeIdentifier {
+                season_no: 1,
+                episode_no: *episode_no,
+            },
+        }
+    }
+
+    /// Every episode in the file, multi episode files have more than one
+    pub fn identifiers(&self) -> Vec<EpisodeIdentifier> {
+        match self {
+            ParsedEpisode::Seasonal {
+                season_no,
+                episode_nos,
+            } => episode_nos
+                .iter()
+                .map(|episode_no| EpisodeIdentifier {
+                    season_no: *season_no,
+                    episode_no: *episode_no,
+                })
+                .collect(),
+            ParsedEpisode::Absolute { .. } => vec![self.identifier()],
+        }
+    }
+}
+
+/// Parses the episode out of a file path relative to the torrent's root.
+pub fn parse_episode(path: &str) -> Option<ParsedEpisode> {
+    let path = Path::new(path);
+    let file_stem = path.file_stem()?.to_str()?;
+
+    if let Some(captures) = SEASON_EPISODE.captures(file_stem) {
+        let season_no = captures[1].parse().ok()?;
+        let mut episode_nos = vec![captures[2].parse().ok()?];
+        if let Some(extra_episodes) = captures.get(3) {
+            episode_nos.extend(
+                extra_episodes
+                    .as_str()
+                    .split(|char: char| !char.is_ascii_digit())
+                    .filter_map(|episode_no| episode_no.parse::<u32>().ok()),
+            );
+        }
+
+        return Some(ParsedEpisode::Seasonal {
+            season_no,
+            episode_nos,
+        });
+    }
+
+    if let Some(captures) = CROSS.captures(file_stem) {
+        return Some(ParsedEpisode::Seasonal {
+            season_no: captures[1].parse().ok()?,
+            episode_nos: vec![captures[2].parse().ok()?],
+        });
+    }
+
+    let episode_no: u32 = EPISODE_ONLY
+        .captures(file_stem)
+        .and_then(|captures| {
+            captures
+                .iter()
+                .skip(1)
+                .flatten()
+                .next()
+                .map(|capture| capture.as_str())
+        })
+        .and_then(|episode_no| episode_no.parse().ok())?;
+
+    // The closest folder that looks like a season wins
+    let season_no = path
+        .parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .filter_map(|component| match component {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .filter_map(|name| SEASON_FOLDER.captures(name))
+        .filter_map(|captures| captures[1].parse::<u32>().ok())
+        .next_back();
+
+    Some(match season_no {
+        Some(season_no) => ParsedEpisode::Seasonal {
+            season_no,
+            episode_nos: vec![episode_no],
+        },
+        None => ParsedEpisode::Absolute { episode_no },
+    })
+}
+
+/// Only video files are suggested, samples are left out.
+pub fn suggest_file_mapping<'a>(
+    files: impl IntoIterator<Item = &'a str>,
+) -> Vec<FileMappingSuggestion> {
+    let parsed: Vec<(&str, Option<ParsedEpisode>)> = files
+        .into_iter()
+        .filter(|file| domain::format::has_video_extension(file))
+        .filter(|file| !file.to_lowercase().contains("sample"))
+        .map(|file| (file, parse_episode(file)))
+        .collect();
+
+    // Absolute numbers are only trusted when the whole pack uses them
+    let is_absolute_pack = parsed
+        .iter()
+        .all(|(_, episode)| matches!(episode, Some(ParsedEpisode::Absolute { .. })));
+
+    let mut occurrences: HashMap<EpisodeIdentifier, usize> = HashMap::new();
+    parsed
+        .iter()
+        .filter_map(|(_, episode)| episode.as_ref())
+        .flat_map(ParsedEpisode::identifiers)
+        .for_each(|identifier| *occurrences.entry(identifier).or_default() += 1);
+
+    parsed
+        .into_iter()
+        .map(|(file, episode)| {
+            let confident = match &episode {
+                None => false,
+                Some(ParsedEpisode::Absolute { .. }) if !is_absolute_pack => false,
+                // Only one episode can be mapped to a file
+                Some(ParsedEpisode::Seasonal { episode_nos, .. }) if episode_nos.len() > 1 => false,
+                Some(episode) => occurrences[&episode.identifier()] == 1,
+            };
+
+            FileMappingSuggestion {
+                file: file.to_string(),
+                episode: episode.as_ref().map(ParsedEpisode::identifier),
+                confident,
+            }
+        })
+        .collect()
+}
+
+/// Returns the part of the mapping that can be applied without asking, if there is any.
+/// Ambiguous files are left out for the user to map.
+pub fn confident_mapping(suggestions: &[FileMappingSuggestion]) -> Option<SeriesFileMapping> {
+    let mapping: SeriesFileMapping = suggestions
+        .iter()
+        .filter(|suggestion| suggestion.confident)
+        .filter_map(|suggestion| {
+            suggestion
+                .episode
+                .clone()
+                .map(|episode| (suggestion.file.clone(), episode))
+        })
+        .collect();
+
+    (!mapping.is_empty()).then_some(mapping)
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::series::EpisodeIdentifier;
+
+    use super::{ParsedEpisode, confident_mapping, parse_episode, suggest_file_mapping};
+
+    fn seasonal(season_no: u32, episode_nos: &[u32]) -> Option<ParsedEpisode> {
+        Some(ParsedEpisode::Seasonal {
+            season_no,
+            episode_nos: episode_nos.to_vec(),
+        })
+    }
+
+    #[test]
+    fn test_parse_season_episode() {
+        assert_eq!(
+            parse_episode("my-series.S02E01.1080p.x265-HEYYY.mkv"),
+            seasonal(2, &[1])
+        );
+        assert_eq!(parse_episode("my series s1e12.mkv"), seasonal(1, &[12]));
+        assert_eq!(
+            parse_episode("Pack/My.Series.S01E01E02.720p.mkv"),
+            seasonal(1, &[1, 2])
+        );
+        assert_eq!(
+            parse_episode("My.Series.S01E01-E02.720p.mkv"),
+            seasonal(1, &[1, 2])
+        );
+
+        assert!(parse_episode("my-series.S02E.1080p.x265-HEYYY.mkv").is_none());
+        assert!(parse_episode("my-series.S02.1080p.x265-HEYYY.mkv").is_none());
+        assert!(parse_episode("my-series.E2S7.1080p.x265-HEYYY.mkv").is_none());
+        assert!(parse_episode("my-series.SE.1080p.x265-HEYYY.mkv").is_none());
+    }
+
+    #[test]
+    fn test_parse_cross() {
+        assert_eq!(parse_episode("My Series 1x02 Title.mkv"), seasonal(1, &[2]));
+        assert_eq!(parse_episode("My.Series.10x15.mkv"), seasonal(10, &[15]));
+        assert!(parse_episode("My.Movie.1920x1080.mkv").is_none());
+        assert!(parse_episode("My.Movie.DD5.1x264.mkv").is_none());
+    }
+
+    #[test]
+    fn test_parse_season_folder() {
+        assert_eq!(parse_episode("Season 1/02.mkv"), seasonal(1, &[2]));
+        assert_eq!(
+            parse_episode("My Series/Season 03/05 - The Title.mkv"),
+            seasonal(3, &[5])
+        );
+        assert_eq!(
+            parse_episode("My.Series.S02.1080p/My.Series.E07.1080p.mkv"),
+            seasonal(2, &[7])
+        );
+        assert_eq!(parse_episode("S4/Episode 11.mkv"), seasonal(4, &[11]));
+    }
+
+    #[test]
+    fn test_parse_absolute() {
+        assert_eq!(
+            parse_episode("[Group] My Show - 012 [1080p].mkv"),
+            Some(ParsedEpisode::Absolute { episode_no: 12 })
+        );
+        assert_eq!(
+            parse_episode("My Show Ep 104.mkv"),
+            Some(ParsedEpisode::Absolute { episode_no: 104 })
+        );
+        assert!(parse_episode("my-series.1080p.x265-HEYYY.mkv").is_none());
+    }
+
+    #[test]
+    fn test_confident_mapping() {
+        let suggestions = suggest_file_mapping([
+            "pack/My.Series.S01E01.mkv",
+            "pack/My.Series.S01E02.mkv",
+            "pack/My.Series.S01E02.en.srt",
+            "pack/Sample/My.Series.S01E01.sample.mkv",
+            "pack/info.nfo",
+        ]);
+        assert_eq!(suggestions.len(), 2);
+
+        let mapping = confident_mapping(&suggestions).unwrap();
+        assert_eq!(
+            mapping["pack/My.Series.S01E02.mkv"],
+            EpisodeIdentifier {
+                season_no: 1,
+                episode_no: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_files_need_a_human() {
+        // Same episode twice
+        let suggestions =
+            suggest_file_mapping(["My.Series.S01E01.mkv", "My.Series.S01E01.REPACK.mkv"]);
+        assert!(suggestions.iter().all(|suggestion| !suggestion.confident));
+        assert!(confident_mapping(&suggestions).is_none());
+
+        // A multi episode file next to one of its episodes
+        let suggestions = suggest_file_mapping(["My.Series.S01E01E02.mkv", "My.Series.S01E02.mkv"]);
+        assert!(suggestions.iter().all(|suggestion| !suggestion.confident));
+        assert!(confident_mapping(&suggestions).is_none());
+
+        // Absolute numbering mixed with seasonal numbering
+        let suggestions = suggest_file_mapping(["My.Series.S01E01.mkv", "My Series - 02.mkv"]);
+        assert!(suggestions[0].confident);
+        assert!(!suggestions[1].confident);
+        let mapping = confident_mapping(&suggestions).unwrap();
+        assert_eq!(mapping.len(), 1);
+        assert!(mapping.contains_key("My.Series.S01E01.mkv"));
+
+        // Nothing that looks like an episode
+        let suggestions = suggest_file_mapping(["My.Series.S01E01.mkv", "Behind The Scenes.mkv"]);
+        assert_eq!(suggestions[1].episode, None);
+        let mapping = confident_mapping(&suggestions).unwrap();
+        assert_eq!(mapping.len(), 1);
+        assert!(!mapping.contains_key("Behind The Scenes.mkv"));
+
+        // A pack of absolute numbered episodes is fine
+        let suggestions = suggest_file_mapping(["My Show - 01.mkv", "My Show - 02.mkv"]);
+        assert!(confident_mapping(&suggestions).is_some());
+    }
+}
@@ -0,0 +1,398 @@
+//! Brings folders that weren't downloaded through the server into the library.
+//!
+//! Every top level entry of the imported folder is one media item. Video files are movies, folders
+//! are series if their videos have episode numbers, or movies if they only have one video.
+
+use std::{
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use domain::{
+    MediaMetaData,
+    import::{ImportOutcome, ImportedItem, TransferMode},
+    metadata::MediaKind,
+    series::{EditSeriesFileMappingForm, SeriesFileMapping},
+};
+use log::{error, info};
+use regex::Regex;
+
+use crate::library::Libraries;
+
+/// A 4 digit year, optionally in parentheses or brackets
+static YEAR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:^|[ ._\-(\[])((?:19|20)\d{2})(?:$|[ ._\-)\]])").expect("Invalid regex supplied")
+});
+
+/// Where release names stop being the title, e.g. `S01`, `Season 2`, `1080p` or `BluRay`
+static TITLE_END: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(?:^|[ ._\-(\[])(?:s\d{1,2}(?:e\d{1,3})?|season[ ._]?\d{1,2}|complete|\d{3,4}p|4k|uhd|bluray|blu-ray|brrip|bdrip|web-?dl|webrip|hdtv|dvdrip|remux|x26[45]|h\.?26[45]|hevc|proper|repack)(?:$|[ ._\-)\]])",
+    )
+    .expect("Invalid regex supplied")
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedItem {
+    /// Absolute path of the top level entry
+    pub source: PathBuf,
+    pub title: String,
+    pub year: Option<u32>,
+    /// `Err` with the reason if the item can't be imported
+    pub content: Result<ScannedContent, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScannedContent {
+    Movie(PathBuf),
+    /// File mapping is relative to the item's source folder
+    Series(SeriesFileMapping),
+}
+
+/// Guesses the title and the release year from a file or folder name.
+pub fn parse_title(name: &str) -> (String, Option<u32>) {
+    // The title can't be empty, so a leading year like `1917` is part of it
+    let year_match = YEAR
+        .captures_iter(name)
+        .filter_map(|captures| captures.get(1))
+        .find(|year| year.start() > 0);
+    let title_end = TITLE_END
+        .find_iter(name)
+        .map(|found| found.start())
+        .find(|start| *start > 0);
+
+    let end = [year_match.map(|year| year.start()), title_end]
+        .into_iter()
+        .flatten()
+        .min()
+        .unwrap_or(name.len());
+
+    let clean = |name: &str| {
+        name.replace(['.', '_'], " ")
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .trim_end_matches([' ', '-', '(', '['])
+            .to_string()
+    };
+
+    let title = match clean(&name[..end]) {
+        title if title.is_empty() => clean(name),
+        title => title,
+    };
+
+    (
+        title,
+        year_match.and_then(|year| year.as_str().parse().ok()),
+    )
+}
+
+/// Looks at every entry of `source_dir` without changing anything.
+pub async fn scan(source_dir: &Path) -> std::io::Result<Vec<ScannedItem>> {
+    let mut items = Vec::new();
+
+    for entry in crate::dir::fully_read_dir(source_dir).await? {
+        let path = entry.path();
+        let Some(name) = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+        else {
+            continue;
+        };
+        // Hidden files and folders, like `.DS_Store`
+        if name.starts_with('.') {
+            continue;
+        }
+
+        let is_dir = entry.file_type().await?.is_dir();
+        let content = if is_dir {
+            scan_folder(&path).await?
+        } else if !domain::format::has_video_extension(&name) {
+            continue;
+        } else {
+            match crate::file_mapping::parse_episode(&name) {
+                Some(crate::file_mapping::ParsedEpisode::Seasonal { .. }) => {
+                    Err("Episode isn't in a folder of its series".to_string())
+                }
+                _ => Ok(ScannedContent::Movie(path.clone())),
+            }
+        };
+
+        let name = match is_dir {
+            true => name.as_str(),
+            false => Path::new(&name)
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or(&name),
+        };
+        let (title, year) = parse_title(name);
+
+        items.push(ScannedItem {
+            source: path,
+            title,
+            year,
+            content,
+        });
+    }
+
+    items.sort_by(|a, b| a.source.cmp(&b.source));
+
+    Ok(items)
+}
+
+async fn scan_folder(folder: &Path) -> std::io::Result<Result<ScannedContent, String>> {
+    let files = relative_files(folder).await?;
+    let suggestions = crate::file_mapping::suggest_file_mapping(files.iter().map(String::as_str));
+
+    let has_episodes = suggestions
+        .iter()
+        .any(|suggestion| suggestion.episode.is_some());
+    let is_single_episode = matches!(
+        suggestions.as_slice(),
+        [suggestion] if matches!(
+            crate::file_mapping::parse_episode(&suggestion.file),
+            Some(crate::file_mapping::ParsedEpisode::Seasonal { .. })
+        )
+    );
+
+    Ok(match suggestions.as_slice() {
+        [] => Err("No video files".to_string()),
+        [movie] if !is_single_episode => Ok(ScannedContent::Movie(folder.join(&movie.file))),
+        _ if has_episodes => crate::file_mapping::confident_mapping(&suggestions)
+            .map(ScannedContent::Series)
+            .ok_or_else(|| "Couldn't tell which file is which episode".to_string()),
+        _ => Err("Multiple videos without episode numbers".to_string()),
+    })
+}
+
+/// Every file under `folder`, relative to it
+async fn relative_files(folder: &Path) -> std::io::Result<Vec<String>> {
+    let mut files = Vec::new();
+    let mut pending = vec![folder.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        for entry in crate::dir::fully_read_dir(&dir).await? {
+            let path = entry.path();
+            if entry.file_type().await?.is_dir() {
+                pending.push(path);
+            } else if let Ok(relative) = path.strip_prefix(folder) {
+                files.push(relative.to_string_lossy().to_string());
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Moves or links a scanned item into the library with `library_id`, or the default library for
+/// its kind. With `dry_run` only the outcome is reported.
+pub async fn import(
+    libraries: &Libraries,
+    library_id: Option<&str>,
+    item: ScannedItem,
+    mode: TransferMode,
+    dry_run: bool,
+) -> ImportedItem {
+    let metadata = MediaMetaData {
+        title: item.title.clone(),
+        year: item.year,
+        ..Default::default()
+    };
+    let media_id = crate::moving::media_id(&metadata);
+
+    let episode_count = match &item.content {
+        Ok(ScannedContent::Series(mapping)) => Some(mapping.len() as u32),
+        _ => None,
+    };
+    let kind = match episode_count {
+        Some(_) => MediaKind::Series,
+        None => MediaKind::Movie,
+    };
+    let library_root = library_id
+        .and_then(|library_id| libraries.get(library_id))
+        .unwrap_or_else(|| libraries.default_for(&kind));
+
+    let outcome = match item.content {
+        Err(reason) => ImportOutcome::Skipped { reason },
+        // Media ids are unique across libraries
+        Ok(_) if libraries.find_media_folder(&media_id).await.is_some() => ImportOutcome::Skipped {
+            reason: format!("{media_id} is already in the library"),
+        },
+        Ok(_) if dry_run => ImportOutcome::WouldImport { media_id },
+        Ok(content) => {
+            match transfer(&library_root.root, &item.source, content, &metadata, mode).await {
+                Ok(_) => {
+                    info!(
+                        "Imported {} as {media_id} into {}",
+                        item.source.display(),
+                        library_root.library.name
+                    );
+                    ImportOutcome::Imported { media_id }
+                }
+                Err(err) => {
+                    error!("Couldn't import {}. Reason: {err}", item.source.display());
+                    ImportOutcome::Failed {
+                        message: err.to_string(),
+                    }
+                }
+            }
+        }
+    };
+
+    ImportedItem {
+        source: item
+            .source
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        title: item.title,
+        year: item.year,
+        episode_count,
+        outcome,
+    }
+}
+
+async fn transfer(
+    media_dir: &Path,
+    source: &Path,
+    content: ScannedContent,
+    metadata: &MediaMetaData,
+    mode: TransferMode,
+) -> crate::moving::Result<()> {
+    match content {
+        ScannedContent::Movie(movie_file) => {
+            crate::moving::generate_movie_media(media_dir, &movie_file, metadata, mode).await?;
+        }
+        ScannedContent::Series(file_mapping) => {
+            let allowed_files: Vec<String> = file_mapping.keys().cloned().collect();
+            let form = EditSeriesFileMappingForm {
+                id: crate::moving::media_id(metadata).into(),
+                file_mapping,
+                phantom: PhantomData,
+            }
+            .validate(&allowed_files)
+            .expect("Confident mappings to have unique episodes");
+
+            crate::moving::generate_series_media(media_dir, source, form, metadata, mode).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use domain::import::{ImportOutcome, TransferMode};
+
+    use super::{ScannedContent, import, parse_title, scan};
+    use crate::library::Libraries;
+
+    #[test]
+    fn test_parse_title() {
+        assert_eq!(
+            parse_title("The.Matrix.1999.1080p.BluRay.x264"),
+            ("The Matrix".to_string(), Some(1999))
+        );
+        assert_eq!(
+            parse_title("Breaking Bad (2008) Season 1-5"),
+            ("Breaking Bad".to_string(), Some(2008))
+        );
+        assert_eq!(parse_title("1917 (2019)"), ("1917".to_string(), Some(2019)));
+        assert_eq!(
+            parse_title("My_Show.S01.720p.WEB-DL"),
+            ("My Show".to_string(), None)
+        );
+        assert_eq!(parse_title("Heat"), ("Heat".to_string(), None));
+    }
+
+    async fn touch(path: &Path) {
+        tokio::fs::create_dir_all(path.parent().unwrap())
+            .await
+            .unwrap();
+        tokio::fs::write(path, "").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scan() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+
+        touch(&root.join("Heat.1995.mkv")).await;
+        touch(&root.join("Loose.Show.S01E01.mkv")).await;
+        touch(&root.join("notes.txt")).await;
+        touch(&root.join("Alien (1979)/Alien.1979.mkv")).await;
+        touch(&root.join("Alien (1979)/Sample/alien-sample.mkv")).await;
+        touch(&root.join("My.Show/Season 1/My.Show.S01E01.mkv")).await;
+        touch(&root.join("My.Show/Season 1/My.Show.S01E02.mkv")).await;
+        touch(&root.join("My.Show/Behind The Scenes.mkv")).await;
+        touch(&root.join("Extras/one.mkv")).await;
+        touch(&root.join("Extras/two.mkv")).await;
+        touch(&root.join("Empty/cover.jpg")).await;
+
+        let items = scan(root).await.unwrap();
+        let find = |title: &str| items.iter().find(|item| item.title == title).unwrap();
+
+        assert_eq!(items.len(), 6);
+        assert_eq!(find("Heat").year, Some(1995));
+        assert_eq!(
+            find("Heat").content,
+            Ok(ScannedContent::Movie(root.join("Heat.1995.mkv")))
+        );
+        assert!(find("Loose Show").content.is_err());
+        assert_eq!(
+            find("Alien").content,
+            Ok(ScannedContent::Movie(
+                root.join("Alien (1979)/Alien.1979.mkv")
+            ))
+        );
+        assert!(matches!(
+            &find("My Show").content,
+            Ok(ScannedContent::Series(mapping)) if mapping.len() == 2
+        ));
+        assert!(find("Extras").content.is_err());
+        assert!(find("Empty").content.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("import");
+        let media_dir = tmp.path().join("media");
+        touch(&root.join("My.Show/My.Show.S01E01.mkv")).await;
+        touch(&root.join("My.Show/My.Show.S01E02.mkv")).await;
+        tokio::fs::create_dir_all(&media_dir).await.unwrap();
+        let libraries = Libraries::new(Vec::new(), &media_dir);
+
+        let item = scan(&root).await.unwrap().remove(0);
+
+        let outcome = import(&libraries, None, item.clone(), TransferMode::HardLink, true).await;
+        assert_eq!(outcome.episode_count, Some(2));
+        assert!(matches!(outcome.outcome, ImportOutcome::WouldImport { .. }));
+        assert!(!media_dir.join("My_Show").exists());
+
+        let outcome = import(
+            &libraries,
+            None,
+            item.clone(),
+            TransferMode::HardLink,
+            false,
+        )
+        .await;
+        assert_eq!(
+            outcome.outcome,
+            ImportOutcome::Imported {
+                media_id: "My_Show".to_string()
+            }
+        );
+        assert!(media_dir.join("My_Show/meta.json").exists());
+        assert!(media_dir.join("My_Show/1").exists());
+        // Hard links keep the originals
+        assert!(root.join("My.Show/My.Show.S01E01.mkv").exists());
+
+        let outcome = import(&libraries, None, item, TransferMode::HardLink, false).await;
+        assert!(matches!(outcome.outcome, ImportOutcome::Skipped { .. }));
+    }
+}
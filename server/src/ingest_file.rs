@@ -0,0 +1,154 @@
+//! Reads and writes `ingest.json`, the file that records when a media folder and each of its
+//! episodes entered the library.
+//!
+//! Media without the file, e.g. media that was copied into the library by hand, falls back to the
+//! time its folder was created.
+
+use std::{collections::HashMap, path::Path, time::SystemTime};
+
+use domain::series::EpisodeIdentifier;
+use log::warn;
+
+pub const INGEST_FILE_NAME: &str = "ingest.json";
+
+#[derive(Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct IngestRecord {
+    pub added_on: Option<SystemTime>,
+    /// Season no -> episode no -> when the episode was added
+    #[serde(default)]
+    pub episodes: HashMap<u32, HashMap<u32, SystemTime>>,
+}
+
+impl IngestRecord {
+    pub fn episode(&self, episode: &EpisodeIdentifier) -> Option<SystemTime> {
+        self.episodes
+            .get(&episode.season_no)
+            .and_then(|season| season.get(&episode.episode_no))
+            .copied()
+    }
+
+    /// The media keeps the time it was first added, episodes are updated since a download may
+    /// replace them
+    fn add(&mut self, episodes: impl IntoIterator<Item = EpisodeIdentifier>, now: SystemTime) {
+        self.added_on.get_or_insert(now);
+
+        for episode in episodes {
+            self.episodes
+                .entry(episode.season_no)
+                .or_default()
+                .insert(episode.episode_no, now);
+        }
+    }
+}
+
+/// Empty if the media folder has no ingest file, or it's unreadable
+pub async fn read(media_folder: impl AsRef<Path>) -> IngestRecord {
+    let ingest_path = media_folder.as_ref().join(INGEST_FILE_NAME);
+
+    match try_read(&ingest_path).await {
+        Ok(record) => record,
+        Err(Error::CantRead(err)) if err.kind() == std::io::ErrorKind::NotFound => {
+            IngestRecord::default()
+        }
+        Err(err) => {
+            warn!(
+                "Ignoring ingest record at {}. Reason: {err}",
+                ingest_path.display()
+            );
+            IngestRecord::default()
+        }
+    }
+}
+
+async fn try_read(ingest_path: &Path) -> Result<IngestRecord> {
+    let ingest_string = tokio::fs::read_to_string(ingest_path)
+        .await
+        .map_err(Error::CantRead)?;
+
+    serde_json::from_str(&ingest_string).map_err(Error::CantParse)
+}
+
+/// Records that the media at `media_folder` and its `episodes` entered the library at `now`
+pub async fn record(
+    media_folder: impl AsRef<Path>,
+    episodes: impl IntoIterator<Item = EpisodeIdentifier>,
+    now: SystemTime,
+) -> Result<()> {
+    let ingest_path = media_folder.as_ref().join(INGEST_FILE_NAME);
+
+    let mut record = read(&media_folder).await;
+    record.add(episodes, now);
+
+    let ingest_string = serde_json::to_string_pretty(&record).map_err(Error::CantSerialize)?;
+
+    // Write to a temporary file first so a crash can't leave a half written record behind
+    let temporary_path = ingest_path.with_extension("json.tmp");
+    tokio::fs::write(&temporary_path, ingest_string)
+        .await
+        .map_err(Error::CantWrite)?;
+    tokio::fs::rename(&temporary_path, &ingest_path)
+        .await
+        .map_err(Error::CantWrite)
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Can't read ingest record. {0}")]
+    CantRead(std::io::Error),
+    #[error("Can't parse ingest record. {0}")]
+    CantParse(serde_json::Error),
+    #[error("Can't serialize ingest record. {0}")]
+    CantSerialize(serde_json::Error),
+    #[error("Can't write ingest record. {0}")]
+    CantWrite(std::io::Error),
+}
+
+type Result<T> = core::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use domain::series::EpisodeIdentifier;
+
+    use super::{INGEST_FILE_NAME, read, record};
+
+    fn episode(season_no: u32, episode_no: u32) -> EpisodeIdentifier {
+        EpisodeIdentifier {
+            season_no,
+            episode_no,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record() {
+        let tmp = tempfile::tempdir().unwrap();
+        let first = UNIX_EPOCH + Duration::from_secs(100);
+        let second = UNIX_EPOCH + Duration::from_secs(200);
+
+        record(tmp.path(), [episode(1, 1), episode(1, 2)], first)
+            .await
+            .unwrap();
+        record(tmp.path(), [episode(1, 2), episode(2, 1)], second)
+            .await
+            .unwrap();
+
+        let ingest = read(tmp.path()).await;
+        assert_eq!(ingest.added_on, Some(first));
+        assert_eq!(ingest.episode(&episode(1, 1)), Some(first));
+        assert_eq!(ingest.episode(&episode(1, 2)), Some(second));
+        assert_eq!(ingest.episode(&episode(2, 1)), Some(second));
+        assert_eq!(ingest.episode(&episode(3, 1)), None);
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_record_is_empty() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert_eq!(read(tmp.path()).await, Default::default());
+
+        tokio::fs::write(tmp.path().join(INGEST_FILE_NAME), "{ not json")
+            .await
+            .unwrap();
+        assert_eq!(read(tmp.path()).await, Default::default());
+    }
+}
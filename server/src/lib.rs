@@ -1,34 +1,114 @@
+pub mod artwork;
+// Handlers return `axum::response::Result`, whose error type is large and not ours to box
+#[allow(clippy::result_large_err)]
+pub mod collection_handlers;
 pub mod crawl;
 pub mod dir;
+#[allow(clippy::result_large_err)]
 pub mod download_handlers;
+pub mod file_mapping;
+pub mod import;
+pub mod ingest_file;
+pub mod library;
+#[allow(clippy::result_large_err)]
+pub mod media_handlers;
+pub mod metadata_file;
 pub mod moving;
 pub mod prepare;
+pub mod search;
 pub mod service;
 pub mod signal;
+#[allow(clippy::result_large_err)]
 pub mod subtitle_handlers;
+pub mod subtitle_providers;
+pub mod subtitle_sync;
 #[cfg(test)]
 pub mod test_utils;
-use std::{
-    path::{Path, PathBuf},
-    sync::Arc,
-};
+pub mod thumbnails;
+use std::{path::PathBuf, sync::Arc};
 
 use clap::Parser;
 
 #[derive(Parser, Clone)]
 #[command(about = "Launches a streamy server.")]
 pub struct Args {
-    /// Path to the media library.
+    /// Path to the media library. Downloads and server data are kept here even if libraries are
+    /// set with `--library`.
     #[arg(short, long, default_value = "./media")]
     pub media_dir: PathBuf,
 
+    /// A library in `NAME[:KIND]=PATH` form, e.g. `Anime:series=/mnt/anime`. KIND is one of
+    /// `mixed`, `movies` or `series` and defaults to `mixed`. Can be repeated.
+    /// The media dir is the only library if not set.
+    #[arg(long = "library")]
+    pub libraries: Vec<library::LibraryRoot>,
+
+    /// A folder media can be imported from, e.g. the download folder of another client. Only
+    /// folders inside it can be imported. Can be repeated, importing is off if not set.
+    #[arg(long = "import-dir")]
+    pub import_dirs: Vec<PathBuf>,
+
     /// The name displayed when server is automatically discovered by a client.
     /// Defaults to your machine's host name.
     #[arg(long, default_value_t = Args::default_name())]
     pub name: String,
+
+    /// How many downloads can run at the same time, the rest wait in the queue.
+    /// Every download runs at once if not set.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_active_downloads: Option<u32>,
+
+    /// API key for OpenSubtitles, which is only searched when set.
+    #[arg(long, env = "OPEN_SUBTITLES_API_KEY")]
+    pub open_subtitles_api_key: Option<String>,
+
+    /// OpenSubtitles user to download as. Downloads count against the anonymous quota of the API
+    /// key if not set.
+    #[arg(
+        long,
+        env = "OPEN_SUBTITLES_USERNAME",
+        requires = "open_subtitles_password"
+    )]
+    pub open_subtitles_username: Option<String>,
+
+    /// Password of the OpenSubtitles user. Prefer the environment variable, arguments show up in
+    /// process lists.
+    #[arg(
+        long,
+        env = "OPEN_SUBTITLES_PASSWORD",
+        requires = "open_subtitles_username",
+        hide_env_values = true
+    )]
+    pub open_subtitles_password: Option<String>,
+
+    /// API key for SubDL, searched next to OpenSubtitles when set.
+    #[arg(long, env = "SUBDL_API_KEY")]
+    pub subdl_api_key: Option<String>,
 }
 
 impl Args {
+    pub fn libraries(&self) -> library::Libraries {
+        library::Libraries::new(self.libraries.clone(), &self.media_dir)
+    }
+
+    pub fn subtitle_providers(&self) -> subtitle_providers::SubtitleProviders {
+        let mut providers = subtitle_providers::SubtitleProviders::default();
+
+        if let Some(api_key) = &self.open_subtitles_api_key {
+            let client = open_subtitles::OpenSubtitlesClient::new(api_key);
+            let client = match (&self.open_subtitles_username, &self.open_subtitles_password) {
+                (Some(username), Some(password)) => client.with_login(username, password),
+                _ => client,
+            };
+            providers = providers.with_open_subtitles(client);
+        }
+
+        match &self.subdl_api_key {
+            Some(api_key) => providers.with_subdl(subdl::SubdlClient::new(api_key)),
+            None => providers,
+        }
+    }
+
     fn default_name() -> String {
         gethostname::gethostname().to_string_lossy().to_string()
     }
@@ -36,13 +116,18 @@ impl Args {
 
 #[derive(Clone)]
 pub struct AppState {
-    pub subtitle_provider: open_subtitles::OpenSubtitlesClient,
-    pub media_dir: Arc<Path>,
+    pub subtitle_provider: subtitle_providers::SubtitleProviders,
+    pub libraries: library::Libraries,
+    /// Folders the import route is allowed to read from
+    pub import_dirs: Arc<[PathBuf]>,
     pub media_signal_watcher: service::media::MediaSignalWatcher,
     pub download_signal_watcher: service::download::DownloadSignalWatcher,
+    pub download_history_watcher: service::history::HistorySignalWatcher,
+    pub collections_signal_watcher: service::collections::CollectionsSignalWatcher,
     pub processing_list_watcher: service::process::ProcessingListWatcher,
     pub subtitle_signal_sender: service::subtitle::SubtitleSignalSender,
     pub preparing_list_watcher: service::prepare::PreparingListWatcher,
+    pub auto_sync_watcher: service::auto_sync::AutoSyncWatcher,
 }
 
 pub type State = axum::extract::State<AppState>;
@@ -0,0 +1,225 @@
+//! Media can be spread over several libraries, each with its own root folder. Paths in the media
+//! library start with the id of the library the file is in, which is also where the library is
+//! served under `/static`.
+
+use std::{
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+use domain::{
+    Media, MediaIdentifier,
+    library::{Library, LibraryKind},
+    metadata::MediaKind,
+};
+
+/// Used when the server is launched without any `--library`
+pub const DEFAULT_LIBRARY_ID: &str = "media";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LibraryRoot {
+    pub library: Library,
+    pub root: PathBuf,
+}
+
+impl LibraryRoot {
+    /// Folder of the media item with `media_id`
+    pub fn media_folder(&self, media_id: &str) -> PathBuf {
+        self.root.join(media_id)
+    }
+}
+
+/// Parses `NAME[:KIND]=PATH`, e.g. `Kids=/mnt/kids` or `Anime:series=/mnt/anime`
+impl FromStr for LibraryRoot {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (library, root) = value
+            .split_once('=')
+            .ok_or_else(|| format!("Expected NAME[:KIND]=PATH, got {value}"))?;
+
+        let (name, kind) = match library.split_once(':') {
+            Some((name, kind)) => (name, kind),
+            None => (library, "mixed"),
+        };
+        let kind = match kind.to_lowercase().as_str() {
+            "mixed" => LibraryKind::Mixed,
+            "movies" => LibraryKind::Movies,
+            "series" => LibraryKind::Series,
+            _ => return Err(format!("Unknown library kind {kind}")),
+        };
+
+        let name = name.trim();
+        let id = crate::moving::sanitize_name_for_url(name).to_lowercase();
+        if id.is_empty() || root.is_empty() {
+            return Err(format!("Expected NAME[:KIND]=PATH, got {value}"));
+        }
+
+        Ok(LibraryRoot {
+            library: Library {
+                id,
+                name: name.to_string(),
+                kind,
+            },
+            root: PathBuf::from(root),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Libraries(Arc<[LibraryRoot]>);
+
+impl Libraries {
+    /// Falls back to a single library at `default_root` if `roots` is empty.
+    /// Libraries with an id that's already taken are left out.
+    pub fn new(roots: Vec<LibraryRoot>, default_root: impl Into<PathBuf>) -> Self {
+        if roots.is_empty() {
+            return Self(Arc::from([LibraryRoot {
+                library: Library {
+                    id: DEFAULT_LIBRARY_ID.to_string(),
+                    name: "Media".to_string(),
+                    kind: LibraryKind::Mixed,
+                },
+                root: default_root.into(),
+            }]));
+        }
+
+        let mut unique_roots: Vec<LibraryRoot> = Vec::with_capacity(roots.len());
+        for root in roots {
+            if unique_roots
+                .iter()
+                .any(|unique| unique.library.id == root.library.id)
+            {
+                log::warn!(
+                    "There is more than one library with id {}. Ignoring {}",
+                    root.library.id,
+                    root.root.display()
+                );
+                continue;
+            }
+            unique_roots.push(root);
+        }
+
+        Self(Arc::from(unique_roots))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &LibraryRoot> {
+        self.0.iter()
+    }
+
+    pub fn list(&self) -> Vec<Library> {
+        self.iter().map(|root| root.library.clone()).collect()
+    }
+
+    pub fn get(&self, library_id: &str) -> Option<&LibraryRoot> {
+        self.iter().find(|root| root.library.id == library_id)
+    }
+
+    /// The first library that takes media of `kind`, or the first library if none does
+    pub fn default_for(&self, kind: &MediaKind) -> &LibraryRoot {
+        self.iter()
+            .find(|root| root.library.kind.accepts(kind))
+            .unwrap_or(&self.0[0])
+    }
+
+    /// Where a path of the media library is on disk
+    pub fn resolve(&self, path: impl AsRef<Path>) -> Option<PathBuf> {
+        let mut components = path.as_ref().components();
+        let Some(Component::Normal(library_id)) = components.next() else {
+            return None;
+        };
+        let root = self.get(library_id.to_str()?)?;
+
+        Some(root.root.join(components.as_path()))
+    }
+
+    /// Folder of `media` on disk
+    pub fn media_folder(&self, media: &Media) -> Option<PathBuf> {
+        self.get(&media.library_id)
+            .map(|root| root.media_folder(&media.id))
+    }
+
+    /// The library that has a folder for `media_id`, along with the folder
+    pub async fn find_media_folder(&self, media_id: &str) -> Option<(&LibraryRoot, PathBuf)> {
+        for root in self.iter() {
+            let folder = root.media_folder(media_id);
+            if tokio::fs::try_exists(&folder).await.unwrap_or(false) {
+                return Some((root, folder));
+            }
+        }
+
+        None
+    }
+
+    /// Turns the paths of an identifier that points to a file on disk into media library paths
+    pub fn relative_identifier(&self, identifier: MediaIdentifier) -> Option<MediaIdentifier> {
+        let root = self
+            .iter()
+            .find(|root| Path::new(&identifier.path().media).starts_with(&root.root))?;
+        let paths = identifier
+            .path()
+            .strip_prefix(&root.root)?
+            .add_prefix(&root.library.id);
+
+        Some(identifier.with_path(paths))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use domain::{library::LibraryKind, metadata::MediaKind};
+
+    use super::{DEFAULT_LIBRARY_ID, Libraries, LibraryRoot};
+
+    #[test]
+    fn test_parse_library() {
+        let root: LibraryRoot = "Anime:series=/mnt/anime".parse().unwrap();
+        assert_eq!(root.library.id, "anime");
+        assert_eq!(root.library.name, "Anime");
+        assert_eq!(root.library.kind, LibraryKind::Series);
+        assert_eq!(root.root, PathBuf::from("/mnt/anime"));
+
+        let root: LibraryRoot = "Kids Movies=/mnt/kids".parse().unwrap();
+        assert_eq!(root.library.id, "kids_movies");
+        assert_eq!(root.library.kind, LibraryKind::Mixed);
+
+        assert!("/mnt/kids".parse::<LibraryRoot>().is_err());
+        assert!("Kids:cartoons=/mnt/kids".parse::<LibraryRoot>().is_err());
+        assert!("=/mnt/kids".parse::<LibraryRoot>().is_err());
+    }
+
+    #[test]
+    fn test_libraries() {
+        let default = Libraries::new(Vec::new(), "./media");
+        assert_eq!(default.list().len(), 1);
+        assert_eq!(
+            default.resolve(format!("{DEFAULT_LIBRARY_ID}/My_Movie/My_Movie.mp4")),
+            Some(PathBuf::from("./media/My_Movie/My_Movie.mp4"))
+        );
+
+        let libraries = Libraries::new(
+            vec![
+                "Movies:movies=/mnt/a/movies".parse().unwrap(),
+                "Anime:series=/mnt/b/anime".parse().unwrap(),
+                "Movies=/mnt/c/movies".parse().unwrap(),
+            ],
+            "./media",
+        );
+
+        // Duplicate ids are left out
+        assert_eq!(libraries.list().len(), 2);
+        assert_eq!(
+            libraries.default_for(&MediaKind::Series).library.id,
+            "anime"
+        );
+        assert_eq!(
+            libraries.resolve("anime/My_Series/1/1.mp4"),
+            Some(PathBuf::from("/mnt/b/anime/My_Series/1/1.mp4"))
+        );
+        assert_eq!(libraries.resolve("kids/My_Movie"), None);
+        assert_eq!(libraries.resolve("/movies/My_Movie"), None);
+    }
+}
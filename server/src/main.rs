@@ -5,10 +5,12 @@ use axum::{
     routing::{get, post},
 };
 use clap::Parser;
-use domain::Media;
+use domain::{Media, library::Library};
 use log::{error, info};
-use open_subtitles::OpenSubtitlesClient;
-use server::{AppState, Args, State, download_handlers, prepare, subtitle_handlers};
+use server::{
+    AppState, Args, State, artwork, collection_handlers, download_handlers, media_handlers,
+    prepare, subtitle_handlers,
+};
 use tokio::net::TcpListener;
 use tower_http::services::ServeDir;
 
@@ -21,7 +23,7 @@ async fn main() {
         download_path.push("qbittorrent");
         download_path
     };
-    let subtitle_provider = OpenSubtitlesClient::new();
+    let subtitle_provider = args.subtitle_providers();
 
     let (media_signal_watcher, media_signal_receiver): (
         server::service::media::MediaSignalWatcher,
@@ -31,6 +33,14 @@ async fn main() {
         server::service::download::DownloadSignalWatcher,
         _,
     ) = server::signal::new_watcher_receiver_pair(Box::new([]));
+    let (download_history_watcher, download_history_receiver): (
+        server::service::history::HistorySignalWatcher,
+        _,
+    ) = server::signal::new_watcher_receiver_pair(Box::new([]));
+    let (collections_signal_watcher, collections_signal_receiver): (
+        server::service::collections::CollectionsSignalWatcher,
+        _,
+    ) = server::signal::new_watcher_receiver_pair(Box::new([]));
     let processing_list_watcher =
         server::service::process::ProcessingListWatcher::new(Box::new([]));
 
@@ -44,19 +54,31 @@ async fn main() {
         _,
     ) = server::signal::new_watcher_receiver_pair((Vec::new(), Vec::new()));
 
+    let (auto_sync_watcher, auto_sync_receiver): (server::service::auto_sync::AutoSyncWatcher, _) =
+        server::signal::new_watcher_receiver_pair(Vec::new());
+
     let shared_state = AppState {
         preparing_list_watcher,
+        auto_sync_watcher,
         subtitle_provider,
         subtitle_signal_sender,
         media_signal_watcher,
         download_signal_watcher,
+        download_history_watcher,
+        collections_signal_watcher,
         processing_list_watcher,
-        media_dir: Arc::from(args.media_dir.clone().into_boxed_path()),
+        libraries: args.libraries(),
+        import_dirs: Arc::from(args.import_dirs.clone()),
     };
 
+    // Libraries written by older versions are brought up to date before the first crawl
+    for library_root in shared_state.libraries.iter() {
+        server::metadata_file::migrate_library(&library_root.root).await;
+    }
+
     let abort_services = {
         let media_watcher_join_handler = server::service::media::spawn(
-            args.media_dir.clone(),
+            shared_state.libraries.clone(),
             media_signal_receiver,
             shared_state.media_signal_watcher.clone(),
             shared_state.preparing_list_watcher.clone(),
@@ -67,11 +89,23 @@ async fn main() {
             download_path,
             download_signal_receiver,
             shared_state.download_signal_watcher.clone(),
+            args.max_active_downloads,
+        )
+        .await;
+
+        let history_handle = server::service::history::spawn(
+            args.media_dir.join("download_history.json"),
+            download_history_receiver,
+        )
+        .await;
+
+        let collections_handle = server::service::collections::spawn(
+            args.media_dir.join("collections.json"),
+            collections_signal_receiver,
         )
         .await;
 
-        let torrent_watcher_handle =
-            server::service::process::spawn(args.media_dir.clone(), shared_state.clone());
+        let torrent_watcher_handle = server::service::process::spawn(shared_state.clone());
 
         let mdns_handle = server::service::mdns::spawn(&args.name, 3000);
         // No need to halt, just log if we can't register Zeroconf.
@@ -86,31 +120,112 @@ async fn main() {
         let prepare_handle =
             server::service::prepare::spawn(preparing_list_receiver, shared_state.clone());
 
+        let auto_sync_handle =
+            server::service::auto_sync::spawn(auto_sync_receiver, shared_state.clone());
+
+        let thumbnails_handle = server::service::thumbnails::spawn(shared_state.clone());
+
         move || {
             media_watcher_join_handler.abort();
             bittorrent_client_join_handle.abort();
             torrent_watcher_handle.abort();
+            history_handle.abort();
+            collections_handle.abort();
             subtitle_handle.abort();
             prepare_handle.abort();
+            auto_sync_handle.abort();
+            thumbnails_handle.abort();
             let _ = mdns_handle.map(|handle| handle.shutdown());
         }
     };
 
-    let app = Router::new()
-        .nest_service("/static", ServeDir::new(args.media_dir))
+    // Paths in the media library start with the library id
+    let app = shared_state
+        .libraries
+        .iter()
+        .fold(Router::new(), |router, library_root| {
+            router.nest_service(
+                &format!("/static/{}", library_root.library.id),
+                ServeDir::new(&library_root.root),
+            )
+        })
         .route("/health", get(health_handler))
         .route("/get_movies", get(movie_list_handler))
+        .route("/libraries", get(library_list_handler))
+        .route(
+            "/artwork/{media_id}/{variant}",
+            get(artwork::handlers::get_artwork),
+        )
+        .route("/media/search", get(media_handlers::search_media))
+        .route("/media/recent", get(media_handlers::recently_added))
+        .route("/media/edit-metadata", post(media_handlers::edit_metadata))
+        .route("/media/delete", post(media_handlers::delete_media))
+        .route("/media/rename", post(media_handlers::rename_media))
+        .route("/library/import", post(media_handlers::import_media))
+        .route(
+            "/media/edit-episode-metadata",
+            post(media_handlers::edit_episode_metadata),
+        )
+        .route("/collections", get(collection_handlers::get_collections))
+        .route(
+            "/collections/create",
+            post(collection_handlers::create_collection),
+        )
+        .route(
+            "/collections/update",
+            post(collection_handlers::update_collection),
+        )
+        .route(
+            "/collections/delete",
+            post(collection_handlers::delete_collection),
+        )
+        .route(
+            "/collections/add-media",
+            post(collection_handlers::add_to_collection),
+        )
+        .route(
+            "/collections/remove-media",
+            post(collection_handlers::remove_from_collection),
+        )
+        .route(
+            "/collections/move",
+            post(collection_handlers::move_collection),
+        )
         .route("/download/add", post(download_handlers::add_download))
         .route("/download/remove", post(download_handlers::remove_download))
         .route(
             "/download/torrent-contents",
             get(download_handlers::get_torrent_contents),
         )
+        .route(
+            "/download/file-mapping-suggestions",
+            get(download_handlers::get_file_mapping_suggestions),
+        )
         .route(
             "/download/set-file-mapping",
             post(download_handlers::update_file_mapping),
         )
         .route("/download/get", get(download_handlers::get_downloads))
+        .route(
+            "/download/history",
+            get(download_handlers::get_download_history),
+        )
+        .route(
+            "/download/history/clear",
+            post(download_handlers::clear_download_history),
+        )
+        .route(
+            "/download/set-queue-position",
+            post(download_handlers::set_queue_position),
+        )
+        .route(
+            "/download/queue-settings",
+            get(download_handlers::get_queue_settings),
+        )
+        .route(
+            "/download/set-queue-settings",
+            post(download_handlers::set_queue_settings),
+        )
         .route(
             "/subtitles/search",
             post(subtitle_handlers::search_subtitles),
@@ -119,6 +234,16 @@ async fn main() {
             "/subtitles/download",
             post(subtitle_handlers::download_subtitles),
         )
+        .route("/subtitles/quota", get(subtitle_handlers::get_quota))
+        .route("/subtitles/sync", post(subtitle_handlers::sync_subtitle))
+        .route(
+            "/subtitles/auto-sync",
+            get(subtitle_handlers::get_auto_sync_queue).post(subtitle_handlers::auto_sync_subtitle),
+        )
+        .route(
+            "/subtitles/{format}/{*path}",
+            get(subtitle_handlers::converted_subtitle),
+        )
         .route(
             "/prepare/get",
             get(prepare::handlers::handle_get_preparing_items),
@@ -142,8 +267,33 @@ async fn main() {
     abort_services()
 }
 
-async fn movie_list_handler(extract::State(state): State) -> Json<Box<[Media]>> {
-    Json(state.media_signal_watcher.data.borrow().as_ref().into())
+#[derive(serde::Deserialize)]
+struct MediaListQuery {
+    /// Only lists media of this library
+    library: Option<String>,
+}
+
+async fn movie_list_handler(
+    extract::State(state): State,
+    extract::Query(query): extract::Query<MediaListQuery>,
+) -> Json<Box<[Media]>> {
+    let media = state.media_signal_watcher.data.borrow().clone();
+    Json(
+        media
+            .into_iter()
+            .filter(|media| {
+                query
+                    .library
+                    .as_ref()
+                    .is_none_or(|library_id| &media.library_id == library_id)
+            })
+            .map(artwork::with_artwork_url)
+            .collect(),
+    )
+}
+
+async fn library_list_handler(extract::State(state): State) -> Json<Vec<Library>> {
+    Json(state.libraries.list())
 }
 
 async fn health_handler() -> String {
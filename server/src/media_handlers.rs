@@ -0,0 +1,456 @@
+use std::{
+    collections::HashSet,
+    path::{Component, Path, PathBuf},
+};
+
+use axum::{Json, extract, http::StatusCode};
+use domain::{
+    DeleteMediaForm, EditEpisodeMetaDataForm, EditMediaMetaDataForm, Media, MediaContent,
+    MediaMetaData, MediaPaths, RenameMediaForm,
+    import::{ImportForm, ImportOutcome, ImportedItem},
+    search::{MediaQuery, MediaSearchResults, RecentlyAdded, RecentlyAddedQuery},
+};
+use log::{error, info, warn};
+
+use super::State;
+use crate::service::media::MediaSignal;
+
+pub async fn search_media(
+    extract::State(state): State,
+    extract::Query(query): extract::Query<MediaQuery>,
+) -> Json<MediaSearchResults> {
+    let library = state.media_signal_watcher.data.borrow().clone();
+    let (preparing, pending_track_selection) = state.preparing_list_watcher.data.borrow().clone();
+    let preparing: HashSet<&str> = preparing
+        .iter()
+        .chain(pending_track_selection.iter().map(|item| &item.media))
+        .map(|identifier| identifier.id())
+        .collect();
+
+    let mut results = crate::search::search(&library, &preparing, &query);
+    results.items = results
+        .items
+        .into_iter()
+        .map(crate::artwork::with_artwork_url)
+        .collect();
+
+    Json(results)
+}
+
+pub async fn recently_added(
+    extract::State(state): State,
+    extract::Query(query): extract::Query<RecentlyAddedQuery>,
+) -> Json<Vec<RecentlyAdded>> {
+    let library = state.media_signal_watcher.data.borrow().clone();
+
+    let items = crate::search::recently_added(&library, &query)
+        .into_iter()
+        .map(|item| RecentlyAdded {
+            media: crate::artwork::with_artwork_url(item.media),
+            ..item
+        })
+        .collect();
+
+    Json(items)
+}
+
+pub async fn edit_metadata(
+    extract::State(state): State,
+    Json(form): Json<EditMediaMetaDataForm>,
+) -> axum::response::Result<()> {
+    if form.metadata.title.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+
+    let media = find_media(&state, &form.media_id)?;
+
+    save_and_recrawl(&state, &media, form.metadata).await
+}
+
+pub async fn edit_episode_metadata(
+    extract::State(state): State,
+    Json(form): Json<EditEpisodeMetaDataForm>,
+) -> axum::response::Result<()> {
+    let media = find_media(&state, &form.media_id)?;
+    media
+        .get_media_paths(Some(&form.episode))
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let mut metadata = media.metadata.clone();
+    metadata
+        .episodes
+        .entry(form.episode.season_no)
+        .or_default()
+        .insert(form.episode.episode_no, form.metadata);
+
+    save_and_recrawl(&state, &media, metadata).await
+}
+
+fn find_media(state: &crate::AppState, media_id: &str) -> axum::response::Result<Media> {
+    state
+        .media_signal_watcher
+        .data
+        .borrow()
+        .iter()
+        .find(|media| media.id == media_id)
+        .cloned()
+        .ok_or_else(|| StatusCode::NOT_FOUND.into())
+}
+
+async fn save_and_recrawl(
+    state: &crate::AppState,
+    media: &Media,
+    metadata: MediaMetaData,
+) -> axum::response::Result<()> {
+    let media_folder = state
+        .libraries
+        .media_folder(media)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    save_metadata(&media_folder, media, metadata).await?;
+    recrawl(state, &media.id).await
+}
+
+/// Writes `meta.json`, the cached artwork is only dropped when its source changed
+async fn save_metadata(
+    media_folder: &Path,
+    media: &Media,
+    mut metadata: MediaMetaData,
+) -> axum::response::Result<()> {
+    metadata.thumbnail =
+        crate::artwork::source_thumbnail(media, metadata.thumbnail).map_err(|err| {
+            warn!("Refused metadata of {}. Reason: {err}", media.id);
+            StatusCode::BAD_REQUEST
+        })?;
+    let thumbnail_changed = media.metadata.thumbnail != metadata.thumbnail;
+
+    crate::moving::save_metadata(media_folder, metadata)
+        .await
+        .map_err(|err| {
+            error!("Couldn't save metadata of {}. Reason: {err}", media.id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if thumbnail_changed {
+        crate::artwork::clear(media_folder).await;
+    }
+
+    Ok(())
+}
+
+pub async fn delete_media(
+    extract::State(state): State,
+    Json(form): Json<DeleteMediaForm>,
+) -> axum::response::Result<()> {
+    let media = find_media(&state, form.media_id())?;
+    let library_root = state
+        .libraries
+        .get(&media.library_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    // Paths in the media library start with the library id
+    let targets: Vec<(PathBuf, bool)> = match &form {
+        DeleteMediaForm::Media { media_id } => {
+            vec![(Path::new(&media.library_id).join(media_id), true)]
+        }
+        DeleteMediaForm::Season { season_no, .. } => {
+            let MediaContent::Series(seasons) = &media.content else {
+                return Err(StatusCode::BAD_REQUEST.into());
+            };
+            // Seasons are folders, find it through any of its episodes
+            let season_folder = seasons
+                .get(season_no)
+                .and_then(|season| season.values().next())
+                .and_then(|episode| Path::new(&episode.media).parent())
+                .ok_or(StatusCode::NOT_FOUND)?;
+
+            if season_folder == Path::new(&media.library_id).join(&media.id) {
+                return Err(StatusCode::BAD_REQUEST.into());
+            }
+
+            vec![(season_folder.to_path_buf(), true)]
+        }
+        DeleteMediaForm::Episode { episode, .. } => {
+            let MediaContent::Series(_) = &media.content else {
+                return Err(StatusCode::BAD_REQUEST.into());
+            };
+            let paths = media
+                .get_media_paths(Some(episode))
+                .ok_or(StatusCode::NOT_FOUND)?;
+
+            episode_files(paths)
+                .into_iter()
+                .map(|path| (path, false))
+                .collect()
+        }
+        DeleteMediaForm::Subtitle { path, .. } => {
+            let is_known_subtitle = crate::search::media_paths(&media.content)
+                .flat_map(|paths| paths.subtitles.iter())
+                .any(|subtitle| &subtitle.path == path);
+            if !is_known_subtitle {
+                return Err(StatusCode::NOT_FOUND.into());
+            }
+
+            vec![(PathBuf::from(path), false)]
+        }
+    };
+
+    for (target, is_dir) in targets {
+        let Ok(target) = target.strip_prefix(&media.library_id) else {
+            return Err(StatusCode::BAD_REQUEST.into());
+        };
+        // Generated files like thumbnails may not exist
+        let Some(target) = crate::dir::contained_path(&library_root.root, target).await else {
+            if is_dir {
+                return Err(StatusCode::NOT_FOUND.into());
+            }
+            continue;
+        };
+
+        let result = match is_dir {
+            true => tokio::fs::remove_dir_all(&target).await,
+            false => tokio::fs::remove_file(&target).await,
+        };
+
+        result.map_err(|err| {
+            error!("Couldn't delete {}. Reason: {err}", target.display());
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+        info!("Deleted {}", target.display());
+    }
+
+    recrawl(&state, &media.id).await
+}
+
+pub async fn rename_media(
+    extract::State(state): State,
+    Json(form): Json<RenameMediaForm>,
+) -> axum::response::Result<()> {
+    let title = form.title.trim();
+    if title.is_empty() {
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+
+    let media = find_media(&state, &form.media_id)?;
+    let library_root = state
+        .libraries
+        .get(&media.library_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let metadata = MediaMetaData {
+        title: title.to_string(),
+        ..media.metadata.clone()
+    };
+
+    let new_id = crate::moving::media_id(&metadata);
+    if new_id == media.id {
+        return save_and_recrawl(&state, &media, metadata).await;
+    }
+    // The id becomes a folder name next to the current one, `..` would leave the library
+    if !matches!(
+        Path::new(&new_id).components().collect::<Vec<_>>()[..],
+        [Component::Normal(_)]
+    ) {
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+    if state.libraries.find_media_folder(&new_id).await.is_some() {
+        return Err(StatusCode::CONFLICT.into());
+    }
+
+    let root = tokio::fs::canonicalize(&library_root.root)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let from = crate::dir::contained_path(&root, &media.id)
+        .await
+        .filter(|from| from.parent() == Some(root.as_path()))
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let to = root.join(&new_id);
+
+    tokio::fs::rename(&from, &to).await.map_err(|err| {
+        error!(
+            "Couldn't rename {} to {}. Reason: {err}",
+            from.display(),
+            to.display()
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    info!("Renamed {} to {}", from.display(), to.display());
+
+    save_metadata(&to, &media, metadata).await?;
+
+    // The old id is dropped from the library before the new one is crawled
+    recrawl(&state, &media.id).await?;
+    recrawl(&state, &new_id).await
+}
+
+pub async fn import_media(
+    extract::State(state): State,
+    Json(form): Json<ImportForm>,
+) -> axum::response::Result<Json<Vec<ImportedItem>>> {
+    if state.import_dirs.is_empty() {
+        return Err(StatusCode::FORBIDDEN.into());
+    }
+    let mut source_dir = None;
+    for import_dir in state.import_dirs.iter() {
+        source_dir = crate::dir::contained_path(import_dir, &form.path).await;
+        if source_dir.is_some() {
+            break;
+        }
+    }
+    let source_dir = source_dir.ok_or(StatusCode::NOT_FOUND)?;
+    if let Some(library_id) = &form.library_id
+        && state.libraries.get(library_id).is_none()
+    {
+        return Err(StatusCode::NOT_FOUND.into());
+    }
+
+    // Importing a library into itself, or a folder that contains it, would shuffle it around
+    for library_root in state.libraries.iter() {
+        let root = tokio::fs::canonicalize(&library_root.root)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        if source_dir.starts_with(&root) || root.starts_with(&source_dir) {
+            return Err(StatusCode::BAD_REQUEST.into());
+        }
+    }
+
+    let scanned = crate::import::scan(&source_dir).await.map_err(|err| {
+        error!("Couldn't scan {}. Reason: {err}", source_dir.display());
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let mut imported = Vec::with_capacity(scanned.len());
+    for item in scanned {
+        imported.push(
+            crate::import::import(
+                &state.libraries,
+                form.library_id.as_deref(),
+                item,
+                form.mode,
+                form.dry_run,
+            )
+            .await,
+        );
+    }
+
+    let has_changes = imported
+        .iter()
+        .any(|item| matches!(item.outcome, ImportOutcome::Imported { .. }));
+    if has_changes {
+        state
+            .media_signal_watcher
+            .signal_sender
+            .send(MediaSignal::CrawlAll)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+
+    Ok(Json(imported))
+}
+
+/// The video and everything that belongs to it
+fn episode_files(paths: &MediaPaths) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::iter::once(&paths.media)
+        .chain(paths.subtitles.iter().map(|subtitle| &subtitle.path))
+        .map(PathBuf::from)
+        .collect();
+
+    if let Some(thumbnails) = crate::thumbnails::ThumbnailPaths::of(&paths.media) {
+        files.extend([thumbnails.frame, thumbnails.sprite_sheet, thumbnails.track]);
+    }
+
+    files
+}
+
+/// Returns once the media service picked up the changes
+async fn recrawl(state: &crate::AppState, media_id: &str) -> axum::response::Result<()> {
+    let mut media_library = state.media_signal_watcher.data.clone();
+    media_library.mark_unchanged();
+
+    state
+        .media_signal_watcher
+        .signal_sender
+        .send(MediaSignal::CrawlPartial {
+            media_id: media_id.to_string(),
+        })
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    media_library
+        .changed()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::{Media, MediaContent, MediaMetaData, MediaPaths};
+
+    use super::save_metadata;
+
+    #[tokio::test]
+    async fn test_edit_keeps_artwork_source() {
+        let tmp = tempfile::tempdir().unwrap();
+        let artwork = tmp.path().join("artwork");
+        tokio::fs::write(&artwork, b"cached").await.unwrap();
+
+        let media = Media {
+            id: "My_Movie".to_string(),
+            library_id: "media".to_string(),
+            added_on: None,
+            artwork_url: Some("/artwork/My_Movie/poster".to_string()),
+            metadata: MediaMetaData {
+                thumbnail: "https://image.com/poster.jpg".to_string(),
+                title: "My Movie".to_string(),
+                ..Default::default()
+            },
+            content: MediaContent::Movie(MediaPaths::default()),
+        };
+        let read_metadata = || async {
+            let metadata = tokio::fs::read_to_string(tmp.path().join("meta.json"))
+                .await
+                .unwrap();
+            crate::metadata_file::parse(&metadata).unwrap().metadata
+        };
+
+        // Clients send back the artwork they were served
+        for thumbnail in [
+            "/artwork/My_Movie/poster",
+            "http://192.168.1.2:3000/artwork/My_Movie/poster",
+        ] {
+            let metadata = MediaMetaData {
+                thumbnail: thumbnail.to_string(),
+                title: "My Edited Movie".to_string(),
+                ..Default::default()
+            };
+            save_metadata(tmp.path(), &media, metadata).await.unwrap();
+
+            let saved = read_metadata().await;
+            assert_eq!(saved.title, "My Edited Movie");
+            assert_eq!(saved.thumbnail, "https://image.com/poster.jpg");
+            assert!(artwork.exists());
+        }
+
+        let metadata = MediaMetaData {
+            thumbnail: "/artwork/Other_Movie/poster".to_string(),
+            title: "My Movie".to_string(),
+            ..Default::default()
+        };
+        assert!(save_metadata(tmp.path(), &media, metadata).await.is_err());
+        assert_eq!(read_metadata().await.title, "My Edited Movie");
+
+        let metadata = MediaMetaData {
+            thumbnail: "https://image.com/other.jpg".to_string(),
+            title: "My Movie".to_string(),
+            ..Default::default()
+        };
+        save_metadata(tmp.path(), &media, metadata).await.unwrap();
+        assert_eq!(
+            read_metadata().await.thumbnail,
+            "https://image.com/other.jpg"
+        );
+        assert!(!artwork.exists());
+    }
+}
@@ -0,0 +1,258 @@
+//! Reads and writes `meta.json`, the file that holds the metadata of a media folder.
+//!
+//! Every file is tagged with a `version`. Files written before versioning was introduced
+//! have no `version` field and are treated as version 1.
+
+use std::path::Path;
+
+use domain::MediaMetaData;
+use log::{info, warn};
+
+pub const METADATA_FILE_NAME: &str = "meta.json";
+
+/// Bump this when the format changes and teach [`parse`] how to migrate the older version.
+pub const CURRENT_VERSION: u64 = 2;
+
+#[derive(serde::Serialize)]
+struct VersionedMetaData<'a> {
+    version: u64,
+    #[serde(flatten)]
+    metadata: &'a MediaMetaData,
+}
+
+/// The format before versioning
+#[derive(serde::Deserialize)]
+struct MetaDataV1 {
+    thumbnail: String,
+    title: String,
+}
+
+impl From<MetaDataV1> for MediaMetaData {
+    fn from(value: MetaDataV1) -> Self {
+        Self {
+            thumbnail: value.thumbnail,
+            title: value.title,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParsedMetaData {
+    pub metadata: MediaMetaData,
+    /// Version the file was written in
+    pub version: u64,
+}
+
+impl ParsedMetaData {
+    pub fn is_outdated(&self) -> bool {
+        self.version < CURRENT_VERSION
+    }
+}
+
+pub fn parse(metadata_string: &str) -> Result<ParsedMetaData> {
+    let value: serde_json::Value =
+        serde_json::from_str(metadata_string).map_err(Error::CantParse)?;
+
+    let version = match value.get("version") {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| Error::InvalidVersion(version.to_string()))?,
+    };
+
+    let metadata = match version {
+        1 => serde_json::from_value::<MetaDataV1>(value)
+            .map_err(Error::CantParse)?
+            .into(),
+        CURRENT_VERSION => serde_json::from_value(value).map_err(Error::CantParse)?,
+        _ => return Err(Error::InvalidVersion(version.to_string())),
+    };
+
+    Ok(ParsedMetaData { metadata, version })
+}
+
+/// Always writes the current version
+pub fn to_string(metadata: &MediaMetaData) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(&VersionedMetaData {
+        version: CURRENT_VERSION,
+        metadata,
+    })
+}
+
+/// Rewrites every outdated `meta.json` in the library with the current version.
+/// Returns how many files were migrated.
+pub async fn migrate_library(media_dir: impl AsRef<Path>) -> usize {
+    let media_dir = media_dir.as_ref();
+    let Ok(read_dir) = crate::dir::fully_read_dir(media_dir).await else {
+        return 0;
+    };
+
+    let mut migrated = 0;
+    for entry in read_dir {
+        let folder = entry.path();
+        let metadata_path = folder.join(METADATA_FILE_NAME);
+        let Ok(metadata_string) = tokio::fs::read_to_string(&metadata_path).await else {
+            continue;
+        };
+
+        let parsed = match parse(&metadata_string) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                warn!(
+                    "Not migrating metadata at {}. Reason: {err}",
+                    metadata_path.display()
+                );
+                continue;
+            }
+        };
+
+        if !parsed.is_outdated() {
+            continue;
+        }
+
+        match crate::moving::save_metadata(&folder, parsed.metadata).await {
+            Ok(()) => {
+                info!(
+                    "Migrated metadata at {} from version {} to {CURRENT_VERSION}",
+                    metadata_path.display(),
+                    parsed.version
+                );
+                migrated += 1;
+            }
+            Err(err) => warn!(
+                "Couldn't migrate metadata at {}. Reason: {err}",
+                metadata_path.display()
+            ),
+        }
+    }
+
+    migrated
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Can't parse metadata. {0}")]
+    CantParse(serde_json::Error),
+    #[error("Unsupported metadata version {0}")]
+    InvalidVersion(String),
+}
+
+type Result<T> = core::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use domain::{EpisodeMetaData, ExternalIds, MediaMetaData};
+
+    use super::{CURRENT_VERSION, Error, METADATA_FILE_NAME, migrate_library, parse, to_string};
+
+    fn full_metadata() -> MediaMetaData {
+        MediaMetaData {
+            thumbnail: "https://image.com".to_string(),
+            title: "My Series".to_string(),
+            year: Some(2013),
+            overview: Some("A series".to_string()),
+            genres: vec!["Comedy".to_string()],
+            external_ids: ExternalIds {
+                tmdb: Some(60625),
+                imdb: Some("tt2861424".to_string()),
+            },
+            episodes: HashMap::from([(
+                1,
+                HashMap::from([(
+                    1,
+                    EpisodeMetaData {
+                        title: Some("Pilot".to_string()),
+                        air_date: Some("2013-12-02".to_string()),
+                    },
+                )]),
+            )]),
+        }
+    }
+
+    #[test]
+    fn test_parse_v1() {
+        let parsed =
+            parse(r#"{ "thumbnail": "https://some-link", "title": "Example Movie" }"#).unwrap();
+
+        assert_eq!(parsed.version, 1);
+        assert!(parsed.is_outdated());
+        assert_eq!(
+            parsed.metadata,
+            MediaMetaData {
+                thumbnail: "https://some-link".to_string(),
+                title: "Example Movie".to_string(),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let metadata = full_metadata();
+        let parsed = parse(&to_string(&metadata).unwrap()).unwrap();
+
+        assert_eq!(parsed.version, CURRENT_VERSION);
+        assert!(!parsed.is_outdated());
+        assert_eq!(parsed.metadata, metadata);
+    }
+
+    #[test]
+    fn test_optional_fields() {
+        let parsed =
+            parse(r#"{ "version": 2, "thumbnail": "https://some-link", "title": "Example" }"#)
+                .unwrap();
+
+        assert_eq!(parsed.metadata.year, None);
+        assert!(parsed.metadata.episodes.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_version() {
+        assert!(matches!(
+            parse(r#"{ "version": 99, "thumbnail": "", "title": "" }"#),
+            Err(Error::InvalidVersion(_))
+        ));
+        assert!(matches!(
+            parse(r#"{ "version": "two", "thumbnail": "", "title": "" }"#),
+            Err(Error::InvalidVersion(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_migrate_library() {
+        let tmp = tempfile::tempdir().unwrap();
+        let old = tmp.path().join("Old_Movie");
+        let new = tmp.path().join("New_Movie");
+        tokio::fs::create_dir(&old).await.unwrap();
+        tokio::fs::create_dir(&new).await.unwrap();
+
+        tokio::fs::write(
+            old.join(METADATA_FILE_NAME),
+            r#"{ "thumbnail": "https://some-link", "title": "Old Movie" }"#,
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(
+            new.join(METADATA_FILE_NAME),
+            to_string(&full_metadata()).unwrap(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(migrate_library(tmp.path()).await, 1);
+
+        let migrated = parse(
+            &tokio::fs::read_to_string(old.join(METADATA_FILE_NAME))
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(migrated.version, CURRENT_VERSION);
+        assert_eq!(migrated.metadata.title, "Old Movie");
+
+        assert_eq!(migrate_library(tmp.path()).await, 0);
+    }
+}
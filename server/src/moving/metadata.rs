@@ -3,11 +3,14 @@ use tokio::io::AsyncWriteExt;
 use super::{Error, Result};
 use std::path::Path;
 
+/// Writes `meta.json` in the current format
 pub async fn save_metadata(
     at_folder: impl AsRef<Path>,
     metadata: domain::MediaMetaData,
 ) -> Result<()> {
-    let destination = at_folder.as_ref().join("meta.json");
+    let destination = at_folder
+        .as_ref()
+        .join(crate::metadata_file::METADATA_FILE_NAME);
     let mut metadata_file = tokio::fs::OpenOptions::new()
         .create(true)
         .write(true)
@@ -20,8 +23,8 @@ pub async fn save_metadata(
         })?;
 
     let metadata_string =
-        serde_json::to_string_pretty(&metadata).map_err(|err| Error::CantSerializeMetadata {
-            metadata,
+        crate::metadata_file::to_string(&metadata).map_err(|err| Error::CantSerializeMetadata {
+            metadata: Box::new(metadata),
             inner: err,
         })?;
 
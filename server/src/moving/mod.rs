@@ -2,15 +2,18 @@ mod metadata;
 mod movies;
 mod series;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use domain::import::TransferMode;
+
+pub use metadata::save_metadata;
 pub use movies::generate_movie_media;
 pub use series::generate_series_media;
 
 const URL_SAFE_NON_ALPHANUMERIC_CHARS: [char; 11] =
     ['$', '-', '_', '.', '+', '!', '*', '\'', '(', ')', ','];
 
-fn sanitize_name_for_url(input: &str) -> String {
+pub(crate) fn sanitize_name_for_url(input: &str) -> String {
     input
         .chars()
         .map(|char| {
@@ -23,6 +26,34 @@ fn sanitize_name_for_url(input: &str) -> String {
         .collect()
 }
 
+/// Id of the media item that gets generated for `metadata`, which is also its folder name.
+pub fn media_id(metadata: &domain::MediaMetaData) -> String {
+    sanitize_name_for_url(&metadata.title)
+}
+
+/// Puts `from` at `to`, either by moving it or by hard linking it.
+async fn transfer(mode: TransferMode, from: &Path, to: &Path) -> Result<()> {
+    let result = match mode {
+        TransferMode::Move => match tokio::fs::rename(from, to).await {
+            // Libraries can be on other disks than downloads
+            Err(err) if err.kind() == std::io::ErrorKind::CrossesDevices => {
+                match tokio::fs::copy(from, to).await {
+                    Ok(_) => tokio::fs::remove_file(from).await,
+                    Err(err) => Err(err),
+                }
+            }
+            result => result,
+        },
+        TransferMode::HardLink => tokio::fs::hard_link(from, to).await,
+    };
+
+    result.map_err(|err| Error::CantMove {
+        from: from.to_path_buf(),
+        to: to.to_path_buf(),
+        inner: err,
+    })
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("Media file has no name")]
@@ -44,14 +75,14 @@ pub enum Error {
     CantCreateMetaData { at: PathBuf, inner: std::io::Error },
     #[error("Can't serialize metadata {metadata:#?}. {inner}")]
     CantSerializeMetadata {
-        metadata: domain::MediaMetaData,
+        metadata: Box<domain::MediaMetaData>,
         inner: serde_json::Error,
     },
     #[error("Can't write metadata at {at}. {inner}")]
     CantWriteMetadata { at: PathBuf, inner: std::io::Error },
 }
 
-type Result<T> = core::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
 #[cfg(test)]
 mod tests {
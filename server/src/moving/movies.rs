@@ -1,17 +1,16 @@
 use std::path::{Path, PathBuf};
 
 use super::{Error, Result};
-use domain::MediaMetaData;
-
-use super::sanitize_name_for_url;
+use domain::{MediaMetaData, import::TransferMode};
 
 /// Returns the resulting movie file's path
 pub async fn generate_movie_media(
     media_dir: &Path,
     movie_file: &Path,
     metadata: &MediaMetaData,
+    mode: TransferMode,
 ) -> Result<PathBuf> {
-    let target_dir = media_dir.join(sanitize_name_for_url(&metadata.title));
+    let target_dir = media_dir.join(super::media_id(metadata));
     // We want to avoid URL breaking names since files are hosted directly with their names
     let file_name = {
         let movie_file_stem = movie_file
@@ -34,13 +33,7 @@ pub async fn generate_movie_media(
 
     // 2. Move movie file to destination
     let destination = target_dir.join(&file_name);
-    tokio::fs::rename(movie_file, &destination)
-        .await
-        .map_err(|err| Error::CantMove {
-            from: movie_file.to_path_buf(),
-            to: destination,
-            inner: err,
-        })?;
+    super::transfer(mode, movie_file, &destination).await?;
 
     // 3. Save metadata
     super::metadata::save_metadata(&target_dir, metadata.clone()).await?;
@@ -50,7 +43,7 @@ pub async fn generate_movie_media(
 
 #[cfg(test)]
 mod tests {
-    use domain::MediaMetaData;
+    use domain::{MediaMetaData, import::TransferMode};
 
     use crate::{moving::generate_movie_media, test_utils::fixtures_path};
 
@@ -65,12 +58,14 @@ mod tests {
         let metadata = MediaMetaData {
             title: "My Movie".to_string(),
             thumbnail: "http://path.to/image".to_string(),
+            ..Default::default()
         };
 
         let output_dir = tmp.path().join("generate_movie_media");
-        let movie_file_path = generate_movie_media(&output_dir, &working_copy, &metadata)
-            .await
-            .unwrap();
+        let movie_file_path =
+            generate_movie_media(&output_dir, &working_copy, &metadata, TransferMode::Move)
+                .await
+                .unwrap();
 
         dbg!(&movie_file_path);
 
@@ -6,18 +6,18 @@ use std::{
 use super::{Error, Result};
 use domain::{
     MediaMetaData,
+    import::TransferMode,
     series::{EditSeriesFileMappingForm, file_mapping_form_state},
 };
 
-use super::sanitize_name_for_url;
-
 pub async fn generate_series_media(
     media_dir: &Path,
     source_dir: &Path,
     mapping: EditSeriesFileMappingForm<file_mapping_form_state::Valid>,
     metadata: &MediaMetaData,
+    mode: TransferMode,
 ) -> Result<Box<[PathBuf]>> {
-    let target_dir = media_dir.join(sanitize_name_for_url(&metadata.title));
+    let target_dir = media_dir.join(super::media_id(metadata));
 
     // 1. Create destination dir
     {
@@ -62,7 +62,7 @@ pub async fn generate_series_media(
 
     // 2. Move media files to destination
     {
-        // 2a. Move files
+        // 2a. Move or link files
         let move_futures = resolved_mapping
             .iter()
             .map(|(source, destination)| async move {
@@ -78,16 +78,7 @@ pub async fn generate_series_media(
                     })?;
                 }
 
-                tokio::fs::rename(source, destination)
-                    .await
-                    .map_err(|err| Error::CantMove {
-                        from: source.clone(),
-                        to: destination.clone(),
-                        inner: err,
-                    })?;
-
-                let result: Result<()> = Ok(());
-                result
+                super::transfer(mode, source, destination).await
             });
 
         futures::future::join_all(move_futures)
@@ -104,7 +95,7 @@ pub async fn generate_series_media(
 
 #[cfg(test)]
 mod tests {
-    use domain::MediaMetaData;
+    use domain::{MediaMetaData, import::TransferMode};
 
     use crate::{
         moving::generate_series_media,
@@ -132,7 +123,9 @@ mod tests {
             &MediaMetaData {
                 title: "My Series".to_string(),
                 thumbnail: "http://image.com".to_string(),
+                ..Default::default()
             },
+            TransferMode::Move,
         )
         .await
         .unwrap();
@@ -3,8 +3,10 @@ use std::{
     path::{Path, PathBuf},
 };
 
+#[allow(clippy::result_large_err)]
 pub mod handlers;
-use log::info;
+use domain::subtitles::SubtitleCodecKind;
+use log::{info, warn};
 
 pub async fn prepare_media(
     media_identifier: &domain::MediaIdentifier,
@@ -30,6 +32,7 @@ pub async fn prepare_media(
 
             let domain::Track::Subtitle {
                 id,
+                codec,
                 language,
                 external_id,
             } = track
@@ -41,14 +44,21 @@ pub async fn prepare_media(
                 return None;
             }
 
-            Some((id, language))
+            let Some(kind) = SubtitleCodecKind::from_codec(&codec) else {
+                warn!(
+                    "Subtitle track with id {id} at {} has an unknown codec '{codec}'. Skipping it.",
+                    media_path.display()
+                );
+                return None;
+            };
+
+            Some((id, kind, language))
         })
         .collect::<Vec<_>>();
 
-    // TODO: extract
     let new_subs = if !missing_external_subtitles.is_empty() {
         info!(
-            "{} subtitles are embedded to {} but don't have .srt counterparts, generating them.",
+            "{} subtitles are embedded to {} but don't have external counterparts, generating them.",
             missing_external_subtitles.len(),
             media_path.display()
         );
@@ -57,7 +67,7 @@ pub async fn prepare_media(
 
         let (new_subs, track_mapping): (Vec<_>, Vec<_>) = missing_external_subtitles
             .into_iter()
-            .map(|(track_id, language)| {
+            .map(|(track_id, kind, language)| {
                 let language = match language {
                     Some(language) => language,
                     None => {
@@ -75,31 +85,35 @@ pub async fn prepare_media(
 
                 let file_name = match media_identifier {
                     domain::MediaIdentifier::Movie { .. } => {
-                        format!("{}-{}.srt", language.to_iso639_2t(), external_id)
+                        format!(
+                            "{}-{}.{}",
+                            language.to_iso639_2t(),
+                            external_id,
+                            kind.extension()
+                        )
                     }
                     domain::MediaIdentifier::Series { episode, .. } => format!(
-                        "{}-{}-{}.srt",
+                        "{}-{}-{}.{}",
                         episode.episode_no,
                         language.to_iso639_2t(),
-                        external_id
+                        external_id,
+                        kind.extension()
                     ),
                 };
 
                 let output_path = subs_folder.join(file_name).to_string_lossy().to_string();
-                (
-                    domain::subtitles::Subtitle {
-                        id: external_id,
-                        language,
-                        path: output_path.clone(),
-                    },
-                    (track_id, output_path),
-                )
+                // Bitmap sidecars aren't a part of the subtitle library, they can't be embedded
+                // as text
+                let new_sub = (kind != SubtitleCodecKind::Bitmap).then(|| {
+                    domain::subtitles::Subtitle::new(external_id, language, output_path.clone())
+                });
+                (new_sub, (track_id, kind, output_path))
             })
             .unzip();
 
         tokio::fs::create_dir_all(subs_folder).await?;
         ffmpeg::extract_tracks(media_path.to_string_lossy().to_string(), track_mapping).await?;
-        new_subs
+        new_subs.into_iter().flatten().collect()
     } else {
         vec![]
     };
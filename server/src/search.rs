@@ -0,0 +1,511 @@
+//! Searching, filtering and sorting the media library.
+
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use domain::{
+    Media, MediaContent, MediaPaths,
+    metadata::MediaKind,
+    search::{
+        MediaQuery, MediaSearchResults, MediaSort, RecentlyAdded, RecentlyAddedQuery, SortOrder,
+    },
+    series::EpisodeIdentifier,
+};
+
+/// Lowercase words without punctuation, so `Spider-Man` matches `spider man`
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|char: char| !char.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// `None` if the title doesn't match. Every query word has to start a word of the title.
+fn relevance(title: &str, query: &[String]) -> Option<u8> {
+    let title = words(title);
+    let is_match = query
+        .iter()
+        .all(|query_word| title.iter().any(|word| word.starts_with(query_word)));
+    if !is_match {
+        return None;
+    }
+
+    Some(if title == query {
+        3
+    } else if title.starts_with(query) {
+        2
+    } else {
+        1
+    })
+}
+
+/// Paths of the movie, or every episode of the series
+pub(crate) fn media_paths(content: &MediaContent) -> Box<dyn Iterator<Item = &MediaPaths> + '_> {
+    match content {
+        MediaContent::Movie(paths) => Box::new(std::iter::once(paths)),
+        MediaContent::Series(seasons) => {
+            Box::new(seasons.values().flat_map(|season| season.values()))
+        }
+    }
+}
+
+fn kind(media: &Media) -> MediaKind {
+    match media.content {
+        MediaContent::Movie(_) => MediaKind::Movie,
+        MediaContent::Series(_) => MediaKind::Series,
+    }
+}
+
+/// `None` for times too far in the future to represent, query values aren't checked
+fn from_unix_seconds(seconds: u64) -> Option<SystemTime> {
+    UNIX_EPOCH.checked_add(Duration::from_secs(seconds))
+}
+
+/// `preparing` has the ids of media with files that are waiting to be made compatible.
+pub fn search(
+    library: &[Media],
+    preparing: &HashSet<&str>,
+    query: &MediaQuery,
+) -> MediaSearchResults {
+    let query_words = query.text.as_deref().map(words).unwrap_or_default();
+
+    let mut matches: Vec<(u8, &Media)> = library
+        .iter()
+        .filter_map(|media| {
+            let relevance = match query_words.is_empty() {
+                true => 0,
+                false => relevance(&media.metadata.title, &query_words)?,
+            };
+
+            Some((relevance, media))
+        })
+        .filter(|(_, media)| {
+            query
+                .kind
+                .as_ref()
+                .is_none_or(|wanted| &kind(media) == wanted)
+        })
+        .filter(|(_, media)| {
+            query
+                .library
+                .as_ref()
+                .is_none_or(|library_id| &media.library_id == library_id)
+        })
+        .filter(|(_, media)| {
+            query.subtitle_language.as_ref().is_none_or(|language| {
+                media_paths(&media.content)
+                    .flat_map(|paths| paths.subtitles.iter())
+                    .any(|subtitle| &subtitle.language == language)
+            })
+        })
+        .filter(|(_, media)| {
+            query
+                .compatible
+                .is_none_or(|compatible| compatible != preparing.contains(media.id.as_str()))
+        })
+        // Nothing is added after a time that can't be represented, and everything before it
+        .filter(|(_, media)| {
+            query.added_after.is_none_or(|added_after| {
+                media.added_on.is_some_and(|added_on| {
+                    from_unix_seconds(added_after)
+                        .is_some_and(|added_after| added_on >= added_after)
+                })
+            })
+        })
+        .filter(|(_, media)| {
+            query.added_before.is_none_or(|added_before| {
+                media.added_on.is_some_and(|added_on| {
+                    from_unix_seconds(added_before)
+                        .is_none_or(|added_before| added_on < added_before)
+                })
+            })
+        })
+        .collect();
+
+    let by_title = |a: &Media, b: &Media| {
+        a.metadata
+            .title
+            .to_lowercase()
+            .cmp(&b.metadata.title.to_lowercase())
+    };
+    matches.sort_by(|(a_relevance, a), (b_relevance, b)| {
+        let (missing, ordering) = match query.sort {
+            MediaSort::Relevance => (Ordering::Equal, b_relevance.cmp(a_relevance)),
+            MediaSort::Title => (Ordering::Equal, Ordering::Equal),
+            MediaSort::Year => by_optional_key(a.metadata.year, b.metadata.year),
+            MediaSort::Added => by_optional_key(a.added_on, b.added_on),
+        };
+        let ordering = ordering.then_with(|| by_title(a, b));
+
+        missing.then(match query.order {
+            SortOrder::Ascending => ordering,
+            SortOrder::Descending => ordering.reverse(),
+        })
+    });
+
+    let total = matches.len() as u32;
+    let items = matches
+        .into_iter()
+        .skip(query.offset as usize)
+        .take(
+            query
+                .limit
+                .map(|limit| limit as usize)
+                .unwrap_or(usize::MAX),
+        )
+        .map(|(_, media)| media.clone())
+        .collect();
+
+    MediaSearchResults {
+        items,
+        total,
+        offset: query.offset,
+    }
+}
+
+/// Media and series with new episodes, newest first. Media that doesn't know when it was added is
+/// left out.
+pub fn recently_added(library: &[Media], query: &RecentlyAddedQuery) -> Vec<RecentlyAdded> {
+    let since = match query.since.map(from_unix_seconds) {
+        // Nothing was added after a time that can't be represented
+        Some(None) => return Vec::new(),
+        since => since.flatten(),
+    };
+
+    let mut items: Vec<RecentlyAdded> = library
+        .iter()
+        .filter(|media| {
+            query
+                .library
+                .as_ref()
+                .is_none_or(|library_id| &media.library_id == library_id)
+        })
+        .filter_map(|media| {
+            let episodes: Vec<(EpisodeIdentifier, SystemTime)> = match &media.content {
+                MediaContent::Movie(_) => Vec::new(),
+                MediaContent::Series(seasons) => seasons
+                    .iter()
+                    .flat_map(|(season_no, season)| {
+                        season.iter().filter_map(|(episode_no, paths)| {
+                            let episode = EpisodeIdentifier {
+                                season_no: *season_no,
+                                episode_no: *episode_no,
+                            };
+                            Some((episode, paths.added_on?))
+                        })
+                    })
+                    .collect(),
+            };
+            let latest_episode = episodes.iter().map(|(_, added_on)| *added_on).max();
+
+            let added_on = media.added_on.into_iter().chain(latest_episode).max()?;
+            if since.is_some_and(|since| added_on < since) {
+                return None;
+            }
+
+            // Episodes of a download are recorded at the same time
+            let cutoff = since.or(latest_episode);
+            let mut new_episodes: Vec<EpisodeIdentifier> = episodes
+                .into_iter()
+                .filter(|(_, added_on)| cutoff.is_some_and(|cutoff| *added_on >= cutoff))
+                .map(|(episode, _)| episode)
+                .collect();
+            new_episodes.sort_by_key(|episode| (episode.season_no, episode.episode_no));
+
+            Some(RecentlyAdded {
+                media: media.clone(),
+                added_on,
+                new_episodes,
+            })
+        })
+        .collect();
+
+    items.sort_by(|a, b| {
+        b.added_on
+            .cmp(&a.added_on)
+            .then_with(|| a.media.metadata.title.cmp(&b.media.metadata.title))
+    });
+    if let Some(limit) = query.limit {
+        items.truncate(limit as usize);
+    }
+
+    items
+}
+
+/// Orders media that is missing the key after the rest, whichever the sort order, and the rest
+/// by the key
+fn by_optional_key<T: Ord>(a: Option<T>, b: Option<T>) -> (Ordering, Ordering) {
+    match (a, b) {
+        (Some(a), Some(b)) => (Ordering::Equal, a.cmp(&b)),
+        (a, b) => (b.is_some().cmp(&a.is_some()), Ordering::Equal),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use domain::{
+        Media, MediaContent, MediaMetaData, MediaPaths,
+        language::LanguageCode,
+        metadata::MediaKind,
+        search::{MediaQuery, MediaSort, RecentlyAddedQuery, SortOrder},
+        series::EpisodeIdentifier,
+        subtitles::Subtitle,
+    };
+
+    use super::{from_unix_seconds, recently_added, search};
+
+    fn movie(id: &str, title: &str, year: u32, added_on: u64) -> Media {
+        Media {
+            id: id.to_string(),
+            library_id: "media".to_string(),
+            added_on: Some(from_unix_seconds(added_on).unwrap()),
+            artwork_url: None,
+            metadata: MediaMetaData {
+                title: title.to_string(),
+                year: Some(year),
+                ..Default::default()
+            },
+            content: MediaContent::Movie(MediaPaths {
+                media: format!("media/{id}/{id}.mp4"),
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn library() -> Vec<Media> {
+        let mut series = movie("Alien_Worlds", "Alien Worlds", 2020, 300);
+        series.content = MediaContent::Series(HashMap::from([(
+            1,
+            HashMap::from([(
+                1,
+                MediaPaths {
+                    media: "media/Alien_Worlds/1/1.mp4".to_string(),
+                    subtitles: vec![Subtitle::new(
+                        "1".to_string(),
+                        LanguageCode::Turkish,
+                        "media/Alien_Worlds/1/1.tr.vtt".to_string(),
+                    )],
+                    ..Default::default()
+                },
+            )]),
+        )]));
+
+        vec![
+            movie("Aliens", "Aliens", 1986, 200),
+            movie("Alien", "Alien", 1979, 100),
+            movie("Spider-Man", "Spider-Man", 2002, 400),
+            series,
+        ]
+    }
+
+    fn ids(results: &domain::search::MediaSearchResults) -> Vec<&str> {
+        results
+            .items
+            .iter()
+            .map(|media| media.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_text_search() {
+        let library = library();
+        let query = |text: &str| MediaQuery {
+            text: Some(text.to_string()),
+            ..Default::default()
+        };
+
+        let results = search(&library, &HashSet::new(), &query("alien"));
+        assert_eq!(ids(&results), ["Alien", "Alien_Worlds", "Aliens"]);
+        assert_eq!(results.total, 3);
+
+        let results = search(&library, &HashSet::new(), &query("spider man"));
+        assert_eq!(ids(&results), ["Spider-Man"]);
+
+        let results = search(&library, &HashSet::new(), &query("worlds ali"));
+        assert_eq!(ids(&results), ["Alien_Worlds"]);
+
+        assert!(
+            search(&library, &HashSet::new(), &query("predator"))
+                .items
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_filters() {
+        let library = library();
+
+        let results = search(
+            &library,
+            &HashSet::new(),
+            &MediaQuery {
+                kind: Some(MediaKind::Series),
+                ..Default::default()
+            },
+        );
+        assert_eq!(ids(&results), ["Alien_Worlds"]);
+
+        let results = search(
+            &library,
+            &HashSet::new(),
+            &MediaQuery {
+                subtitle_language: Some(LanguageCode::Turkish),
+                ..Default::default()
+            },
+        );
+        assert_eq!(ids(&results), ["Alien_Worlds"]);
+
+        let results = search(
+            &library,
+            &HashSet::from(["Aliens"]),
+            &MediaQuery {
+                compatible: Some(false),
+                ..Default::default()
+            },
+        );
+        assert_eq!(ids(&results), ["Aliens"]);
+
+        let results = search(
+            &library,
+            &HashSet::new(),
+            &MediaQuery {
+                added_after: Some(200),
+                added_before: Some(400),
+                ..Default::default()
+            },
+        );
+        assert_eq!(ids(&results), ["Alien_Worlds", "Aliens"]);
+
+        // Bounds too far in the future to represent
+        for (added_after, added_before, expected) in
+            [(Some(u64::MAX), None, 0), (None, Some(u64::MAX), 4)]
+        {
+            let results = search(
+                &library,
+                &HashSet::new(),
+                &MediaQuery {
+                    added_after,
+                    added_before,
+                    ..Default::default()
+                },
+            );
+            assert_eq!(results.total, expected);
+        }
+    }
+
+    #[test]
+    fn test_sort_and_pagination() {
+        let library = library();
+
+        let results = search(
+            &library,
+            &HashSet::new(),
+            &MediaQuery {
+                sort: MediaSort::Added,
+                order: SortOrder::Descending,
+                offset: 1,
+                limit: Some(2),
+                ..Default::default()
+            },
+        );
+        assert_eq!(ids(&results), ["Alien_Worlds", "Aliens"]);
+        assert_eq!(results.total, 4);
+        assert_eq!(results.offset, 1);
+
+        let results = search(
+            &library,
+            &HashSet::new(),
+            &MediaQuery {
+                sort: MediaSort::Year,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            ids(&results),
+            ["Alien", "Aliens", "Spider-Man", "Alien_Worlds"]
+        );
+
+        // Media without a year goes last in both orders
+        let mut library = library;
+        library[1].metadata.year = None;
+        for (order, expected) in [
+            (
+                SortOrder::Ascending,
+                ["Aliens", "Spider-Man", "Alien_Worlds", "Alien"],
+            ),
+            (
+                SortOrder::Descending,
+                ["Alien_Worlds", "Spider-Man", "Aliens", "Alien"],
+            ),
+        ] {
+            let results = search(
+                &library,
+                &HashSet::new(),
+                &MediaQuery {
+                    sort: MediaSort::Year,
+                    order,
+                    ..Default::default()
+                },
+            );
+            assert_eq!(ids(&results), expected);
+        }
+    }
+
+    #[test]
+    fn test_recently_added() {
+        let mut library = library();
+        let MediaContent::Series(seasons) = &mut library[3].content else {
+            panic!("Expected a series");
+        };
+        let season = seasons.get_mut(&1).unwrap();
+        season.get_mut(&1).unwrap().added_on = Some(from_unix_seconds(300).unwrap());
+        season.insert(
+            2,
+            MediaPaths {
+                media: "media/Alien_Worlds/1/2.mp4".to_string(),
+                added_on: Some(from_unix_seconds(500).unwrap()),
+                ..Default::default()
+            },
+        );
+        library[1].added_on = None;
+
+        let items = recently_added(&library, &RecentlyAddedQuery::default());
+        let ids: Vec<&str> = items.iter().map(|item| item.media.id.as_str()).collect();
+        assert_eq!(ids, ["Alien_Worlds", "Spider-Man", "Aliens"]);
+        assert_eq!(items[0].added_on, from_unix_seconds(500).unwrap());
+        assert_eq!(
+            items[0].new_episodes,
+            [EpisodeIdentifier {
+                season_no: 1,
+                episode_no: 2
+            }]
+        );
+        assert!(items[1].new_episodes.is_empty());
+
+        let items = recently_added(
+            &library,
+            &RecentlyAddedQuery {
+                since: Some(250),
+                limit: Some(1),
+                ..Default::default()
+            },
+        );
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].new_episodes.len(), 2);
+
+        let items = recently_added(
+            &library,
+            &RecentlyAddedQuery {
+                since: Some(u64::MAX),
+                ..Default::default()
+            },
+        );
+        assert!(items.is_empty());
+    }
+}
@@ -0,0 +1,125 @@
+use std::{collections::VecDeque, path::PathBuf};
+
+use domain::subtitles::SubtitleAutoSyncForm;
+use log::{error, info};
+
+pub enum AutoSyncMessage {
+    Sync(AutoSyncJob),
+    Done(AutoSyncJob),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AutoSyncJob {
+    /// With library paths, as the client sent it
+    pub form: SubtitleAutoSyncForm,
+    pub media_path: PathBuf,
+    pub subtitle_path: PathBuf,
+}
+
+/// Queued jobs, the first one is running
+pub type AutoSyncWatcher = crate::signal::SignalWatcher<AutoSyncMessage, Vec<SubtitleAutoSyncForm>>;
+pub type AutoSyncReceiver =
+    crate::signal::SignalReceiver<AutoSyncMessage, Vec<SubtitleAutoSyncForm>>;
+
+/// Writes synced copies of subtitles, one at a time since each job decodes a whole audio track
+pub fn spawn(
+    mut signal_receiver: AutoSyncReceiver,
+    crate::AppState {
+        media_signal_watcher,
+        auto_sync_watcher,
+        ..
+    }: crate::AppState,
+) -> tokio::task::JoinHandle<()> {
+    let mut queue: VecDeque<AutoSyncJob> = VecDeque::with_capacity(50);
+    let mut task: Option<tokio::task::JoinHandle<()>> = None;
+
+    tokio::spawn(async move {
+        while let Some(signal) = signal_receiver.signal_receiver.recv().await {
+            // 1. Handle message
+            match signal {
+                AutoSyncMessage::Sync(job) => {
+                    if queue.contains(&job) {
+                        info!("{:#?} is already queued to be synced.", job.form);
+                        continue;
+                    }
+                    queue.push_back(job);
+                }
+                AutoSyncMessage::Done(job) => {
+                    // 1. Last task was done, ready to run next item
+                    task = None;
+
+                    // 2. Tell media lib to pick up the synced copy
+                    if let Err(err) = media_signal_watcher
+                        .signal_sender
+                        .send(crate::service::media::MediaSignal::CrawlPartial {
+                            media_id: job.form.media_id.clone(),
+                        })
+                        .await
+                    {
+                        error!(
+                            "Synced {:#?} but couldn't tell media service to recrawl it due to {err}. Restart the server.",
+                            job.form
+                        );
+                    }
+
+                    // 3. Remove from queue
+                    match queue.front().map(|first| first == &job) {
+                        Some(true) => {
+                            queue.pop_front();
+                        }
+                        _ => {
+                            error!(
+                                "Syncing {:#?} was done but it was already removed from the queue. Check server code.",
+                                job.form
+                            );
+                        }
+                    }
+                }
+            }
+
+            // 2. Announce we've updated the queue
+            if signal_receiver
+                .updater
+                .send(queue.iter().map(|job| job.form.clone()).collect())
+                .is_err()
+            {
+                error!("Auto sync list receiver was dropped. Can't update the auto sync list");
+            }
+
+            // 3. Start working on a job if none present
+            if task.is_none()
+                && let Some(head) = queue.front().cloned()
+            {
+                let sender = auto_sync_watcher.signal_sender.clone();
+                task = Some(tokio::spawn(async move {
+                    sync(sender, head).await;
+                }))
+            }
+        }
+    })
+}
+
+async fn sync(sender: tokio::sync::mpsc::Sender<AutoSyncMessage>, job: AutoSyncJob) {
+    info!("Syncing subtitle at {}", job.subtitle_path.display());
+
+    match crate::subtitle_sync::write_auto_synced(&job.media_path, &job.subtitle_path).await {
+        Ok((retimed, alignment)) => info!(
+            "Synced subtitle at {} to {} with {:?}. {:.0}% of the cues are on speech.",
+            job.subtitle_path.display(),
+            retimed.path.display(),
+            alignment.retiming,
+            alignment.speech_ratio * 100.0
+        ),
+        Err(err) => error!(
+            "Couldn't sync subtitle at {}. {err}",
+            job.subtitle_path.display()
+        ),
+    }
+
+    if let Err(err) = sender.send(AutoSyncMessage::Done(job.clone())).await {
+        error!(
+            "Synced {:#?} but couldn't tell auto sync service about it. {err}",
+            job.form
+        );
+    }
+}
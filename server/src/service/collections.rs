@@ -0,0 +1,410 @@
+use std::path::{Path, PathBuf};
+
+use domain::collection::{
+    AddToCollectionForm, Collection, CreateCollectionForm, DeleteCollectionForm,
+    MoveCollectionForm, RemoveFromCollectionForm, UpdateCollectionForm,
+};
+use log::{error, info, warn};
+use tokio::sync::oneshot;
+
+pub enum CollectionOperation {
+    Create(CreateCollectionForm),
+    Update(UpdateCollectionForm),
+    Delete(DeleteCollectionForm),
+    AddMedia(AddToCollectionForm),
+    RemoveMedia(RemoveFromCollectionForm),
+    Move(MoveCollectionForm),
+}
+
+pub struct CollectionSignal {
+    pub operation: CollectionOperation,
+    /// Gets the collection that was changed
+    pub result_sender: oneshot::Sender<Result<Collection, Error>>,
+}
+
+pub type CollectionsSignalWatcher =
+    crate::signal::SignalWatcher<CollectionSignal, Box<[Collection]>>;
+pub type CollectionsSignalReceiver =
+    crate::signal::SignalReceiver<CollectionSignal, Box<[Collection]>>;
+
+/// A service that keeps collections on disk
+pub async fn spawn(
+    collections_path: PathBuf,
+    mut collections_signal_receiver: CollectionsSignalReceiver,
+) -> tokio::task::JoinHandle<()> {
+    let mut collections = read_collections(&collections_path).await;
+    info!("Loaded {} collections", collections.len());
+    collections_signal_receiver
+        .updater
+        .send(collections.clone().into())
+        .expect("Collections channel was closed");
+
+    tokio::spawn(async move {
+        while let Some(CollectionSignal {
+            operation,
+            result_sender,
+        }) = collections_signal_receiver.signal_receiver.recv().await
+        {
+            // Changes are only kept once they're on disk
+            let mut changed_collections = collections.clone();
+            let result = match apply(&mut changed_collections, operation) {
+                Ok(collection) => write_collections(&collections_path, &changed_collections)
+                    .await
+                    .inspect_err(|err| {
+                        error!(
+                            "Couldn't save collections to {}. Reason: {err}",
+                            collections_path.display()
+                        )
+                    })
+                    .map(|()| collection),
+                Err(err) => Err(err),
+            };
+
+            if result.is_ok() {
+                collections = changed_collections;
+                collections_signal_receiver
+                    .updater
+                    .send(collections.clone().into())
+                    .expect("Collections channel was closed");
+            }
+
+            // The handler may have given up waiting
+            let _ = result_sender.send(result);
+        }
+    })
+}
+
+fn apply(
+    collections: &mut Vec<Collection>,
+    operation: CollectionOperation,
+) -> Result<Collection, Error> {
+    let find = |collections: &mut Vec<Collection>, id: &str| {
+        collections
+            .iter()
+            .position(|collection| collection.id == id)
+            .ok_or_else(|| Error::NotFound(id.to_string()))
+    };
+
+    match operation {
+        CollectionOperation::Create(CreateCollectionForm {
+            name,
+            kind,
+            media_ids,
+        }) => {
+            let name = valid_name(name)?;
+            let collection = Collection {
+                id: unique_id(collections, &name),
+                name,
+                kind,
+                media_ids: dedupe(media_ids),
+            };
+            collections.push(collection.clone());
+
+            Ok(collection)
+        }
+        CollectionOperation::Update(UpdateCollectionForm { id, name, kind }) => {
+            let name = valid_name(name)?;
+            let index = find(collections, &id)?;
+            let collection = &mut collections[index];
+            collection.name = name;
+            collection.kind = kind;
+
+            Ok(collection.clone())
+        }
+        CollectionOperation::Delete(DeleteCollectionForm { id }) => {
+            let index = find(collections, &id)?;
+
+            Ok(collections.remove(index))
+        }
+        CollectionOperation::AddMedia(AddToCollectionForm {
+            id,
+            media_id,
+            position,
+        }) => {
+            let index = find(collections, &id)?;
+            let media_ids = &mut collections[index].media_ids;
+            media_ids.retain(|current| current != &media_id);
+
+            let position = position
+                .map(|position| (position as usize).min(media_ids.len()))
+                .unwrap_or(media_ids.len());
+            media_ids.insert(position, media_id);
+
+            Ok(collections[index].clone())
+        }
+        CollectionOperation::RemoveMedia(RemoveFromCollectionForm { id, media_id }) => {
+            let index = find(collections, &id)?;
+            collections[index]
+                .media_ids
+                .retain(|current| current != &media_id);
+
+            Ok(collections[index].clone())
+        }
+        CollectionOperation::Move(MoveCollectionForm { id, position }) => {
+            let index = find(collections, &id)?;
+            let collection = collections.remove(index);
+            let position = (position as usize).min(collections.len());
+            collections.insert(position, collection.clone());
+
+            Ok(collection)
+        }
+    }
+}
+
+fn valid_name(name: String) -> Result<String, Error> {
+    match name.trim() {
+        "" => Err(Error::EmptyName),
+        trimmed => Ok(trimmed.to_string()),
+    }
+}
+
+/// Ids are made from the name when the collection is created and don't change afterwards
+fn unique_id(collections: &[Collection], name: &str) -> String {
+    let base = crate::moving::sanitize_name_for_url(name).to_lowercase();
+    let is_taken = |id: &str| collections.iter().any(|collection| collection.id == id);
+
+    if !is_taken(&base) {
+        return base;
+    }
+
+    (2..)
+        .map(|suffix| format!("{base}-{suffix}"))
+        .find(|id| !is_taken(id))
+        .expect("Some suffix to be free")
+}
+
+fn dedupe(media_ids: Vec<String>) -> Vec<String> {
+    let mut unique: Vec<String> = Vec::with_capacity(media_ids.len());
+    for media_id in media_ids {
+        if !unique.contains(&media_id) {
+            unique.push(media_id);
+        }
+    }
+    unique
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("There is no collection with id {0}")]
+    NotFound(String),
+    #[error("Collection name can't be empty")]
+    EmptyName,
+    #[error("Can't read collections. {0}")]
+    CantRead(std::io::Error),
+    #[error("Can't parse collections. {0}")]
+    CantParse(serde_json::Error),
+    #[error("Can't serialize collections. {0}")]
+    CantSerialize(serde_json::Error),
+    #[error("Can't write collections. {0}")]
+    CantWrite(std::io::Error),
+}
+
+/// Starts without collections when there is no collections file yet, or it's unreadable.
+async fn read_collections(collections_path: &Path) -> Vec<Collection> {
+    match try_read_collections(collections_path).await {
+        Ok(collections) => collections,
+        Err(Error::CantRead(err)) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => {
+            warn!(
+                "Ignoring collections at {}. Reason: {err}",
+                collections_path.display()
+            );
+            Vec::new()
+        }
+    }
+}
+
+async fn try_read_collections(collections_path: &Path) -> Result<Vec<Collection>, Error> {
+    let collections_string = tokio::fs::read_to_string(collections_path)
+        .await
+        .map_err(Error::CantRead)?;
+
+    serde_json::from_str(&collections_string).map_err(Error::CantParse)
+}
+
+async fn write_collections(
+    collections_path: &Path,
+    collections: &[Collection],
+) -> Result<(), Error> {
+    let collections_string =
+        serde_json::to_string_pretty(collections).map_err(Error::CantSerialize)?;
+
+    // Write to a temporary file first so a crash can't leave half written collections behind
+    let temporary_path = collections_path.with_extension("json.tmp");
+    tokio::fs::write(&temporary_path, collections_string)
+        .await
+        .map_err(Error::CantWrite)?;
+    tokio::fs::rename(&temporary_path, collections_path)
+        .await
+        .map_err(Error::CantWrite)
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::collection::{
+        AddToCollectionForm, CollectionKind, CreateCollectionForm, DeleteCollectionForm,
+        MoveCollectionForm, RemoveFromCollectionForm, UpdateCollectionForm,
+    };
+
+    use super::{CollectionOperation, CollectionSignal, Error, apply, read_collections};
+
+    fn create(name: &str, media_ids: &[&str]) -> CollectionOperation {
+        CollectionOperation::Create(CreateCollectionForm {
+            name: name.to_string(),
+            kind: CollectionKind::List,
+            media_ids: media_ids.iter().map(|id| id.to_string()).collect(),
+        })
+    }
+
+    fn add(id: &str, media_id: &str, position: Option<u32>) -> CollectionOperation {
+        CollectionOperation::AddMedia(AddToCollectionForm {
+            id: id.to_string(),
+            media_id: media_id.to_string(),
+            position,
+        })
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut collections = Vec::new();
+
+        let watch_later = apply(&mut collections, create("Watch later", &["A", "B", "A"])).unwrap();
+        assert_eq!(watch_later.id, "watch_later");
+        assert_eq!(watch_later.media_ids, ["A", "B"]);
+
+        // Ids stay unique
+        let duplicate = apply(&mut collections, create(" Watch later ", &[])).unwrap();
+        assert_eq!(duplicate.id, "watch_later-2");
+        assert_eq!(duplicate.name, "Watch later");
+
+        assert!(matches!(
+            apply(&mut collections, create("  ", &[])),
+            Err(Error::EmptyName)
+        ));
+
+        let collection = apply(&mut collections, add("watch_later", "C", Some(0))).unwrap();
+        assert_eq!(collection.media_ids, ["C", "A", "B"]);
+
+        // Media that's already there is moved
+        let collection = apply(&mut collections, add("watch_later", "C", None)).unwrap();
+        assert_eq!(collection.media_ids, ["A", "B", "C"]);
+
+        let collection = apply(
+            &mut collections,
+            CollectionOperation::RemoveMedia(RemoveFromCollectionForm {
+                id: "watch_later".to_string(),
+                media_id: "A".to_string(),
+            }),
+        )
+        .unwrap();
+        assert_eq!(collection.media_ids, ["B", "C"]);
+
+        let collection = apply(
+            &mut collections,
+            CollectionOperation::Update(UpdateCollectionForm {
+                id: "watch_later-2".to_string(),
+                name: "Alien".to_string(),
+                kind: CollectionKind::Franchise,
+            }),
+        )
+        .unwrap();
+        assert_eq!(collection.id, "watch_later-2");
+        assert_eq!(collection.kind, CollectionKind::Franchise);
+
+        apply(
+            &mut collections,
+            CollectionOperation::Move(MoveCollectionForm {
+                id: "watch_later-2".to_string(),
+                position: 0,
+            }),
+        )
+        .unwrap();
+        assert_eq!(collections[0].name, "Alien");
+
+        apply(
+            &mut collections,
+            CollectionOperation::Delete(DeleteCollectionForm {
+                id: "watch_later".to_string(),
+            }),
+        )
+        .unwrap();
+        assert_eq!(collections.len(), 1);
+
+        assert!(matches!(
+            apply(&mut collections, add("watch_later", "A", None)),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_collections_are_persisted() {
+        let tmp = tempfile::tempdir().unwrap();
+        let collections_path = tmp.path().join("collections.json");
+
+        let (watcher, receiver): (super::CollectionsSignalWatcher, _) =
+            crate::signal::new_watcher_receiver_pair(Box::new([]));
+        let handle = super::spawn(collections_path.clone(), receiver).await;
+
+        let (result_sender, result_receiver) = tokio::sync::oneshot::channel();
+        watcher
+            .signal_sender
+            .send(CollectionSignal {
+                operation: create("Watch later", &["My_Movie"]),
+                result_sender,
+            })
+            .await
+            .unwrap();
+        let collection = result_receiver.await.unwrap().unwrap();
+
+        assert_eq!(*watcher.data.borrow(), [collection.clone()].into());
+        assert_eq!(read_collections(&collections_path).await, vec![collection]);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_unsaved_changes_are_dropped() {
+        let tmp = tempfile::tempdir().unwrap();
+        let collections_path = tmp.path().join("missing").join("collections.json");
+
+        let (watcher, receiver): (super::CollectionsSignalWatcher, _) =
+            crate::signal::new_watcher_receiver_pair(Box::new([]));
+        let handle = super::spawn(collections_path.clone(), receiver).await;
+
+        let (result_sender, result_receiver) = tokio::sync::oneshot::channel();
+        watcher
+            .signal_sender
+            .send(CollectionSignal {
+                operation: create("Watch later", &["My_Movie"]),
+                result_sender,
+            })
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            result_receiver.await.unwrap(),
+            Err(Error::CantWrite(_))
+        ));
+        assert!(watcher.data.borrow().is_empty());
+
+        // The next change starts from the saved state
+        tokio::fs::create_dir(tmp.path().join("missing"))
+            .await
+            .unwrap();
+        let (result_sender, result_receiver) = tokio::sync::oneshot::channel();
+        watcher
+            .signal_sender
+            .send(CollectionSignal {
+                operation: create("Watch later", &[]),
+                result_sender,
+            })
+            .await
+            .unwrap();
+        let collection = result_receiver.await.unwrap().unwrap();
+
+        assert_eq!(collection.id, "watch_later");
+        assert_eq!(read_collections(&collections_path).await, vec![collection]);
+
+        handle.abort();
+    }
+}
@@ -13,8 +13,11 @@ pub async fn spawn(
     download_path: std::path::PathBuf,
     download_signal_receiver: DownloadSignalReceiver,
     download_signal_watcher: DownloadSignalWatcher,
+    max_active_downloads: Option<u32>,
 ) -> tokio::task::JoinHandle<()> {
-    let client = QBittorrentClient::try_new(Some(download_path)).unwrap();
+    let client = QBittorrentClient::try_new(Some(download_path))
+        .unwrap()
+        .with_max_active_downloads(max_active_downloads);
 
     let handle = tokio::spawn(async move {
         client
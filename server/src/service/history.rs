@@ -0,0 +1,225 @@
+use std::path::{Path, PathBuf};
+
+use domain::DownloadHistoryEntry;
+use log::{error, info, warn};
+
+pub enum HistorySignal {
+    Record(Box<[DownloadHistoryEntry]>),
+    Clear,
+}
+
+pub type HistorySignalWatcher =
+    crate::signal::SignalWatcher<HistorySignal, Box<[DownloadHistoryEntry]>>;
+pub type HistorySignalReceiver =
+    crate::signal::SignalReceiver<HistorySignal, Box<[DownloadHistoryEntry]>>;
+
+/// A service that keeps the download history on disk
+pub async fn spawn(
+    history_path: PathBuf,
+    mut history_signal_receiver: HistorySignalReceiver,
+) -> tokio::task::JoinHandle<()> {
+    let mut history = read_history(&history_path).await;
+    info!("Loaded {} download history entries", history.len());
+    history_signal_receiver
+        .updater
+        .send(history.clone().into())
+        .expect("Download history channel was closed");
+
+    tokio::spawn(async move {
+        while let Some(signal) = history_signal_receiver.signal_receiver.recv().await {
+            match signal {
+                HistorySignal::Record(entries) => {
+                    if !record(&mut history, entries) {
+                        continue;
+                    }
+                }
+                HistorySignal::Clear => history.clear(),
+            }
+
+            if let Err(err) = write_history(&history_path, &history).await {
+                error!(
+                    "Couldn't save download history to {}. Reason: {err}",
+                    history_path.display()
+                );
+            }
+
+            history_signal_receiver
+                .updater
+                .send(history.clone().into())
+                .expect("Download history channel was closed");
+        }
+    })
+}
+
+/// Adds the entries of downloads that aren't in the history yet, returns whether any were added.
+/// Faulty downloads are reported on every check until qBittorrent manages to remove them.
+fn record(
+    history: &mut Vec<DownloadHistoryEntry>,
+    entries: impl IntoIterator<Item = DownloadHistoryEntry>,
+) -> bool {
+    let previous_len = history.len();
+    for entry in entries {
+        let is_recorded = history
+            .iter()
+            .any(|recorded| recorded.id == entry.id && recorded.added_on == entry.added_on);
+        if !is_recorded {
+            history.push(entry);
+        }
+    }
+
+    history.len() != previous_len
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Can't read download history. {0}")]
+    CantRead(std::io::Error),
+    #[error("Can't parse download history. {0}")]
+    CantParse(serde_json::Error),
+    #[error("Can't serialize download history. {0}")]
+    CantSerialize(serde_json::Error),
+    #[error("Can't write download history. {0}")]
+    CantWrite(std::io::Error),
+}
+
+/// Starts with an empty history when there is no history file yet, or it's unreadable.
+async fn read_history(history_path: &Path) -> Vec<DownloadHistoryEntry> {
+    match try_read_history(history_path).await {
+        Ok(history) => history,
+        Err(Error::CantRead(err)) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => {
+            warn!(
+                "Ignoring download history at {}. Reason: {err}",
+                history_path.display()
+            );
+            Vec::new()
+        }
+    }
+}
+
+async fn try_read_history(history_path: &Path) -> Result<Vec<DownloadHistoryEntry>, Error> {
+    let history_string = tokio::fs::read_to_string(history_path)
+        .await
+        .map_err(Error::CantRead)?;
+
+    serde_json::from_str(&history_string).map_err(Error::CantParse)
+}
+
+async fn write_history(history_path: &Path, history: &[DownloadHistoryEntry]) -> Result<(), Error> {
+    let history_string = serde_json::to_string_pretty(history).map_err(Error::CantSerialize)?;
+
+    // Write to a temporary file first so a crash can't leave a half written history behind
+    let temporary_path = history_path.with_extension("json.tmp");
+    tokio::fs::write(&temporary_path, history_string)
+        .await
+        .map_err(Error::CantWrite)?;
+    tokio::fs::rename(&temporary_path, history_path)
+        .await
+        .map_err(Error::CantWrite)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use domain::{DownloadFailureReason, DownloadHistoryEntry, DownloadOutcome};
+
+    use super::{HistorySignal, read_history, record};
+
+    fn entry(id: &str, outcome: DownloadOutcome) -> DownloadHistoryEntry {
+        DownloadHistoryEntry {
+            id: id.into(),
+            title: "My Movie".into(),
+            added_on: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            finished_on: UNIX_EPOCH + Duration::from_secs(1_700_000_600),
+            outcome,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_history_is_persisted() {
+        let tmp = tempfile::tempdir().unwrap();
+        let history_path = tmp.path().join("download_history.json");
+
+        let entries = [
+            entry(
+                "completed",
+                DownloadOutcome::Completed {
+                    media_id: "My_Movie".to_string(),
+                },
+            ),
+            entry(
+                "failed",
+                DownloadOutcome::Failed {
+                    reason: DownloadFailureReason::CantMove,
+                    message: "Disk is full".to_string(),
+                },
+            ),
+        ];
+
+        let (watcher, receiver): (super::HistorySignalWatcher, _) =
+            crate::signal::new_watcher_receiver_pair(Box::new([]));
+        let handle = super::spawn(history_path.clone(), receiver).await;
+
+        let mut data = watcher.data.clone();
+        // Skip the history that was loaded on spawn
+        data.mark_unchanged();
+        watcher
+            .signal_sender
+            .send(HistorySignal::Record(entries.clone().into()))
+            .await
+            .unwrap();
+        data.changed().await.unwrap();
+
+        assert_eq!(*data.borrow_and_update(), entries.clone().into());
+        assert_eq!(read_history(&history_path).await, entries.to_vec());
+
+        watcher
+            .signal_sender
+            .send(HistorySignal::Clear)
+            .await
+            .unwrap();
+        data.changed().await.unwrap();
+
+        assert!(data.borrow_and_update().is_empty());
+        assert!(read_history(&history_path).await.is_empty());
+
+        handle.abort();
+    }
+
+    #[test]
+    fn test_faulty_download_is_recorded_once() {
+        let faulty = entry(
+            "faulty",
+            DownloadOutcome::Failed {
+                reason: DownloadFailureReason::Torrent,
+                message: "QBittorrent reported the torrent as Error".to_string(),
+            },
+        );
+        let mut history = Vec::new();
+
+        assert!(record(&mut history, [faulty.clone()]));
+        // Removing it failed, so it's reported again on the next check
+        assert!(!record(&mut history, [faulty.clone(), faulty.clone()]));
+        assert_eq!(history.len(), 1);
+
+        // The same torrent added again is a new download
+        let readded = DownloadHistoryEntry {
+            added_on: faulty.finished_on,
+            ..faulty.clone()
+        };
+        assert!(record(&mut history, [readded.clone()]));
+        assert_eq!(history, [faulty, readded]);
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_history_is_ignored() {
+        let tmp = tempfile::tempdir().unwrap();
+        let history_path = tmp.path().join("download_history.json");
+
+        assert!(read_history(&history_path).await.is_empty());
+
+        tokio::fs::write(&history_path, "not json").await.unwrap();
+        assert!(read_history(&history_path).await.is_empty());
+    }
+}
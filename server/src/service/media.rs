@@ -1,4 +1,4 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::collections::HashMap;
 
 use domain::Media;
 use log::{error, info, warn};
@@ -11,9 +11,9 @@ pub enum MediaSignal {
 pub type MediaSignalWatcher = crate::signal::SignalWatcher<MediaSignal, Box<[Media]>>;
 pub type MediaSignalReceiver = crate::signal::SignalReceiver<MediaSignal, Box<[Media]>>;
 
-/// A service that crawls the media library
+/// A service that crawls the media libraries
 pub async fn spawn(
-    media_dir: PathBuf,
+    libraries: crate::library::Libraries,
     mut media_signal_receiver: MediaSignalReceiver,
     media_signal_watcher: MediaSignalWatcher,
     prepare_signal_watcher: crate::service::prepare::PreparingListWatcher,
@@ -22,56 +22,77 @@ pub async fn spawn(
         let mut media_library = HashMap::new();
 
         while let Some(signal) = media_signal_receiver.signal_receiver.recv().await {
-            tokio::fs::create_dir_all(&media_dir)
-                .await
-                .expect("Couldn't create media dir");
+            for library_root in libraries.iter() {
+                tokio::fs::create_dir_all(&library_root.root)
+                    .await
+                    .expect("Couldn't create library dir");
+            }
 
             media_library = match signal {
                 MediaSignal::CrawlAll => {
                     info!("Crawling media items");
 
-                    let (media_library, prepare_list) =
-                        crate::crawl::crawl_all_folders(media_dir.to_string_lossy().as_ref()).await;
+                    let mut media_library = HashMap::new();
+                    let mut prepare_list = Vec::new();
+
+                    for library_root in libraries.iter() {
+                        let (library_media, library_prepare_list) =
+                            crate::crawl::crawl_all_folders(
+                                library_root.root.to_string_lossy().as_ref(),
+                            )
+                            .await;
+
+                        for (media_id, media) in library_media {
+                            // Media ids have to be unique, the first library to have it wins
+                            if media_library.contains_key(&media_id) {
+                                warn!(
+                                    "Media with id {media_id} is in more than one library. Ignoring the one in {}.",
+                                    library_root.library.name
+                                );
+                                continue;
+                            }
+                            media_library.insert(media_id, in_library(media, library_root));
+                        }
+                        prepare_list.extend(library_prepare_list);
+                    }
 
                     info!("Found {} media items", media_library.len());
 
-                    if !prepare_list.is_empty() {
-                        info!("{} media items need to be prepared", prepare_list.len());
-                        let prepare_futures = prepare_list.into_iter().map(|identifier| {
-                            prepare_signal_watcher
-                                .signal_sender
-                                .send(crate::service::prepare::PrepareMessage::Prepare(identifier))
-                        });
-                        futures::future::join_all(prepare_futures).await;
-                    }
+                    request_preparation(&prepare_signal_watcher, prepare_list).await;
 
                     media_library
                 }
                 MediaSignal::CrawlPartial { media_id } => {
                     info!("Crawling media item with id {media_id}");
 
-                    match crate::crawl::crawl_folder(
-                        media_dir.join(&media_id).to_string_lossy().as_ref(),
-                    )
-                    .await
-                    {
+                    let crawl_result = match libraries.find_media_folder(&media_id).await {
+                        Some((library_root, folder)) => {
+                            crate::crawl::crawl_folder(folder.to_string_lossy().as_ref())
+                                .await
+                                .map(|(new_media, prepare_list)| {
+                                    (
+                                        new_media.map(|media| in_library(media, library_root)),
+                                        prepare_list,
+                                    )
+                                })
+                        }
+                        None => None,
+                    };
+
+                    match crawl_result {
                         Some((new_media, prepare_list)) => {
-                            if let Some(new_media) = new_media {
-                                info!("Updated media item with id {media_id}");
-                                media_library.insert(media_id, new_media);
+                            match new_media {
+                                Some(new_media) => {
+                                    info!("Updated media item with id {media_id}");
+                                    media_library.insert(media_id, new_media);
+                                }
+                                // Same as a full crawl, items without playable content are left out
+                                None => {
+                                    media_library.remove(&media_id);
+                                }
                             }
 
-                            if !prepare_list.is_empty() {
-                                info!("{} media items need to be prepared", prepare_list.len());
-                                let prepare_futures = prepare_list.into_iter().map(|identifier| {
-                                    prepare_signal_watcher.signal_sender.send(
-                                        crate::service::prepare::PrepareMessage::Prepare(
-                                            identifier,
-                                        ),
-                                    )
-                                });
-                                futures::future::join_all(prepare_futures).await;
-                            }
+                            request_preparation(&prepare_signal_watcher, prepare_list).await;
                         }
                         None => {
                             media_library.remove(&media_id);
@@ -99,3 +120,27 @@ pub async fn spawn(
 
     handle
 }
+
+/// Crawled paths are relative to the library root, they're prefixed with the library id here
+fn in_library(mut media: Media, library_root: &crate::library::LibraryRoot) -> Media {
+    media.content = media.content.add_prefix(&library_root.library.id);
+    media.library_id = library_root.library.id.clone();
+    media
+}
+
+async fn request_preparation(
+    prepare_signal_watcher: &crate::service::prepare::PreparingListWatcher,
+    prepare_list: Vec<domain::MediaIdentifier>,
+) {
+    if prepare_list.is_empty() {
+        return;
+    }
+
+    info!("{} media items need to be prepared", prepare_list.len());
+    let prepare_futures = prepare_list.into_iter().map(|identifier| {
+        prepare_signal_watcher
+            .signal_sender
+            .send(crate::service::prepare::PrepareMessage::Prepare(identifier))
+    });
+    futures::future::join_all(prepare_futures).await;
+}
@@ -1,6 +1,10 @@
+pub mod auto_sync;
+pub mod collections;
 pub mod download;
+pub mod history;
 pub mod mdns;
 pub mod media;
 pub mod prepare;
 pub mod process;
 pub mod subtitle;
+pub mod thumbnails;
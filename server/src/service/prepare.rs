@@ -27,7 +27,7 @@ pub type PreparingListReceiver = crate::signal::SignalReceiver<
 pub fn spawn(
     mut signal_receiver: PreparingListReceiver,
     crate::AppState {
-        media_dir,
+        libraries,
         media_signal_watcher,
         preparing_list_watcher,
         ..
@@ -164,12 +164,17 @@ pub fn spawn(
                         .iter()
                         .cloned()
                         .map(|(id, _)| id)
-                        .flat_map(|id| id.strip_prefix(&media_dir))
+                        .flat_map(|id| libraries.relative_identifier(id))
                         .collect(),
                     track_selection_wait_queue
                         .iter()
                         .cloned()
-                        .flat_map(|item| item.strip_prefix(&media_dir))
+                        .flat_map(|item| {
+                            Some(domain::TrackSelectionItem {
+                                media: libraries.relative_identifier(item.media)?,
+                                tracks: item.tracks,
+                            })
+                        })
                         .collect(),
                 ))
                 .is_err()
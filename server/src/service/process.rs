@@ -1,35 +1,63 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashSet,
+    marker::PhantomData,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-use log::{error, info};
-use torrent::{TorrentExtra, TorrentInfo, qbittorrent_client::QBittorrentClientMessage};
+use domain::{
+    DownloadFailureReason, DownloadHistoryEntry, DownloadOutcome,
+    import::TransferMode,
+    metadata::MediaKind,
+    series::{EditSeriesFileMappingForm, EpisodeIdentifier},
+};
+use log::{error, info, warn};
+use torrent::{
+    TorrentExtra, TorrentInfo, TorrentState, qbittorrent_client::QBittorrentClientMessage,
+};
+
+use super::{download::DownloadSignalWatcher, history::HistorySignal};
 
 pub type ProcessingListWatcher = crate::signal::Watcher<Box<[Box<str>]>>;
 
 /// A service that observes downloads and processes them
 pub fn spawn(
-    media_dir: PathBuf,
     crate::AppState {
+        libraries,
         media_signal_watcher,
         mut download_signal_watcher,
         processing_list_watcher,
+        download_history_watcher,
         ..
     }: crate::AppState,
 ) -> tokio::task::JoinHandle<()> {
     tokio::spawn(async move {
+        let mut auto_mapped_ids = HashSet::new();
+
         loop {
-            let ids_to_remove: Box<[_]> = {
+            // 0. Map episodes of new series torrents, if their file names are clear enough
+            {
+                let torrents = download_signal_watcher.data.borrow().clone();
+                auto_map_episodes(&download_signal_watcher, &torrents, &mut auto_mapped_ids).await;
+            }
+
+            let (ids_to_remove, history_entries): (Box<[_]>, Box<[_]>) = {
                 // 1. Get torrent list when it changes
                 let torrents = download_signal_watcher.data.borrow_and_update().clone();
                 let processed_torrents = processing_list_watcher.data.borrow().clone();
 
-                let faulty_ids_to_remove: Box<[Box<str>]> = torrents
+                let faulty_torrents: Box<[(Box<str>, DownloadHistoryEntry)]> = torrents
                     .iter()
-                    .filter_map(|torrent| {
-                        if torrent.state.is_faulty() {
-                            return Some(torrent.hash.clone());
-                        }
+                    .filter(|torrent| torrent.state.is_faulty())
+                    .map(|torrent| {
+                        let outcome = DownloadOutcome::Failed {
+                            reason: DownloadFailureReason::Torrent,
+                            message: format!(
+                                "QBittorrent reported the torrent as {:?}",
+                                torrent.state
+                            ),
+                        };
 
-                        None
+                        (torrent.hash.clone(), history_entry(torrent, outcome))
                     })
                     .collect();
 
@@ -72,10 +100,10 @@ pub fn spawn(
 
                 // 5. Process the torents that needs to be processed
                 let process_futures = torrents_to_process.into_iter().map(
-                    async |torrent| -> Result<Box<str>, ProcessError> {
-                        info!("Preparing torrent named {}", &torrent.name);
+                    async |torrent| -> (TorrentInfo, Result<String, ProcessError>) {
+                        info!("Preparing torrent named {}", torrent.name);
 
-                        process(&media_dir, &torrent)
+                        let result = process(&libraries, &torrent)
                             .await
                             .inspect_err(|err| {
                                 error!(
@@ -84,21 +112,55 @@ pub fn spawn(
                                 )
                             })
                             .inspect(|_| {
-                                info!("Done preparing torrent named {}", &torrent.name);
-                            })
-                            .map(|_| torrent.hash)
+                                info!("Done preparing torrent named {}", torrent.name);
+                            });
+
+                        (torrent, result)
                     },
                 );
 
-                futures::future::join_all(process_futures)
-                    .await
-                    .into_iter()
-                    .flatten()
-                    .chain(faulty_ids_to_remove)
-                    .collect()
+                let processed_torrents = futures::future::join_all(process_futures).await;
+
+                // Torrents that failed to process are kept around so their files aren't lost
+                let processed_ids = processed_torrents
+                    .iter()
+                    .filter(|(_, result)| result.is_ok())
+                    .map(|(torrent, _)| torrent.hash.clone());
+
+                let processed_entries = processed_torrents.iter().map(|(torrent, result)| {
+                    let outcome = match result {
+                        Ok(media_id) => DownloadOutcome::Completed {
+                            media_id: media_id.clone(),
+                        },
+                        Err(err) => DownloadOutcome::Failed {
+                            reason: err.failure_reason(),
+                            message: err.to_string(),
+                        },
+                    };
+
+                    history_entry(torrent, outcome)
+                });
+
+                (
+                    processed_ids
+                        .chain(faulty_torrents.iter().map(|(id, _)| id.clone()))
+                        .collect(),
+                    processed_entries
+                        .chain(faulty_torrents.iter().map(|(_, entry)| entry.clone()))
+                        .collect(),
+                )
             };
 
-            // 6. Remove torrens that are done
+            // 6. Record what happened to the torrents
+            if !history_entries.is_empty() {
+                let _ = download_history_watcher
+                    .signal_sender
+                    .send(HistorySignal::Record(history_entries))
+                    .await
+                    .inspect_err(|_| error!("Download history loop was dropped."));
+            }
+
+            // 7. Remove torrens that are done
             let removal_futures = ids_to_remove.iter().map(async |hash| {
                 let (result_sender, result_receiver) = tokio::sync::oneshot::channel();
 
@@ -128,7 +190,7 @@ pub fn spawn(
 
             let did_media_library_change = !ids_to_remove.is_empty();
 
-            // 7. send a signal to refresh the media library
+            // 8. send a signal to refresh the media library
             if did_media_library_change {
                 let _ = media_signal_watcher
                     .signal_sender
@@ -158,13 +220,153 @@ enum ProcessError {
     CantMove(crate::moving::Error),
 }
 
+impl ProcessError {
+    fn failure_reason(&self) -> DownloadFailureReason {
+        match self {
+            ProcessError::CantGetExtra { .. } => DownloadFailureReason::CorruptMetadata,
+            ProcessError::CantMove(_) => DownloadFailureReason::CantMove,
+        }
+    }
+}
+
 impl From<crate::moving::Error> for ProcessError {
     fn from(value: crate::moving::Error) -> Self {
         ProcessError::CantMove(value)
     }
 }
 
-async fn process(media_dir: &Path, torrent: &TorrentInfo) -> Result<(), ProcessError> {
+/// Sets a file mapping on series torrents that don't have one, when every file can be mapped
+/// confidently. Ids of the torrents that were looked at are added to `attempted_ids`.
+async fn auto_map_episodes(
+    download_signal_watcher: &DownloadSignalWatcher,
+    torrents: &[TorrentInfo],
+    attempted_ids: &mut HashSet<Box<str>>,
+) {
+    for torrent in torrents {
+        if attempted_ids.contains(&torrent.hash) || torrent.state == TorrentState::MetaDL {
+            continue;
+        }
+
+        let Ok(TorrentExtra::Series {
+            metadata,
+            files_mapping_form: None,
+        }) = TorrentExtra::try_from(torrent)
+        else {
+            continue;
+        };
+
+        let (result_sender, result_receiver) = tokio::sync::oneshot::channel();
+        let Some(contents) = async {
+            download_signal_watcher
+                .signal_sender
+                .send(QBittorrentClientMessage::GetTorrentContents {
+                    id: torrent.hash.clone(),
+                    result_sender,
+                })
+                .await
+                .ok()?;
+
+            result_receiver
+                .await
+                .ok()?
+                .inspect_err(|err| {
+                    error!(
+                        "Couldn't get contents of torrent named {}. Reason: {err}",
+                        torrent.name
+                    )
+                })
+                .ok()
+        }
+        .await
+        else {
+            continue;
+        };
+
+        // Files show up once qBittorrent fetches the torrent's metadata
+        if contents.is_empty() {
+            continue;
+        }
+        attempted_ids.insert(torrent.hash.clone());
+
+        let suggestions = crate::file_mapping::suggest_file_mapping(
+            contents.iter().map(|contents| contents.name.as_ref()),
+        );
+        let Some(file_mapping) = crate::file_mapping::confident_mapping(&suggestions) else {
+            info!(
+                "Torrent named {} has ambiguous file names, waiting for a manual file mapping",
+                torrent.name
+            );
+            continue;
+        };
+        let unmapped_files = suggestions.len() - file_mapping.len();
+        if unmapped_files > 0 {
+            info!(
+                "Leaving {unmapped_files} ambiguous files of torrent named {} unmapped",
+                torrent.name
+            );
+        }
+
+        let allowed_files: Box<[String]> = contents
+            .iter()
+            .map(|contents| contents.name.to_string())
+            .collect();
+        let Some(valid_form) = EditSeriesFileMappingForm {
+            id: torrent.hash.clone(),
+            file_mapping,
+            phantom: PhantomData,
+        }
+        .validate(&allowed_files) else {
+            continue;
+        };
+
+        let (result_sender, result_receiver) = tokio::sync::oneshot::channel();
+        let result = async {
+            download_signal_watcher
+                .signal_sender
+                .send(QBittorrentClientMessage::SetExtra {
+                    id: torrent.hash.clone(),
+                    extra: Box::new(TorrentExtra::Series {
+                        metadata,
+                        files_mapping_form: Some(valid_form),
+                    }),
+                    result_sender,
+                })
+                .await
+                .ok()?;
+
+            result_receiver.await.ok()?.ok()
+        }
+        .await;
+
+        match result {
+            Some(()) => info!("Mapped episodes of torrent named {}", torrent.name),
+            None => error!(
+                "Couldn't save the file mapping of torrent named {}",
+                torrent.name
+            ),
+        }
+    }
+}
+
+fn history_entry(torrent: &TorrentInfo, outcome: DownloadOutcome) -> DownloadHistoryEntry {
+    let title = TorrentExtra::try_from(torrent)
+        .map(|extra| extra.metadata().title.into_boxed_str())
+        .unwrap_or_else(|_| torrent.name.clone());
+
+    DownloadHistoryEntry {
+        id: torrent.hash.clone(),
+        title,
+        added_on: UNIX_EPOCH + Duration::from_secs(torrent.added_on as u64),
+        finished_on: SystemTime::now(),
+        outcome,
+    }
+}
+
+/// Returns the id of the resulting media item
+async fn process(
+    libraries: &crate::library::Libraries,
+    torrent: &TorrentInfo,
+) -> Result<String, ProcessError> {
     let extra: TorrentExtra =
         torrent
             .as_ref()
@@ -174,23 +376,61 @@ async fn process(media_dir: &Path, torrent: &TorrentInfo) -> Result<(), ProcessE
                 inner: err,
             })?;
 
-    match extra {
+    let media_id = crate::moving::media_id(extra.metadata_ref());
+    let thumbnail = extra.metadata_ref().thumbnail.clone();
+    let kind = match extra {
+        TorrentExtra::Movie { .. } => MediaKind::Movie,
+        TorrentExtra::Series { .. } => MediaKind::Series,
+    };
+    // Media that's already in a library is updated in place
+    let media_dir = match libraries.find_media_folder(&media_id).await {
+        Some((library_root, _)) => &library_root.root,
+        None => &libraries.default_for(&kind).root,
+    };
+
+    let episodes: Vec<EpisodeIdentifier> = match extra {
         TorrentExtra::Movie { ref metadata } => {
-            crate::moving::generate_movie_media(media_dir, &torrent.save_path, metadata).await?;
+            crate::moving::generate_movie_media(
+                media_dir,
+                &torrent.save_path,
+                metadata,
+                TransferMode::Move,
+            )
+            .await?;
+
+            Vec::new()
         }
         TorrentExtra::Series {
             ref metadata,
             files_mapping_form,
         } => {
+            let files_mapping_form = files_mapping_form.expect("files mapping form was None.");
+            let episodes = files_mapping_form.file_mapping.values().cloned().collect();
+
             crate::moving::generate_series_media(
                 media_dir,
                 &torrent.save_path,
-                files_mapping_form.expect("files mapping form was None."),
+                files_mapping_form,
                 metadata,
+                TransferMode::Move,
             )
             .await?;
+
+            episodes
         }
+    };
+
+    // Without the record the media falls back to when its folder was created
+    if let Err(err) =
+        crate::ingest_file::record(media_dir.join(&media_id), episodes, SystemTime::now()).await
+    {
+        warn!("Couldn't record when {media_id} was added. Reason: {err}");
+    }
+
+    // Media is usable without artwork, it can be downloaded again when it's requested
+    if let Err(err) = crate::artwork::download(&media_dir.join(&media_id), &thumbnail).await {
+        warn!("Couldn't save artwork of {media_id}. Reason: {err}");
     }
 
-    Ok(())
+    Ok(media_id)
 }
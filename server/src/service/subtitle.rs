@@ -5,9 +5,10 @@ use domain::{
     subtitles::{SubtitleDownloadError, SubtitleProvider, SubtitleSelection},
 };
 use log::{info, warn};
-use open_subtitles::OpenSubtitlesClient;
 use tokio::io::AsyncWriteExt;
 
+use crate::subtitle_providers::{self, SubtitleProviders};
+
 pub enum SubtitleSignal {
     Download {
         media_path: PathBuf,
@@ -23,7 +24,7 @@ pub type SubtitleSignalReceiver = tokio::sync::mpsc::Receiver<SubtitleSignal>;
 /// A service that downloads and manages subtitles
 pub fn spawn(
     mut receiver: SubtitleSignalReceiver,
-    subtitle_provider: OpenSubtitlesClient,
+    subtitle_provider: SubtitleProviders,
 ) -> tokio::task::JoinHandle<()> {
     tokio::spawn(async move {
         loop {
@@ -56,7 +57,7 @@ pub fn spawn(
 
 async fn download_subtitle(
     media_path: &Path,
-    subtitle_provider: &OpenSubtitlesClient,
+    subtitle_provider: &SubtitleProviders,
     selection: SubtitleSelection,
     language_code: LanguageCode,
 ) -> Result<(), SubtitleDownloadError> {
@@ -76,10 +77,22 @@ async fn download_subtitle(
         return Err(SubtitleDownloadError::SubtitleAlreadyExists);
     }
 
+    if subtitle_provider.is_quota_exhausted(*id) {
+        warn!("Subtitle download quota is used up, not downloading subtitle with id {id}");
+        return Err(SubtitleDownloadError::DownloadQuotaReached);
+    }
+
     // 2. Download the string data
-    let subtitle_string = subtitle_provider.download(id).await.map_err(|_|
-            // TODO: Check other types of errors too
-            SubtitleDownloadError::DownloadQuotaReached)?;
+    let subtitle_string = subtitle_provider.download(id).await.map_err(|err| {
+        warn!("Couldn't download subtitle with id {id}. {err}");
+        match err {
+            subtitle_providers::Error::UnknownId(_) => SubtitleDownloadError::NonExistentSubtitle,
+            subtitle_providers::Error::OpenSubtitles(
+                open_subtitles::Error::DownloadQuotaReached,
+            ) => SubtitleDownloadError::DownloadQuotaReached,
+            _ => SubtitleDownloadError::ProviderError,
+        }
+    })?;
 
     // 3. Write the string data
     {
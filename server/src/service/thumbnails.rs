@@ -0,0 +1,80 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use domain::{Media, MediaContent};
+use log::{error, warn};
+
+/// Generates thumbnails for media files that don't have them yet, one file at a time.
+pub fn spawn(
+    crate::AppState {
+        libraries,
+        media_signal_watcher,
+        ..
+    }: crate::AppState,
+) -> tokio::task::JoinHandle<()> {
+    let mut media_library = media_signal_watcher.data.clone();
+
+    tokio::spawn(async move {
+        // Failed files aren't retried until the next restart
+        let mut attempted: HashSet<PathBuf> = HashSet::new();
+
+        while media_library.changed().await.is_ok() {
+            let pending: Vec<(String, PathBuf)> = media_library
+                .borrow_and_update()
+                .iter()
+                .flat_map(media_without_thumbnails)
+                .flat_map(|(media_id, media_file)| {
+                    libraries
+                        .resolve(media_file)
+                        .map(|media_file| (media_id, media_file))
+                })
+                .filter(|(_, media_file)| !attempted.contains(media_file))
+                .collect();
+
+            let mut updated_media_ids = HashSet::new();
+            for (media_id, media_file) in pending {
+                attempted.insert(media_file.clone());
+
+                match crate::thumbnails::generate(&media_file).await {
+                    Ok(_) => {
+                        updated_media_ids.insert(media_id);
+                    }
+                    Err(err) => warn!(
+                        "Couldn't generate thumbnails of {}. Reason: {err}",
+                        media_file.display()
+                    ),
+                }
+            }
+
+            for media_id in updated_media_ids {
+                if let Err(err) = media_signal_watcher
+                    .signal_sender
+                    .send(crate::service::media::MediaSignal::CrawlPartial {
+                        media_id: media_id.clone(),
+                    })
+                    .await
+                {
+                    error!(
+                        "Generated thumbnails of {media_id} but couldn't tell media service to recrawl it due to {err}."
+                    );
+                }
+            }
+        }
+    })
+}
+
+/// Media id and media file pairs
+fn media_without_thumbnails(media: &Media) -> Vec<(String, String)> {
+    let paths: Vec<&domain::MediaPaths> = match &media.content {
+        MediaContent::Movie(paths) => vec![paths],
+        MediaContent::Series(seasons) => seasons
+            .values()
+            .flat_map(|season| season.values())
+            .collect(),
+    };
+
+    paths
+        .into_iter()
+        .filter(|paths| paths.thumbnail.is_none() || paths.thumbnail_track.is_none())
+        .map(|paths| (media.id.clone(), paths.media.clone()))
+        .collect()
+}
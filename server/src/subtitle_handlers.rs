@@ -1,20 +1,30 @@
 use std::{collections::HashMap, path::PathBuf};
 
-use axum::{Json, extract, http::StatusCode, response::IntoResponse};
-use domain::subtitles::{SubtitleProvider, SubtitleSearchResponse};
+use axum::{
+    Json, extract,
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use domain::subtitles::{SubtitleProvider, SubtitleQuotaResponse, SubtitleSearchResponse};
 use domain::{
     language::LanguageCode,
     series::EpisodeIdentifier,
     subtitles::{
-        SubtitleDownloadError, SubtitleDownloadForm, SubtitleDownloadResponse, SubtitleSearchForm,
-        SubtitleSelection,
+        Styling, Subtitle, SubtitleAutoSyncForm, SubtitleDownloadError, SubtitleDownloadForm,
+        SubtitleDownloadResponse, SubtitleFormat, SubtitleSearchForm, SubtitleSelection,
+        SubtitleSyncForm, SubtitleTrack,
     },
 };
 use futures::FutureExt;
+use log::warn;
 
 use crate::{
     State,
-    service::subtitle::{SubtitleSignal, SubtitleSignalSender},
+    service::{
+        auto_sync::{AutoSyncJob, AutoSyncMessage},
+        subtitle::{SubtitleSignal, SubtitleSignalSender},
+    },
+    subtitle_sync,
 };
 
 pub async fn search_subtitles(
@@ -30,46 +40,45 @@ pub async fn search_subtitles(
             .find(|media| media.id == form.media_id)
             .ok_or(StatusCode::NOT_FOUND)?;
 
+        // The file itself is optional, searching by name still works without it
+        let search_param = |identifier: Option<EpisodeIdentifier>| -> axum::response::Result<_> {
+            let media_paths = media
+                .get_media_paths(identifier.as_ref())
+                .ok_or(StatusCode::NOT_FOUND)?;
+            let media_file = state.libraries.resolve(&media_paths.media);
+            Ok((identifier, media_paths.track_name.clone(), media_file))
+        };
+
         form.episode_identifiers
             .map(|identifiers| {
                 identifiers
                     .into_iter()
-                    .map(|identifier| {
-                        let track_name: String = {
-                            let media_paths = media
-                                .get_media_paths(Some(&identifier))
-                                .ok_or(StatusCode::NOT_FOUND.into_response())?;
-                            media_paths.track_name.clone()
-                        };
-                        Ok((Some(identifier), track_name))
-                    })
-                    .collect::<axum::response::Result<Vec<(Option<EpisodeIdentifier>, String)>>>()
+                    .map(|identifier| search_param(Some(identifier)))
+                    .collect::<axum::response::Result<Vec<_>>>()
             })
-            .unwrap_or_else(|| {
-                let track_name: String = {
-                    let media_paths = media
-                        .get_media_paths(None)
-                        .ok_or(StatusCode::NOT_FOUND.into_response())?;
-                    media_paths.track_name.clone()
-                };
-                Ok(vec![(None, track_name)])
-            })?
+            .unwrap_or_else(|| Ok(vec![search_param(None)?]))?
     };
 
     // 2. Convert the search params to search futures
-    let search_futures = search_params
-        .iter()
-        .map(|(episode_identifier, track_name)| async {
-            state
-                .subtitle_provider
-                .search(
-                    track_name,
-                    form.language_code.clone(),
-                    episode_identifier.clone(),
-                )
-                .await
-                .map(|result| result.collect::<Vec<_>>())
-        });
+    let search_futures =
+        search_params
+            .iter()
+            .map(|(episode_identifier, track_name, media_file)| async {
+                state
+                    .subtitle_provider
+                    .search(
+                        track_name,
+                        form.language_code.clone(),
+                        episode_identifier.clone(),
+                        media_file.as_deref(),
+                    )
+                    .await
+                    .map(|result| {
+                        result
+                            .filter(|option| form.filters.matches(option))
+                            .collect::<Vec<_>>()
+                    })
+            });
 
     // 3. Drive search futures to completion
     let results = futures::future::join_all(search_futures)
@@ -111,7 +120,8 @@ pub async fn download_subtitles(
             .map(|selection| {
                 media
                     .get_media_paths(selection.episode_identifier())
-                    .map(|media_paths| (selection, state.media_dir.join(&media_paths.media)))
+                    .and_then(|media_paths| state.libraries.resolve(&media_paths.media))
+                    .map(|media_path| (selection, media_path))
             })
             .collect::<Option<Vec<(SubtitleSelection, PathBuf)>>>()
     }
@@ -184,3 +194,182 @@ async fn download_subtitle(
 
     Ok(result)
 }
+
+/// Serves a subtitle of the media library converted to `vtt` or `srt`, in UTF-8
+pub async fn converted_subtitle(
+    extract::State(state): State,
+    extract::Path((format, path)): extract::Path<(String, String)>,
+) -> axum::response::Result<impl IntoResponse> {
+    let (format, content_type) = match SubtitleFormat::from_extension(&format) {
+        Some(SubtitleFormat::WebVtt) => (SubtitleFormat::WebVtt, "text/vtt; charset=utf-8"),
+        Some(SubtitleFormat::Srt) => (SubtitleFormat::Srt, "application/x-subrip; charset=utf-8"),
+        _ => return Err(StatusCode::NOT_FOUND.into()),
+    };
+
+    // Only files that are known to be subtitles are read
+    let is_subtitle = state
+        .media_signal_watcher
+        .data
+        .borrow()
+        .iter()
+        .any(|media| {
+            crate::search::media_paths(&media.content)
+                .flat_map(|paths| paths.subtitles.iter())
+                .any(|subtitle| subtitle.path == path)
+        });
+    if !is_subtitle {
+        return Err(StatusCode::NOT_FOUND.into());
+    }
+
+    let source_format = std::path::Path::new(&path)
+        .extension()
+        .and_then(|extension| SubtitleFormat::from_extension(&extension.to_string_lossy()))
+        .ok_or(StatusCode::UNSUPPORTED_MEDIA_TYPE)?;
+    let subtitle_path = state
+        .libraries
+        .resolve(&path)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let subtitle_bytes = tokio::fs::read(&subtitle_path)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+
+    let track = SubtitleTrack::parse(
+        &domain::subtitles::encoding::decode(&subtitle_bytes),
+        source_format,
+    )
+    .map_err(|err| {
+        warn!(
+            "Couldn't parse subtitle at {}. Reason: {err}",
+            subtitle_path.display()
+        );
+        StatusCode::UNPROCESSABLE_ENTITY
+    })?;
+
+    Ok((
+        [(header::CONTENT_TYPE, content_type)],
+        track.write(format, Styling::Preserve),
+    ))
+}
+
+/// Writes a copy of a subtitle of the media with the given offset and framerate correction
+pub async fn sync_subtitle(
+    extract::State(state): State,
+    axum::Json(SubtitleSyncForm {
+        media_id,
+        subtitle_path,
+        retiming,
+    }): axum::Json<SubtitleSyncForm>,
+) -> axum::response::Result<axum::Json<Subtitle>> {
+    if !retiming.is_valid() {
+        return Err(StatusCode::BAD_REQUEST.into());
+    }
+
+    let (_, subtitle) =
+        find_subtitle(&state, &media_id, &subtitle_path).ok_or(StatusCode::NOT_FOUND)?;
+
+    let resolved_path = state
+        .libraries
+        .resolve(&subtitle.path)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let retimed = subtitle_sync::write_retimed(&resolved_path, &retiming)
+        .await
+        .map_err(|err| {
+            warn!(
+                "Couldn't sync subtitle at {}. Reason: {err}",
+                resolved_path.display()
+            );
+            match err {
+                subtitle_sync::Error::CantRead(_) => StatusCode::NOT_FOUND,
+                subtitle_sync::Error::UnsupportedFormat
+                | subtitle_sync::Error::InvalidName
+                | subtitle_sync::Error::CantParse(_) => StatusCode::UNPROCESSABLE_ENTITY,
+                subtitle_sync::Error::CantWrite(_)
+                | subtitle_sync::Error::Ffmpeg(_)
+                | subtitle_sync::Error::NoMatch => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        })?;
+
+    state
+        .media_signal_watcher
+        .signal_sender
+        .send(crate::service::media::MediaSignal::CrawlPartial { media_id })
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // Same folder as the original, so the library path only differs in the file name
+    let file_name = retimed
+        .path
+        .file_name()
+        .expect("Retimed subtitle to have a file name")
+        .to_string_lossy();
+    let library_path = std::path::Path::new(&subtitle.path)
+        .with_file_name(file_name.as_ref())
+        .to_string_lossy()
+        .to_string();
+
+    Ok(Json(Subtitle::new(
+        retimed.id,
+        subtitle.language,
+        library_path,
+    )))
+}
+
+/// Queues a subtitle of the media to be synced against the speech in its audio
+pub async fn auto_sync_subtitle(
+    extract::State(state): State,
+    axum::Json(form): axum::Json<SubtitleAutoSyncForm>,
+) -> axum::response::Result<()> {
+    let (media_path, subtitle) =
+        find_subtitle(&state, &form.media_id, &form.subtitle_path).ok_or(StatusCode::NOT_FOUND)?;
+    let media_path = state
+        .libraries
+        .resolve(&media_path)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let subtitle_path = state
+        .libraries
+        .resolve(&subtitle.path)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    state
+        .auto_sync_watcher
+        .signal_sender
+        .send(AutoSyncMessage::Sync(AutoSyncJob {
+            form,
+            media_path,
+            subtitle_path,
+        }))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(())
+}
+
+/// Downloads left with OpenSubtitles, asked from the API if nothing was downloaded yet
+pub async fn get_quota(extract::State(state): State) -> axum::Json<SubtitleQuotaResponse> {
+    axum::Json(state.subtitle_provider.quota().await)
+}
+
+/// Subtitles waiting to be synced, the first one is being synced
+pub async fn get_auto_sync_queue(
+    extract::State(state): State,
+) -> axum::Json<Vec<SubtitleAutoSyncForm>> {
+    axum::Json(state.auto_sync_watcher.data.borrow().clone())
+}
+
+/// Library paths of the media file the subtitle belongs to, and the subtitle
+fn find_subtitle(
+    state: &crate::AppState,
+    media_id: &str,
+    subtitle_path: &str,
+) -> Option<(String, Subtitle)> {
+    let media_library = state.media_signal_watcher.data.borrow();
+    let media = media_library.iter().find(|media| media.id == media_id)?;
+
+    crate::search::media_paths(&media.content).find_map(|paths| {
+        paths
+            .subtitles
+            .iter()
+            .find(|subtitle| subtitle.path == subtitle_path)
+            .map(|subtitle| (paths.media.clone(), subtitle.clone()))
+    })
+}
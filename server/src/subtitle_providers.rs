@@ -0,0 +1,327 @@
+//! Fans subtitle searches out to every configured provider.
+//!
+//! Clients only know `usize` ids. OpenSubtitles ids are passed through as they are, so subtitles
+//! downloaded before there were other providers keep their ids. Ids of other providers are hashed
+//! to numbers at or above [`FOREIGN_ID_BIT`], and the provider id behind each number is recorded
+//! when it's handed out in search results.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use domain::{
+    language::LanguageCode,
+    series::EpisodeIdentifier,
+    subtitles::{SubtitleDownloadOption, SubtitleProvider, SubtitleQuotaResponse, rank},
+};
+use log::warn;
+use open_subtitles::OpenSubtitlesClient;
+use subdl::SubdlClient;
+
+/// Set on ids of every provider other than OpenSubtitles, whose ids are well below it. Stays
+/// clear of the sign bit for clients that read ids as signed 64 bit integers.
+const FOREIGN_ID_BIT: u64 = 1 << 62;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ProviderId {
+    OpenSubtitles(usize),
+    Subdl(String),
+}
+
+impl ProviderId {
+    /// Same across restarts, so downloaded files keep matching their search results
+    fn client_id(&self) -> usize {
+        match self {
+            ProviderId::OpenSubtitles(id) => *id,
+            ProviderId::Subdl(id) => {
+                (FOREIGN_ID_BIT | (fnv1a(&format!("subdl:{id}")) & (FOREIGN_ID_BIT - 1))) as usize
+            }
+        }
+    }
+}
+
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SubtitleProviders {
+    open_subtitles: Option<OpenSubtitlesClient>,
+    subdl: Option<SubdlClient>,
+    /// Client ids handed out in search results -> provider ids
+    ids: Arc<Mutex<HashMap<usize, ProviderId>>>,
+}
+
+impl SubtitleProviders {
+    pub fn with_open_subtitles(mut self, open_subtitles: OpenSubtitlesClient) -> Self {
+        self.open_subtitles = Some(open_subtitles);
+        self
+    }
+
+    pub fn with_subdl(mut self, subdl: SubdlClient) -> Self {
+        self.subdl = Some(subdl);
+        self
+    }
+
+    fn record<Id>(
+        &self,
+        options: impl Iterator<Item = SubtitleDownloadOption<Id>>,
+        provider_id: impl Fn(Id) -> ProviderId,
+    ) -> Vec<SubtitleDownloadOption<usize>> {
+        let mut ids = self
+            .ids
+            .lock()
+            .expect("Subtitle id lock to not be poisoned");
+
+        options
+            .map(|option| {
+                let provider_id = provider_id(option.id);
+                let id = provider_id.client_id();
+                ids.insert(id, provider_id);
+
+                SubtitleDownloadOption {
+                    id,
+                    title: option.title,
+                    download_count: option.download_count,
+                    language: option.language,
+                    hash_match: option.hash_match,
+                    metadata: option.metadata,
+                }
+            })
+            .collect()
+    }
+
+    pub fn provider_id(&self, id: usize) -> Option<ProviderId> {
+        let recorded = self
+            .ids
+            .lock()
+            .expect("Subtitle id lock to not be poisoned")
+            .get(&id)
+            .cloned();
+
+        // OpenSubtitles ids are valid without a search, e.g. after a restart
+        recorded.or_else(|| ((id as u64) < FOREIGN_ID_BIT).then_some(ProviderId::OpenSubtitles(id)))
+    }
+
+    /// Downloading `id` is known to fail until the quota of its provider resets
+    pub fn is_quota_exhausted(&self, id: usize) -> bool {
+        match self.provider_id(id) {
+            Some(ProviderId::OpenSubtitles(_)) => self
+                .open_subtitles
+                .as_ref()
+                .and_then(OpenSubtitlesClient::known_quota)
+                .is_some_and(|quota| quota.is_exhausted(SystemTime::now())),
+            // SubDL doesn't have a download quota
+            Some(ProviderId::Subdl(_)) | None => false,
+        }
+    }
+
+    pub async fn quota(&self) -> SubtitleQuotaResponse {
+        let Some(open_subtitles) = &self.open_subtitles else {
+            return SubtitleQuotaResponse {
+                open_subtitles: None,
+                logged_in: false,
+            };
+        };
+
+        let quota = open_subtitles.quota().await.unwrap_or_else(|err| {
+            warn!("Couldn't get OpenSubtitles quota. {err}");
+            open_subtitles.known_quota()
+        });
+        SubtitleQuotaResponse {
+            open_subtitles: quota,
+            logged_in: open_subtitles.is_logged_in(),
+        }
+    }
+}
+
+/// Best fits for `release` first, see [`rank`]. The same release is often uploaded to multiple
+/// providers, only its best ranked copy is kept.
+fn merge(
+    options: impl IntoIterator<Item = Vec<SubtitleDownloadOption<usize>>>,
+    release: &str,
+) -> Vec<SubtitleDownloadOption<usize>> {
+    let mut options = rank(options.into_iter().flatten(), release);
+
+    let mut seen = HashSet::new();
+    options
+        .retain(|option| seen.insert((release_key(&option.title), option.language.to_iso639_1())));
+    options
+}
+
+/// Titles are file names for some providers and release names for others, e.g.
+/// `Movie.2006.720p-GROUP.srt` and `Movie 2006 720p-GROUP`
+fn release_key(title: &str) -> String {
+    let title = title.to_lowercase();
+    let title = [".srt", ".vtt", ".ass", ".ssa", ".sub"]
+        .iter()
+        .find_map(|extension| title.strip_suffix(extension))
+        .unwrap_or(&title);
+
+    title
+        .chars()
+        .filter(|char| char.is_alphanumeric())
+        .collect()
+}
+
+impl SubtitleProvider for SubtitleProviders {
+    type SubtitleId = usize;
+    type Error = Error;
+
+    async fn search(
+        &self,
+        query: &str,
+        language: LanguageCode,
+        episode: Option<EpisodeIdentifier>,
+        media_file: Option<&Path>,
+    ) -> Result<impl Iterator<Item = SubtitleDownloadOption<Self::SubtitleId>>> {
+        let open_subtitles = async {
+            let open_subtitles = self.open_subtitles.as_ref()?;
+            Some(
+                open_subtitles
+                    .search(query, language.clone(), episode.clone(), media_file)
+                    .await
+                    .map(|options| self.record(options, ProviderId::OpenSubtitles))
+                    .map_err(Error::OpenSubtitles),
+            )
+        };
+        let subdl = async {
+            let subdl = self.subdl.as_ref()?;
+            Some(
+                subdl
+                    .search(query, language.clone(), episode.clone(), media_file)
+                    .await
+                    .map(|options| self.record(options, ProviderId::Subdl))
+                    .map_err(Error::Subdl),
+            )
+        };
+        let (open_subtitles, subdl) = futures::join!(open_subtitles, subdl);
+
+        // A provider that's down or out of quota shouldn't hide results of the others
+        let (results, errors): (Vec<_>, Vec<_>) = open_subtitles
+            .into_iter()
+            .chain(subdl)
+            .partition(|result| result.is_ok());
+        let errors: Vec<Error> = errors.into_iter().filter_map(Result::err).collect();
+        for err in &errors {
+            warn!("Couldn't search subtitles for {query}. {err}");
+        }
+        if results.is_empty() {
+            return Err(Error::NoProvider(errors));
+        }
+
+        Ok(merge(results.into_iter().filter_map(Result::ok), query).into_iter())
+    }
+
+    async fn download(&self, id: &Self::SubtitleId) -> Result<String> {
+        match self.provider_id(*id) {
+            Some(ProviderId::OpenSubtitles(open_subtitles_id)) => {
+                let open_subtitles = self.open_subtitles.as_ref().ok_or(Error::UnknownId(*id))?;
+                open_subtitles
+                    .download(&open_subtitles_id)
+                    .await
+                    .map_err(Error::OpenSubtitles)
+            }
+            Some(ProviderId::Subdl(subdl_id)) => {
+                let subdl = self.subdl.as_ref().ok_or(Error::UnknownId(*id))?;
+                subdl.download(&subdl_id).await.map_err(Error::Subdl)
+            }
+            None => Err(Error::UnknownId(*id)),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("OpenSubtitles failed. {0}")]
+    OpenSubtitles(open_subtitles::Error),
+    #[error("SubDL failed. {0}")]
+    Subdl(subdl::Error),
+    #[error("No provider could search. {0:?}")]
+    NoProvider(Vec<Error>),
+    #[error("Subtitle with id {0} wasn't in any search results")]
+    UnknownId(usize),
+}
+
+type Result<T> = core::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::{FOREIGN_ID_BIT, ProviderId, SubtitleProviders, merge};
+    use domain::{language::LanguageCode, subtitles::SubtitleDownloadOption};
+
+    fn option(id: usize, title: &str, download_count: usize) -> SubtitleDownloadOption<usize> {
+        SubtitleDownloadOption {
+            id,
+            title: title.to_string(),
+            download_count,
+            language: LanguageCode::English,
+            hash_match: false,
+            metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let merged = merge(
+            [
+                vec![
+                    option(1, "Movie.2006.720p.BluRay-GROUP.srt", 10),
+                    option(2, "Movie.2006.1080p.WEB-OTHER.srt", 500),
+                    SubtitleDownloadOption {
+                        hash_match: true,
+                        ..option(5, "Movie.2006.720p.WEB-EXACT.srt", 1)
+                    },
+                ],
+                vec![
+                    option(3, "Movie 2006 720p BluRay-GROUP", 0),
+                    option(4, "Movie.2006.DVDRip", 0),
+                ],
+            ],
+            "Movie.2006.720p.BluRay-GROUP",
+        );
+
+        let ids: Vec<_> = merged.iter().map(|option| option.id).collect();
+        assert_eq!(ids, [5, 1, 2, 4]);
+    }
+
+    #[test]
+    fn test_provider_ids() {
+        let providers = SubtitleProviders::default();
+        let options = providers.record(
+            [SubtitleDownloadOption {
+                id: "1984093-2048876".to_string(),
+                title: String::new(),
+                download_count: 0,
+                language: LanguageCode::English,
+                hash_match: false,
+                metadata: Default::default(),
+            }]
+            .into_iter(),
+            ProviderId::Subdl,
+        );
+
+        let id = options[0].id;
+        assert!(id as u64 >= FOREIGN_ID_BIT);
+        assert_eq!(
+            id,
+            ProviderId::Subdl("1984093-2048876".to_string()).client_id()
+        );
+        assert_eq!(
+            providers.provider_id(id),
+            Some(ProviderId::Subdl("1984093-2048876".to_string()))
+        );
+
+        // OpenSubtitles ids don't need a search
+        assert_eq!(
+            providers.provider_id(42),
+            Some(ProviderId::OpenSubtitles(42))
+        );
+        assert_eq!(providers.provider_id(id + 1), None);
+    }
+}
@@ -0,0 +1,175 @@
+//! Writes retimed copies of stored subtitles next to the original, either with a given retiming
+//! or one that is found from the speech in the audio.
+//!
+//! The copy keeps the episode and language prefix of the original file name and gets an id with a
+//! `_sync` suffix, e.g. `1-eng-42.srt` becomes `1-eng-42_sync.srt`, then `1-eng-42_sync2.srt`.
+
+use std::path::{Path, PathBuf};
+
+use domain::subtitles::{
+    ParseError, Retiming, Styling, SubtitleFormat, SubtitleTrack,
+    align::{Alignment, align},
+};
+use tokio::io::AsyncWriteExt;
+
+const SYNC_SUFFIX: &str = "_sync";
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct RetimedSubtitle {
+    pub id: String,
+    pub path: PathBuf,
+}
+
+pub async fn write_retimed(subtitle_path: &Path, retiming: &Retiming) -> Result<RetimedSubtitle> {
+    let (format, mut track) = read(subtitle_path).await?;
+    track.retime(retiming);
+
+    write_copy(subtitle_path, format, &track).await
+}
+
+/// Lines the cues up with the speech in the audio of `media_path` and writes the result as a
+/// copy
+pub async fn write_auto_synced(
+    media_path: &Path,
+    subtitle_path: &Path,
+) -> Result<(RetimedSubtitle, Alignment)> {
+    let (format, track) = read(subtitle_path).await?;
+    let voice_activity = ffmpeg::detect_voice_activity(media_path)
+        .await
+        .map_err(Error::Ffmpeg)?;
+
+    // Tries thousands of offsets, keep it off the async workers
+    let (mut track, alignment) = tokio::task::spawn_blocking(move || {
+        let alignment = align(&track, &voice_activity);
+        (track, alignment)
+    })
+    .await
+    .expect("Alignment to not panic");
+    let alignment = alignment.ok_or(Error::NoMatch)?;
+    track.retime(&alignment.retiming);
+
+    let retimed = write_copy(subtitle_path, format, &track).await?;
+    Ok((retimed, alignment))
+}
+
+async fn read(subtitle_path: &Path) -> Result<(SubtitleFormat, SubtitleTrack)> {
+    let format = subtitle_path
+        .extension()
+        .and_then(|extension| SubtitleFormat::from_extension(&extension.to_string_lossy()))
+        .ok_or(Error::UnsupportedFormat)?;
+
+    let subtitle_bytes = tokio::fs::read(subtitle_path)
+        .await
+        .map_err(Error::CantRead)?;
+    let track = SubtitleTrack::parse(
+        &domain::subtitles::encoding::decode(&subtitle_bytes),
+        format,
+    )
+    .map_err(Error::CantParse)?;
+
+    Ok((format, track))
+}
+
+async fn write_copy(
+    subtitle_path: &Path,
+    format: SubtitleFormat,
+    track: &SubtitleTrack,
+) -> Result<RetimedSubtitle> {
+    let (prefix, id) = subtitle_path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.rsplit_once('-'))
+        .ok_or(Error::InvalidName)?;
+    let retimed = track.write(format, Styling::Preserve);
+
+    // Syncing a synced copy again starts from the original id
+    let base_id = id
+        .split_once(SYNC_SUFFIX)
+        .map_or(id, |(base_id, _)| base_id);
+    for copy_no in 1.. {
+        let id = match copy_no {
+            1 => format!("{base_id}{SYNC_SUFFIX}"),
+            copy_no => format!("{base_id}{SYNC_SUFFIX}{copy_no}"),
+        };
+        let path = subtitle_path.with_file_name(format!("{prefix}-{id}.{}", format.extension()));
+
+        let mut file = match tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await
+        {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(Error::CantWrite(err)),
+        };
+        file.write_all(retimed.as_bytes())
+            .await
+            .map_err(Error::CantWrite)?;
+
+        return Ok(RetimedSubtitle { id, path });
+    }
+
+    unreachable!("Copy numbers to run out before free file names")
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Subtitle format isn't supported")]
+    UnsupportedFormat,
+    #[error("Subtitle file name doesn't have an id")]
+    InvalidName,
+    #[error("Can't read subtitle. {0}")]
+    CantRead(std::io::Error),
+    #[error("Can't parse subtitle. {0}")]
+    CantParse(ParseError),
+    #[error("Can't write retimed subtitle. {0}")]
+    CantWrite(std::io::Error),
+    #[error("Can't detect speech. {0}")]
+    Ffmpeg(ffmpeg::Error),
+    #[error("Subtitle doesn't match the speech in the audio")]
+    NoMatch,
+}
+
+type Result<T> = core::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use domain::subtitles::Retiming;
+
+    use super::{RetimedSubtitle, write_retimed};
+
+    #[tokio::test]
+    async fn test_write_retimed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let original = tmp.path().join("1-eng-42.srt");
+        tokio::fs::write(&original, "1\n00:00:01,000 --> 00:00:02,000\nHello\n")
+            .await
+            .unwrap();
+        let retiming = Retiming {
+            offset_millis: 500,
+            framerate: None,
+        };
+
+        let first = write_retimed(&original, &retiming).await.unwrap();
+        assert_eq!(
+            first,
+            RetimedSubtitle {
+                id: "42_sync".to_string(),
+                path: tmp.path().join("1-eng-42_sync.srt")
+            }
+        );
+        assert_eq!(
+            tokio::fs::read_to_string(&first.path).await.unwrap(),
+            "1\n00:00:01,500 --> 00:00:02,500\nHello\n"
+        );
+
+        // Copies of copies don't pile up suffixes
+        let second = write_retimed(&first.path, &retiming).await.unwrap();
+        assert_eq!(second.id, "42_sync2");
+        assert_eq!(
+            tokio::fs::read_to_string(&second.path).await.unwrap(),
+            "1\n00:00:02,000 --> 00:00:03,000\nHello\n"
+        );
+    }
+}
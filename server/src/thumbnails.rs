@@ -0,0 +1,162 @@
+//! Preview images generated from the video itself. They're kept in a `thumbnails` folder next to
+//! the media file, named after it.
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use log::info;
+
+const THUMBNAILS_DIR: &str = "thumbnails";
+
+/// Representative frames are taken a bit into the video to skip intros and black frames
+const FRAME_POSITION_RATIO: f64 = 0.1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThumbnailPaths {
+    pub frame: PathBuf,
+    pub sprite_sheet: PathBuf,
+    /// WebVTT track that points at tiles of the sprite sheet
+    pub track: PathBuf,
+}
+
+impl ThumbnailPaths {
+    pub fn of(media_file: impl AsRef<Path>) -> Option<Self> {
+        let media_file = media_file.as_ref();
+        let dir = media_file.parent()?.join(THUMBNAILS_DIR);
+        let stem = media_file.file_stem()?.to_str()?;
+
+        Some(Self {
+            frame: dir.join(format!("{stem}.jpg")),
+            sprite_sheet: dir.join(format!("{stem}.sprite.jpg")),
+            track: dir.join(format!("{stem}.vtt")),
+        })
+    }
+
+    /// Returns the paths of the frame and the thumbnail track, if they were generated
+    pub async fn existing(media_file: impl AsRef<Path>) -> (Option<String>, Option<String>) {
+        let Some(paths) = Self::of(media_file) else {
+            return (None, None);
+        };
+
+        let exists = async |path: PathBuf| {
+            tokio::fs::try_exists(&path)
+                .await
+                .unwrap_or(false)
+                .then(|| path.to_string_lossy().to_string())
+        };
+
+        // The track is useless without its sprite sheet
+        let track = match exists(paths.sprite_sheet).await {
+            Some(_) => exists(paths.track).await,
+            None => None,
+        };
+
+        (exists(paths.frame).await, track)
+    }
+}
+
+/// Extracts a representative frame, a sprite sheet and its thumbnail track from the media file.
+pub async fn generate(media_file: impl AsRef<Path>) -> Result<ThumbnailPaths> {
+    let media_file = media_file.as_ref();
+    let paths = ThumbnailPaths::of(media_file)
+        .ok_or_else(|| Error::InvalidMediaPath(media_file.to_path_buf()))?;
+
+    if let Some(dir) = paths.frame.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(Error::CantWrite)?;
+    }
+
+    let duration = ffmpeg::get_duration(media_file).await?;
+
+    ffmpeg::extract_frame(
+        media_file,
+        Duration::from_secs_f64(duration.as_secs_f64() * FRAME_POSITION_RATIO),
+        &paths.frame,
+    )
+    .await?;
+
+    let sprite_sheet = ffmpeg::SpriteSheet::for_duration(duration);
+    ffmpeg::generate_sprite_sheet(media_file, &sprite_sheet, &paths.sprite_sheet).await?;
+
+    let sprite_sheet_name = paths
+        .sprite_sheet
+        .file_name()
+        .expect("Sprite sheet to have a file name")
+        .to_string_lossy();
+    tokio::fs::write(
+        &paths.track,
+        sprite_sheet.to_webvtt(&sprite_sheet_name, duration),
+    )
+    .await
+    .map_err(Error::CantWrite)?;
+
+    info!("Generated thumbnails of {}", media_file.display());
+
+    Ok(paths)
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Media path {0:#?} has no file name")]
+    InvalidMediaPath(PathBuf),
+    #[error("Can't generate thumbnails. {0}")]
+    Ffmpeg(#[from] ffmpeg::Error),
+    #[error("Can't write thumbnails. {0}")]
+    CantWrite(std::io::Error),
+}
+
+type Result<T> = core::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::ThumbnailPaths;
+
+    #[test]
+    fn test_paths() {
+        let paths = ThumbnailPaths::of("media/My_Series/1/3.mp4").unwrap();
+
+        assert_eq!(
+            paths,
+            ThumbnailPaths {
+                frame: PathBuf::from("media/My_Series/1/thumbnails/3.jpg"),
+                sprite_sheet: PathBuf::from("media/My_Series/1/thumbnails/3.sprite.jpg"),
+                track: PathBuf::from("media/My_Series/1/thumbnails/3.vtt"),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_existing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let media_file = tmp.path().join("My_Movie.mp4");
+        let paths = ThumbnailPaths::of(&media_file).unwrap();
+
+        assert_eq!(ThumbnailPaths::existing(&media_file).await, (None, None));
+
+        tokio::fs::create_dir(paths.frame.parent().unwrap())
+            .await
+            .unwrap();
+        tokio::fs::write(&paths.frame, "").await.unwrap();
+        tokio::fs::write(&paths.track, "").await.unwrap();
+
+        // The sprite sheet is missing
+        assert_eq!(
+            ThumbnailPaths::existing(&media_file).await,
+            (Some(paths.frame.to_string_lossy().to_string()), None)
+        );
+
+        tokio::fs::write(&paths.sprite_sheet, "").await.unwrap();
+        assert_eq!(
+            ThumbnailPaths::existing(&media_file).await,
+            (
+                Some(paths.frame.to_string_lossy().to_string()),
+                Some(paths.track.to_string_lossy().to_string())
+            )
+        );
+    }
+}
@@ -1,10 +1,11 @@
 use crate::capabilities::service_discovery::{DiscoveredService, ServiceDiscoveryOperation};
 use crate::features;
-use crate::features::data::DataRequest;
+use crate::features::data::{CollectionSummary, DataRequest, DownloadSummary};
 use crate::features::playback::PlaybackModel;
 use crate::features::query::QueryState;
 use crate::features::query::view_model_queries::{
-    ActionState, MediaItems, MediaItemsContent, SubtitleSearchResults, SubtitleSearchState,
+    ActionState, DownloadHistory, MediaItems, MediaItemsContent, RecentlyAddedItems, SearchResults,
+    SubtitleSearchResults, SubtitleSearchState,
 };
 use crate::features::subtitle::SubtitleEvent;
 use crate::features::{
@@ -13,8 +14,14 @@ use crate::features::{
 };
 use crux_core::command::CommandContext;
 use crux_core::{App, macros::effect, render::RenderOperation};
-use domain::Download;
 use domain::series::SeriesFileMapping;
+use domain::{
+    Download, DownloadHistoryEntry, DownloadQueueSettings,
+    collection::Collection,
+    library::Library,
+    search::{MediaQuery, MediaSearchResults, RecentlyAdded},
+    subtitles::Retiming,
+};
 use partially::Partial;
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -30,6 +37,7 @@ pub enum Event {
     Startup,
     ScreenChanged(Screen),
     UpdateData(DataRequest),
+    Search(MediaQuery),
     ServerCommunication(ServerCommunicationEvent),
     Play(PlayEvent),
     PlaybackProgress((u64, PlaybackPosition)),
@@ -61,28 +69,52 @@ pub struct Model {
     pub current_screen: Screen,
     pub connection_state: Option<QueryState<()>>,
     pub media_items: QueryState<MediaItemsContent>,
+    pub search_results: QueryState<MediaSearchResults>,
+    pub recently_added: QueryState<Vec<RecentlyAdded>>,
+    pub libraries: Vec<Library>,
+    /// Id of the library `media_items` are listed from, every library if `None`
+    pub selected_library: Option<String>,
+    pub collections: Vec<Collection>,
     pub downloads: Vec<Download>,
+    pub download_queue_settings: Option<DownloadQueueSettings>,
+    pub download_history: QueryState<Vec<DownloadHistoryEntry>>,
     pub torrent_contents: Option<(String, SeriesFileMapping)>,
+    /// Files in `torrent_contents` the server couldn't confidently map to an episode
+    pub ambiguous_files: Vec<String>,
     pub playback: PlaybackModel,
     pub discovered_services: Vec<DiscoveredService>,
 
     // TODO consolidate
     pub subtitles_search_results: QueryState<SubtitleSearchResults>,
     pub subtitle_download_results: Option<QueryState<()>>,
+    /// Correction being edited on the subtitle sync screen
+    pub subtitle_retiming: Retiming,
+    pub subtitle_sync_result: Option<QueryState<()>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct ViewModel {
     connection_state: Option<ActionState>,
     media_items: MediaItems,
-    downloads: Vec<Download>,
+    search_results: SearchResults,
+    /// Home screen section with new media and series that got new episodes
+    recently_added: RecentlyAddedItems,
+    libraries: Vec<Library>,
+    selected_library: Option<String>,
+    collections: Vec<CollectionSummary>,
+    downloads: Vec<DownloadSummary>,
+    download_queue_settings: Option<DownloadQueueSettings>,
+    download_history: DownloadHistory,
     playback_detail: PlaybackModel,
     torrent_contents: Option<(String, SeriesFileMapping)>,
+    ambiguous_files: Vec<String>,
     discovered_services: Vec<DiscoveredService>,
 
     // TODO consolidate
     subtitle_search_results: SubtitleSearchState,
     subtitle_download_results: Option<ActionState>,
+    subtitle_retiming: Retiming,
+    subtitle_sync_result: Option<ActionState>,
 }
 
 #[derive(Default)]
@@ -105,6 +137,7 @@ impl App for CounterApp {
             Event::Startup => features::lifetime::handle_startup(model),
             Event::ScreenChanged(screen) => features::lifetime::handle_screen_change(model, screen),
             Event::UpdateData(request) => features::data::update_data(model, request),
+            Event::Search(query) => features::data::handle_search(model, query),
             Event::ServerCommunication(event) => {
                 features::server_communication::handle_server_communication(model, event)
             }
@@ -132,15 +165,34 @@ impl App for CounterApp {
         ViewModel {
             connection_state: model.connection_state.clone().map(ActionState::from),
             media_items: model.media_items.clone().into(),
+            search_results: model.search_results.clone().into(),
+            recently_added: model.recently_added.clone().into(),
+            libraries: model.libraries.clone(),
+            selected_library: model.selected_library.clone(),
+            collections: model
+                .collections
+                .iter()
+                .map(|collection| CollectionSummary::new(collection, model.media_items.get_data()))
+                .collect(),
             playback_detail: model.playback.clone(),
-            downloads: model.downloads.clone(),
+            downloads: model
+                .downloads
+                .iter()
+                .cloned()
+                .map(DownloadSummary::from)
+                .collect(),
+            download_queue_settings: model.download_queue_settings.clone(),
+            download_history: model.download_history.clone().into(),
             torrent_contents: model.torrent_contents.clone(),
+            ambiguous_files: model.ambiguous_files.clone(),
             discovered_services: model.discovered_services.clone(),
             subtitle_search_results: model.subtitles_search_results.clone().into(),
             subtitle_download_results: model
                 .subtitle_download_results
                 .clone()
                 .map(ActionState::from),
+            subtitle_retiming: model.subtitle_retiming,
+            subtitle_sync_result: model.subtitle_sync_result.clone().map(ActionState::from),
         }
     }
 }
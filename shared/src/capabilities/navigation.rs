@@ -1,13 +1,16 @@
 use crux_core::{Command, Request, capability::Operation, command::RequestBuilder};
-use domain::{Media, SeasonContents, language::LanguageCode, series::EpisodeIdentifier};
+use domain::{
+    Media, SeasonContents, language::LanguageCode, series::EpisodeIdentifier, subtitles::Subtitle,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub enum NavigationOperation {
-    Push(Screen),
-    ReplaceRoot(Screen),
+    // Screens are boxed since they can carry whole media items
+    Push(Box<Screen>),
+    ReplaceRoot(Box<Screen>),
     Pop(usize),
-    Reset(Option<Screen>),
+    Reset(Option<Box<Screen>>),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Default)]
@@ -30,6 +33,7 @@ pub enum Screen {
     },
     ServerFileMapping(String),
     AddDownload,
+    DownloadHistory,
     SubtitleSelection {
         media: Media,
         /// (Season, Pre selected episodes). None means this is a movie.
@@ -42,6 +46,11 @@ pub enum Screen {
         /// `None` for movies, `Some` with episode identifiers for series
         episodes: Option<Vec<EpisodeIdentifier>>,
     },
+    /// Offset and framerate correction for a stored subtitle
+    SubtitleSync {
+        media: Media,
+        subtitle: Subtitle,
+    },
 }
 
 impl Operation for NavigationOperation {
@@ -56,7 +65,7 @@ where
     Effect: Send + From<Request<NavigationOperation>> + 'static,
     Event: Send + 'static,
 {
-    Command::request_from_shell(NavigationOperation::ReplaceRoot(to))
+    Command::request_from_shell(NavigationOperation::ReplaceRoot(Box::new(to)))
 }
 
 #[must_use]
@@ -65,7 +74,7 @@ where
     Effect: Send + From<Request<NavigationOperation>> + 'static,
     Event: Send + 'static,
 {
-    Command::request_from_shell(NavigationOperation::Push(to))
+    Command::request_from_shell(NavigationOperation::Push(Box::new(to)))
 }
 
 #[must_use]
@@ -85,5 +94,5 @@ where
     Effect: Send + From<Request<NavigationOperation>> + 'static,
     Event: Send + 'static,
 {
-    Command::request_from_shell(NavigationOperation::Reset(screen))
+    Command::request_from_shell(NavigationOperation::Reset(screen.map(Box::new)))
 }
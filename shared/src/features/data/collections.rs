@@ -0,0 +1,146 @@
+use domain::{
+    Media,
+    collection::{Collection, CollectionKind},
+};
+
+use crate::{
+    Event, Model, PartialModel,
+    capabilities::{
+        http,
+        navigation::{self, Screen},
+    },
+    features::{
+        data::DataRequest, query::view_model_queries::MediaItemsContent, utils::update_model,
+    },
+};
+
+/// A collection with its media, in order. Media that isn't loaded is left out.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct CollectionSummary {
+    pub id: String,
+    pub name: String,
+    pub kind: CollectionKind,
+    pub media: Vec<Media>,
+}
+
+impl CollectionSummary {
+    pub fn new(collection: &Collection, media_items: Option<&MediaItemsContent>) -> Self {
+        Self {
+            id: collection.id.clone(),
+            name: collection.name.clone(),
+            kind: collection.kind,
+            media: collection
+                .media_ids
+                .iter()
+                .filter_map(|media_id| media_items.and_then(|items| items.get(media_id)))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+pub fn handle_get_collections(model: &Model) -> crate::Command {
+    let base_url = model.base_url.clone();
+
+    crate::Command::new(async move |ctx| {
+        let url = {
+            let mut url = if let Some(url) = base_url {
+                url
+            } else {
+                return navigation::push(Screen::ServerAddressEntry)
+                    .into_future(ctx)
+                    .await;
+            };
+
+            url.set_path("collections");
+            url
+        };
+
+        match http::get(url).into_future(ctx.clone()).await {
+            http::HttpOutput::Success { data, .. } => {
+                // TODO: Add logging when we can't get data or deserialize from JSON string
+                let collections: Option<Vec<Collection>> =
+                    data.and_then(|data| serde_json::from_str(&data).ok());
+
+                update_model(
+                    &ctx,
+                    PartialModel {
+                        collections,
+                        ..Default::default()
+                    },
+                );
+            }
+            http::HttpOutput::Error => {
+                // TODO: add logging
+            }
+        }
+    })
+}
+
+/// Collections are fetched again once the server replies
+pub fn post_and_refresh(model: &Model, path: &'static str, body: String) -> crate::Command {
+    let base_url = model.base_url.clone();
+
+    crate::Command::new(async move |ctx| {
+        let url = {
+            let mut url = if let Some(url) = base_url {
+                url
+            } else {
+                return navigation::push(Screen::ServerAddressEntry)
+                    .into_future(ctx)
+                    .await;
+            };
+
+            url.set_path(path);
+            url
+        };
+
+        http::post(url, body).into_future(ctx.clone()).await;
+    })
+    .then(crate::Command::event(Event::UpdateData(
+        DataRequest::GetCollections,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use domain::{
+        Media, MediaContent, MediaMetaData, MediaPaths,
+        collection::{Collection, CollectionKind},
+    };
+
+    use super::CollectionSummary;
+
+    fn media(id: &str) -> Media {
+        Media {
+            id: id.to_string(),
+            library_id: "media".to_string(),
+            added_on: None,
+            artwork_url: None,
+            metadata: MediaMetaData {
+                title: id.to_string(),
+                ..Default::default()
+            },
+            content: MediaContent::Movie(MediaPaths::default()),
+        }
+    }
+
+    #[test]
+    fn test_summary_keeps_order() {
+        let collection = Collection {
+            id: "watch_later".to_string(),
+            name: "Watch later".to_string(),
+            kind: CollectionKind::List,
+            media_ids: vec!["B".to_string(), "Missing".to_string(), "A".to_string()],
+        };
+        let media_items =
+            HashMap::from([("A".to_string(), media("A")), ("B".to_string(), media("B"))]);
+
+        let summary = CollectionSummary::new(&collection, Some(&media_items));
+
+        assert_eq!(summary.media, vec![media("B"), media("A")]);
+        assert!(CollectionSummary::new(&collection, None).media.is_empty());
+    }
+}
@@ -1,6 +1,6 @@
-use std::{collections::HashMap, path::Path};
+use std::collections::HashMap;
 
-use domain::series::EpisodeIdentifier;
+use domain::series::{EpisodeIdentifier, FileMappingSuggestion};
 
 use crate::{
     Model, PartialModel,
@@ -25,44 +25,44 @@ pub fn handle_get_contents(model: &Model, id: String) -> crate::Command {
                     .await;
             };
 
-            url.set_path("download/torrent-contents");
+            url.set_path("download/file-mapping-suggestions");
             url.set_query(Some(format!("id={id}").as_ref()));
             url
         };
 
         match http::get(url).into_future(ctx.clone()).await {
             http::HttpOutput::Success { data, .. } => {
-                let files = data
-                    .and_then(|data| serde_json::from_str::<Vec<String>>(&data).ok())
-                    .map(|files| {
-                        let len = files.len();
-                        let map =
-                            files
-                                .into_iter()
-                                .fold(HashMap::with_capacity(len), |mut map, file| {
-                                    if let Some(extension) = (file.as_ref() as &Path).extension() {
-                                        // TODO make this a generic check
-                                        if extension == "srt" || extension == "nfo" {
-                                            return map;
-                                        }
-                                    }
+                let suggestions = data.and_then(|data| {
+                    serde_json::from_str::<Vec<FileMappingSuggestion>>(&data).ok()
+                });
 
-                                    let identifier = detect_episode_identifier(&file).unwrap_or(
-                                        EpisodeIdentifier {
-                                            season_no: 1,
-                                            episode_no: 1,
-                                        },
-                                    );
-                                    map.insert(file, identifier);
-                                    map
-                                });
-                        (id, map)
-                    });
+                let ambiguous_files = suggestions.as_ref().map(|suggestions| {
+                    suggestions
+                        .iter()
+                        .filter(|suggestion| !suggestion.confident)
+                        .map(|suggestion| suggestion.file.clone())
+                        .collect()
+                });
+
+                let files = suggestions.map(|suggestions| {
+                    let map = suggestions
+                        .into_iter()
+                        .map(|suggestion| {
+                            let identifier = suggestion.episode.unwrap_or(EpisodeIdentifier {
+                                season_no: 1,
+                                episode_no: 1,
+                            });
+                            (suggestion.file, identifier)
+                        })
+                        .collect::<HashMap<_, _>>();
+                    (id, map)
+                });
 
                 update_model(
                     &ctx,
                     PartialModel {
                         torrent_contents: Some(files),
+                        ambiguous_files,
                         ..Default::default()
                     },
                 );
@@ -73,38 +73,3 @@ pub fn handle_get_contents(model: &Model, id: String) -> crate::Command {
         }
     })
 }
-
-pub fn detect_episode_identifier(path: &str) -> Option<EpisodeIdentifier> {
-    let re = regex::Regex::new(r".*S([0-9]+)E([0-9]+).*").expect("Invalid regex supplied");
-    let first_capture = re.captures_iter(path).next()?;
-    let (_, [season_no_str, episode_no_str]) = first_capture.extract();
-
-    Some(EpisodeIdentifier {
-        season_no: season_no_str.parse().ok()?,
-        episode_no: episode_no_str.parse().ok()?,
-    })
-}
-
-#[cfg(test)]
-mod tests {
-    use domain::series::EpisodeIdentifier;
-
-    use super::detect_episode_identifier;
-
-    #[test]
-    fn test_detect_episode_identifier() {
-        assert_eq!(
-            detect_episode_identifier("my-series.S02E01.1080p.x265-HEYYY.mkv").unwrap(),
-            EpisodeIdentifier {
-                season_no: 2,
-                episode_no: 1
-            }
-        );
-
-        assert!(detect_episode_identifier("my-series.S02E.1080p.x265-HEYYY.mkv").is_none());
-        assert!(detect_episode_identifier("my-series.S02.1080p.x265-HEYYY.mkv").is_none());
-        assert!(detect_episode_identifier("my-series.02.1080p.x265-HEYYY.mkv").is_none());
-        assert!(detect_episode_identifier("my-series.E2S7.1080p.x265-HEYYY.mkv").is_none());
-        assert!(detect_episode_identifier("my-series.SE.1080p.x265-HEYYY.mkv").is_none());
-    }
-}
@@ -1,14 +1,76 @@
-use domain::{Download, DownloadForm};
+use std::time::Duration;
+
+use domain::{
+    Bytes, BytesPerSecond, Download, DownloadForm, DownloadQueuePositionForm, DownloadQueueSettings,
+};
 
 use crate::{
-    Model, PartialModel,
+    Event, Model, PartialModel,
     capabilities::{
         http,
         navigation::{self, Screen},
     },
-    features::utils::update_model,
+    features::{data::DataRequest, query::QueryState, utils::update_model},
 };
 
+/// A download with its numbers formatted for display.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+pub struct DownloadSummary {
+    pub download: Download,
+    /// e.g. "1.4 GB"
+    pub size: String,
+    /// e.g. "2.1 MB/s"
+    pub download_speed: String,
+    pub upload_speed: String,
+    /// e.g. "1h 4m". `None` if it can't be estimated.
+    pub eta: Option<String>,
+}
+
+impl From<Download> for DownloadSummary {
+    fn from(download: Download) -> Self {
+        Self {
+            size: format_bytes(download.size),
+            download_speed: format_speed(download.download_speed),
+            upload_speed: format_speed(download.upload_speed),
+            eta: download.eta.map(format_eta),
+            download,
+        }
+    }
+}
+
+fn format_bytes(Bytes(bytes): Bytes) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+fn format_speed(BytesPerSecond(bytes_per_second): BytesPerSecond) -> String {
+    format!("{}/s", format_bytes(Bytes(bytes_per_second)))
+}
+
+fn format_eta(eta: Duration) -> String {
+    let seconds = eta.as_secs();
+    let (days, hours, minutes) = (seconds / 86400, seconds / 3600 % 24, seconds / 60 % 60);
+
+    match (days, hours, minutes) {
+        (0, 0, 0) => format!("{seconds}s"),
+        (0, 0, minutes) => format!("{minutes}m {}s", seconds % 60),
+        (0, hours, minutes) => format!("{hours}h {minutes}m"),
+        (days, hours, _) => format!("{days}d {hours}h"),
+    }
+}
+
 pub fn handle_get_downloads(model: &Model) -> crate::Command {
     let base_url = model.base_url.clone();
 
@@ -70,3 +132,212 @@ pub fn handle_add_download(model: &Model, download_form: DownloadForm) -> crate:
             .await;
     })
 }
+
+pub fn handle_set_download_queue_position(
+    model: &Model,
+    form: DownloadQueuePositionForm,
+) -> crate::Command {
+    let base_url = model.base_url.clone();
+
+    crate::Command::new(async move |ctx| {
+        let url = {
+            let mut url = if let Some(url) = base_url {
+                url
+            } else {
+                return navigation::push(Screen::ServerAddressEntry)
+                    .into_future(ctx)
+                    .await;
+            };
+
+            url.set_path("download/set-queue-position");
+            url
+        };
+
+        // TODO: remove unwrap
+        http::post(url, serde_json::to_string(&form).unwrap())
+            .into_future(ctx.clone())
+            .await;
+    })
+    .then(crate::Command::event(Event::UpdateData(
+        DataRequest::GetDownloads,
+    )))
+}
+
+pub fn handle_get_download_queue_settings(model: &Model) -> crate::Command {
+    let base_url = model.base_url.clone();
+
+    crate::Command::new(async move |ctx| {
+        let url = {
+            let mut url = if let Some(url) = base_url {
+                url
+            } else {
+                return navigation::push(Screen::ServerAddressEntry)
+                    .into_future(ctx)
+                    .await;
+            };
+
+            url.set_path("download/queue-settings");
+            url
+        };
+
+        match http::get(url).into_future(ctx.clone()).await {
+            http::HttpOutput::Success { data, .. } => {
+                // TODO: Add logging when we can't get data or deserialize from JSON string
+                let settings: Option<DownloadQueueSettings> =
+                    data.and_then(|data| serde_json::from_str(&data).ok());
+
+                update_model(
+                    &ctx,
+                    PartialModel {
+                        download_queue_settings: Some(settings),
+                        ..Default::default()
+                    },
+                );
+            }
+            http::HttpOutput::Error => {
+                // TODO: add logging
+            }
+        }
+    })
+}
+
+pub fn handle_set_download_queue_settings(
+    model: &Model,
+    settings: DownloadQueueSettings,
+) -> crate::Command {
+    let base_url = model.base_url.clone();
+
+    crate::Command::new(async move |ctx| {
+        let url = {
+            let mut url = if let Some(url) = base_url {
+                url
+            } else {
+                return navigation::push(Screen::ServerAddressEntry)
+                    .into_future(ctx)
+                    .await;
+            };
+
+            url.set_path("download/set-queue-settings");
+            url
+        };
+
+        // TODO: remove unwrap
+        http::post(url, serde_json::to_string(&settings).unwrap())
+            .into_future(ctx.clone())
+            .await;
+    })
+    .then(
+        crate::Command::event(Event::UpdateData(DataRequest::GetDownloadQueueSettings)).and(
+            crate::Command::event(Event::UpdateData(DataRequest::GetDownloads)),
+        ),
+    )
+}
+
+pub fn handle_get_download_history(model: &Model) -> crate::Command {
+    let base_url = model.base_url.clone();
+    let last_known_history = model.download_history.get_data().cloned();
+
+    crate::Command::new(async move |ctx| {
+        let url = {
+            let mut url = if let Some(url) = base_url {
+                url
+            } else {
+                return navigation::push(Screen::ServerAddressEntry)
+                    .into_future(ctx)
+                    .await;
+            };
+
+            url.set_path("download/history");
+            url
+        };
+
+        update_model(
+            &ctx,
+            PartialModel {
+                download_history: Some(QueryState::Loading {
+                    data: last_known_history,
+                }),
+                ..Default::default()
+            },
+        );
+
+        let download_history = match http::get(url).into_future(ctx.clone()).await {
+            http::HttpOutput::Success { data, .. } => {
+                match data.and_then(|data| serde_json::from_str(&data).ok()) {
+                    Some(history) => QueryState::Success { data: history },
+                    None => QueryState::Error {
+                        message: "Couldn't read the download history".to_string(),
+                    },
+                }
+            }
+            http::HttpOutput::Error => QueryState::Error {
+                message: "Couldn't get the download history".to_string(),
+            },
+        };
+
+        update_model(
+            &ctx,
+            PartialModel {
+                download_history: Some(download_history),
+                ..Default::default()
+            },
+        );
+    })
+}
+
+pub fn handle_clear_download_history(model: &Model) -> crate::Command {
+    let base_url = model.base_url.clone();
+
+    crate::Command::new(async move |ctx| {
+        let url = {
+            let mut url = if let Some(url) = base_url {
+                url
+            } else {
+                return navigation::push(Screen::ServerAddressEntry)
+                    .into_future(ctx)
+                    .await;
+            };
+
+            url.set_path("download/history/clear");
+            url
+        };
+
+        http::post(url, String::new())
+            .into_future(ctx.clone())
+            .await;
+    })
+    .then(crate::Command::event(Event::UpdateData(
+        DataRequest::GetDownloadHistory,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use domain::{Bytes, BytesPerSecond};
+
+    use super::{format_bytes, format_eta, format_speed};
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(Bytes(0)), "0 B");
+        assert_eq!(format_bytes(Bytes(999)), "999 B");
+        assert_eq!(format_bytes(Bytes(1500)), "1.5 KB");
+        assert_eq!(format_bytes(Bytes(1_400_000_000)), "1.4 GB");
+        assert_eq!(format_bytes(Bytes(3_000_000_000_000_000)), "3000.0 TB");
+    }
+
+    #[test]
+    fn test_format_speed() {
+        assert_eq!(format_speed(BytesPerSecond(2_100_000)), "2.1 MB/s");
+    }
+
+    #[test]
+    fn test_format_eta() {
+        assert_eq!(format_eta(Duration::from_secs(42)), "42s");
+        assert_eq!(format_eta(Duration::from_secs(125)), "2m 5s");
+        assert_eq!(format_eta(Duration::from_secs(3840)), "1h 4m");
+        assert_eq!(format_eta(Duration::from_secs(90000)), "1d 1h");
+    }
+}
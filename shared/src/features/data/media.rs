@@ -1,23 +1,28 @@
 use std::collections::HashMap;
 
-use domain::Media;
+use domain::{
+    DeleteMediaForm, EditEpisodeMetaDataForm, EditMediaMetaDataForm, Media, RenameMediaForm,
+    library::Library,
+    search::{MediaQuery, MediaSearchResults, RecentlyAdded, RecentlyAddedQuery},
+};
 
 use crate::{
-    Model, PartialModel,
+    Event, Model, PartialModel,
     capabilities::{
         http,
         navigation::{self, Screen},
     },
-    features::{query::QueryState, utils::update_model},
+    features::{data::DataRequest, query::QueryState, utils::update_model},
 };
 
 pub fn handle_get_media(model: &Model) -> crate::Command {
     let base_url = model.base_url.clone();
     let last_known_movies = model.media_items.get_data().cloned();
+    let selected_library = model.selected_library.clone();
 
     crate::Command::new(|ctx| async move {
-        let url = {
-            let mut url = if let Some(url) = base_url {
+        let (url, base_url) = {
+            let base_url = if let Some(url) = base_url {
                 url
             } else {
                 return navigation::push(Screen::ServerAddressEntry)
@@ -25,8 +30,12 @@ pub fn handle_get_media(model: &Model) -> crate::Command {
                     .await;
             };
 
+            let mut url = base_url.clone();
             url.set_path("get_movies");
-            url
+            if let Some(library_id) = selected_library {
+                url.query_pairs_mut().append_pair("library", &library_id);
+            }
+            (url, base_url)
         };
 
         update_model(
@@ -46,7 +55,10 @@ pub fn handle_get_media(model: &Model) -> crate::Command {
                     .map(|movies| {
                         let mut movies_hashmap = HashMap::with_capacity(movies.len());
 
-                        movies.into_iter().for_each(|media| {
+                        movies.into_iter().for_each(|mut media| {
+                            media.artwork_url = media
+                                .artwork_url
+                                .map(|artwork_url| resolve_artwork_url(&base_url, artwork_url));
                             movies_hashmap.insert(media.id.clone(), media);
                         });
 
@@ -68,3 +80,281 @@ pub fn handle_get_media(model: &Model) -> crate::Command {
         }
     })
 }
+
+/// Results of a query with an offset are appended to the ones that are already loaded
+pub fn handle_search(model: &Model, query: MediaQuery) -> crate::Command {
+    let base_url = model.base_url.clone();
+    let previous_results = model
+        .search_results
+        .get_data()
+        .cloned()
+        .filter(|_| query.offset > 0);
+
+    crate::Command::new(|ctx| async move {
+        let (url, base_url) = {
+            let base_url = if let Some(url) = base_url {
+                url
+            } else {
+                return navigation::push(Screen::ServerAddressEntry)
+                    .into_future(ctx)
+                    .await;
+            };
+
+            let mut url = base_url.clone();
+            url.set_path("media/search");
+            // TODO: remove unwrap
+            url.set_query(Some(&serde_urlencoded::to_string(&query).unwrap()));
+            (url, base_url)
+        };
+
+        update_model(
+            &ctx,
+            PartialModel {
+                search_results: Some(QueryState::Loading {
+                    data: previous_results.clone(),
+                }),
+                ..Default::default()
+            },
+        );
+
+        let results = match http::get(url).into_future(ctx.clone()).await {
+            http::HttpOutput::Success { data, .. } => {
+                data.and_then(|data| serde_json::from_str::<MediaSearchResults>(&data).ok())
+            }
+            http::HttpOutput::Error => None,
+        };
+
+        let search_results = match results {
+            Some(mut results) => {
+                for media in &mut results.items {
+                    media.artwork_url = media
+                        .artwork_url
+                        .take()
+                        .map(|artwork_url| resolve_artwork_url(&base_url, artwork_url));
+                }
+
+                if let Some(mut previous_results) = previous_results {
+                    previous_results.items.append(&mut results.items);
+                    results.items = previous_results.items;
+                    results.offset = previous_results.offset;
+                }
+
+                QueryState::Success { data: results }
+            }
+            None => QueryState::Error {
+                message: "Couldn't search the library".to_string(),
+            },
+        };
+
+        update_model(
+            &ctx,
+            PartialModel {
+                search_results: Some(search_results),
+                ..Default::default()
+            },
+        );
+    })
+}
+
+/// How many items the home screen shows
+const RECENTLY_ADDED_LIMIT: u32 = 20;
+
+pub fn handle_get_recently_added(model: &Model) -> crate::Command {
+    let base_url = model.base_url.clone();
+    let last_known_items = model.recently_added.get_data().cloned();
+    let query = RecentlyAddedQuery {
+        library: model.selected_library.clone(),
+        limit: Some(RECENTLY_ADDED_LIMIT),
+        ..Default::default()
+    };
+
+    crate::Command::new(|ctx| async move {
+        let (url, base_url) = {
+            let base_url = if let Some(url) = base_url {
+                url
+            } else {
+                return navigation::push(Screen::ServerAddressEntry)
+                    .into_future(ctx)
+                    .await;
+            };
+
+            let mut url = base_url.clone();
+            url.set_path("media/recent");
+            // TODO: remove unwrap
+            url.set_query(Some(&serde_urlencoded::to_string(&query).unwrap()));
+            (url, base_url)
+        };
+
+        update_model(
+            &ctx,
+            PartialModel {
+                recently_added: Some(QueryState::Loading {
+                    data: last_known_items,
+                }),
+                ..Default::default()
+            },
+        );
+
+        let recently_added = match http::get(url).into_future(ctx.clone()).await {
+            http::HttpOutput::Success { data, .. } => {
+                data.and_then(|data| serde_json::from_str::<Vec<RecentlyAdded>>(&data).ok())
+            }
+            http::HttpOutput::Error => None,
+        };
+
+        let recently_added = match recently_added {
+            Some(mut items) => {
+                for item in &mut items {
+                    item.media.artwork_url = item
+                        .media
+                        .artwork_url
+                        .take()
+                        .map(|artwork_url| resolve_artwork_url(&base_url, artwork_url));
+                }
+
+                QueryState::Success { data: items }
+            }
+            None => QueryState::Error {
+                message: "Couldn't get recently added media".to_string(),
+            },
+        };
+
+        update_model(
+            &ctx,
+            PartialModel {
+                recently_added: Some(recently_added),
+                ..Default::default()
+            },
+        );
+    })
+}
+
+pub fn handle_get_libraries(model: &Model) -> crate::Command {
+    let base_url = model.base_url.clone();
+
+    crate::Command::new(async move |ctx| {
+        let url = {
+            let mut url = if let Some(url) = base_url {
+                url
+            } else {
+                return navigation::push(Screen::ServerAddressEntry)
+                    .into_future(ctx)
+                    .await;
+            };
+
+            url.set_path("libraries");
+            url
+        };
+
+        match http::get(url).into_future(ctx.clone()).await {
+            http::HttpOutput::Success { data, .. } => {
+                // TODO: Add logging when we can't get data or deserialize from JSON string
+                let libraries: Vec<Library> = data
+                    .and_then(|data| serde_json::from_str(&data).ok())
+                    .unwrap_or_default();
+
+                update_model(
+                    &ctx,
+                    PartialModel {
+                        libraries: Some(libraries),
+                        ..Default::default()
+                    },
+                );
+            }
+            http::HttpOutput::Error => {
+                // TODO: add logging
+            }
+        }
+    })
+}
+
+pub fn handle_select_library(model: &mut Model, library_id: Option<String>) -> crate::Command {
+    model.selected_library = library_id;
+
+    crate::Command::event(Event::UpdateData(DataRequest::GetMedia)).and(crate::Command::event(
+        Event::UpdateData(DataRequest::GetRecentlyAdded),
+    ))
+}
+
+pub fn handle_edit_media_metadata(model: &Model, form: EditMediaMetaDataForm) -> crate::Command {
+    // TODO: remove unwrap
+    post_and_refresh(
+        model,
+        "media/edit-metadata",
+        serde_json::to_string(&form).unwrap(),
+    )
+}
+
+pub fn handle_edit_episode_metadata(
+    model: &Model,
+    form: EditEpisodeMetaDataForm,
+) -> crate::Command {
+    // TODO: remove unwrap
+    post_and_refresh(
+        model,
+        "media/edit-episode-metadata",
+        serde_json::to_string(&form).unwrap(),
+    )
+}
+
+pub fn handle_delete_media(model: &Model, form: DeleteMediaForm) -> crate::Command {
+    // TODO: remove unwrap
+    post_and_refresh(model, "media/delete", serde_json::to_string(&form).unwrap())
+}
+
+pub fn handle_rename_media(model: &Model, form: RenameMediaForm) -> crate::Command {
+    // TODO: remove unwrap
+    post_and_refresh(model, "media/rename", serde_json::to_string(&form).unwrap())
+}
+
+/// The server replies after the library is updated, so media is fetched again right after
+fn post_and_refresh(model: &Model, path: &'static str, body: String) -> crate::Command {
+    let base_url = model.base_url.clone();
+
+    crate::Command::new(async move |ctx| {
+        let url = {
+            let mut url = if let Some(url) = base_url {
+                url
+            } else {
+                return navigation::push(Screen::ServerAddressEntry)
+                    .into_future(ctx)
+                    .await;
+            };
+
+            url.set_path(path);
+            url
+        };
+
+        http::post(url, body).into_future(ctx.clone()).await;
+    })
+    .then(crate::Command::event(Event::UpdateData(
+        DataRequest::GetMedia,
+    )))
+}
+
+/// The server sends the path it serves artwork at, shells need the full URL.
+fn resolve_artwork_url(base_url: &url::Url, artwork_url: String) -> String {
+    base_url
+        .join(&artwork_url)
+        .map(String::from)
+        .unwrap_or(artwork_url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve_artwork_url;
+
+    #[test]
+    fn test_resolve_artwork_url() {
+        let base_url = url::Url::parse("http://192.168.1.2:3000").unwrap();
+
+        assert_eq!(
+            resolve_artwork_url(&base_url, "/artwork/My_Movie/poster".to_string()),
+            "http://192.168.1.2:3000/artwork/My_Movie/poster"
+        );
+        assert_eq!(
+            resolve_artwork_url(&base_url, "https://image.com/poster.jpg".to_string()),
+            "https://image.com/poster.jpg"
+        );
+    }
+}
@@ -1,24 +1,64 @@
+mod collections;
 mod contents;
 mod downloads;
 mod media;
 mod series;
 
 use domain::{
-    DownloadForm,
+    DeleteMediaForm, DownloadForm, DownloadQueuePositionForm, DownloadQueueSettings,
+    EditEpisodeMetaDataForm, EditMediaMetaDataForm, RenameMediaForm,
+    collection::{
+        AddToCollectionForm, CreateCollectionForm, DeleteCollectionForm, MoveCollectionForm,
+        RemoveFromCollectionForm, UpdateCollectionForm,
+    },
     series::{EditSeriesFileMappingForm, file_mapping_form_state},
 };
 
 use crate::Model;
 
+pub use collections::CollectionSummary;
+pub use downloads::DownloadSummary;
+pub use media::handle_search;
+
+use collections::{handle_get_collections, post_and_refresh};
+
 use contents::handle_get_contents;
-use downloads::{handle_add_download, handle_get_downloads};
-use media::handle_get_media;
+use downloads::{
+    handle_add_download, handle_clear_download_history, handle_get_download_history,
+    handle_get_download_queue_settings, handle_get_downloads, handle_set_download_queue_position,
+    handle_set_download_queue_settings,
+};
+use media::{
+    handle_delete_media, handle_edit_episode_metadata, handle_edit_media_metadata,
+    handle_get_libraries, handle_get_media, handle_get_recently_added, handle_rename_media,
+    handle_select_library,
+};
 
 #[derive(serde::Serialize, serde::Deserialize, PartialEq, Eq, Clone, Debug)]
 pub enum DataRequest {
     GetMedia,
+    GetLibraries,
+    GetRecentlyAdded,
+    /// Only lists media of the library with this id, or every library if `None`
+    SelectLibrary(Option<String>),
+    EditMediaMetaData(EditMediaMetaDataForm),
+    EditEpisodeMetaData(EditEpisodeMetaDataForm),
+    DeleteMedia(DeleteMediaForm),
+    RenameMedia(RenameMediaForm),
+    GetCollections,
+    CreateCollection(CreateCollectionForm),
+    UpdateCollection(UpdateCollectionForm),
+    DeleteCollection(DeleteCollectionForm),
+    AddToCollection(AddToCollectionForm),
+    RemoveFromCollection(RemoveFromCollectionForm),
+    MoveCollection(MoveCollectionForm),
     GetDownloads,
     AddDownload(DownloadForm),
+    SetDownloadQueuePosition(DownloadQueuePositionForm),
+    GetDownloadQueueSettings,
+    SetDownloadQueueSettings(DownloadQueueSettings),
+    GetDownloadHistory,
+    ClearDownloadHistory,
     GetContents(String),
     SetSeriesFileMapping(EditSeriesFileMappingForm<file_mapping_form_state::NeedsValidation>),
 }
@@ -28,7 +68,55 @@ pub fn update_data(model: &mut Model, request: DataRequest) -> crate::Command {
         DataRequest::SetSeriesFileMapping(form) => series::handle_file_mapping(model, form),
         DataRequest::GetContents(id) => handle_get_contents(model, id),
         DataRequest::GetMedia => handle_get_media(model),
+        DataRequest::GetLibraries => handle_get_libraries(model),
+        DataRequest::GetRecentlyAdded => handle_get_recently_added(model),
+        DataRequest::SelectLibrary(library_id) => handle_select_library(model, library_id),
+        DataRequest::EditMediaMetaData(form) => handle_edit_media_metadata(model, form),
+        DataRequest::EditEpisodeMetaData(form) => handle_edit_episode_metadata(model, form),
+        DataRequest::DeleteMedia(form) => handle_delete_media(model, form),
+        DataRequest::RenameMedia(form) => handle_rename_media(model, form),
+        DataRequest::GetCollections => handle_get_collections(model),
+        // TODO: remove unwraps
+        DataRequest::CreateCollection(form) => post_and_refresh(
+            model,
+            "collections/create",
+            serde_json::to_string(&form).unwrap(),
+        ),
+        DataRequest::UpdateCollection(form) => post_and_refresh(
+            model,
+            "collections/update",
+            serde_json::to_string(&form).unwrap(),
+        ),
+        DataRequest::DeleteCollection(form) => post_and_refresh(
+            model,
+            "collections/delete",
+            serde_json::to_string(&form).unwrap(),
+        ),
+        DataRequest::AddToCollection(form) => post_and_refresh(
+            model,
+            "collections/add-media",
+            serde_json::to_string(&form).unwrap(),
+        ),
+        DataRequest::RemoveFromCollection(form) => post_and_refresh(
+            model,
+            "collections/remove-media",
+            serde_json::to_string(&form).unwrap(),
+        ),
+        DataRequest::MoveCollection(form) => post_and_refresh(
+            model,
+            "collections/move",
+            serde_json::to_string(&form).unwrap(),
+        ),
         DataRequest::GetDownloads => handle_get_downloads(model),
         DataRequest::AddDownload(download_form) => handle_add_download(model, download_form),
+        DataRequest::SetDownloadQueuePosition(form) => {
+            handle_set_download_queue_position(model, form)
+        }
+        DataRequest::GetDownloadQueueSettings => handle_get_download_queue_settings(model),
+        DataRequest::SetDownloadQueueSettings(settings) => {
+            handle_set_download_queue_settings(model, settings)
+        }
+        DataRequest::GetDownloadHistory => handle_get_download_history(model),
+        DataRequest::ClearDownloadHistory => handle_clear_download_history(model),
     }
 }
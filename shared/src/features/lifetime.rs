@@ -1,5 +1,5 @@
 use crux_core::{Command, render::render};
-use domain::Media;
+use domain::{Media, subtitles::Retiming};
 use url::Url;
 
 use crate::{
@@ -47,7 +47,14 @@ pub fn handle_screen_change(model: &mut Model, screen: Screen) -> crate::Command
     model.current_screen = screen.clone();
 
     let command = match screen {
-        Screen::List => Command::event(Event::UpdateData(DataRequest::GetMedia)),
+        Screen::List => Command::event(Event::UpdateData(DataRequest::GetMedia))
+            .and(Command::event(Event::UpdateData(DataRequest::GetLibraries)))
+            .and(Command::event(Event::UpdateData(
+                DataRequest::GetRecentlyAdded,
+            )))
+            .and(Command::event(Event::UpdateData(
+                DataRequest::GetCollections,
+            ))),
         Screen::Detail(Media { id, .. }) => Command::new(|ctx| async move {
             let (initial_seconds, episode) = PlayEvent::FromSavedPosition { id: id.clone() }
                 .get_position(ctx.clone())
@@ -80,6 +87,9 @@ pub fn handle_screen_change(model: &mut Model, screen: Screen) -> crate::Command
         Screen::MediaManagerDetail(_) => Command::done(),
         Screen::MediaManagerSeason { .. } => Command::done(),
         Screen::AddDownload => Command::done(),
+        Screen::DownloadHistory => {
+            Command::event(Event::UpdateData(DataRequest::GetDownloadHistory))
+        }
         Screen::Startup => Command::done(),
         Screen::ServerAddressEntry => {
             Command::new(|ctx| async move {
@@ -98,6 +108,11 @@ pub fn handle_screen_change(model: &mut Model, screen: Screen) -> crate::Command
             model.subtitle_download_results = None;
             Command::done()
         }
+        Screen::SubtitleSync { .. } => {
+            model.subtitle_retiming = Retiming::default();
+            model.subtitle_sync_result = None;
+            Command::done()
+        }
     };
 
     render().and(command)
@@ -158,9 +158,9 @@ pub fn handle_play(model: &mut Model, play_event: PlayEvent) -> crate::Command {
                 let media_paths = season.get(&defaulted_episode_id.episode_no).unwrap();
                 let title = format!(
                     "{} S{} E{}",
-                    &media_item.metadata.title,
-                    &defaulted_episode_id.season_no,
-                    &defaulted_episode_id.episode_no
+                    media_item.metadata.title,
+                    defaulted_episode_id.season_no,
+                    defaulted_episode_id.episode_no
                 );
                 let playback_data = PlaybackPosition::SeriesEpisode {
                     id: id.clone(),
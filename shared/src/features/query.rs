@@ -85,7 +85,12 @@ macro_rules! query_state_type {
 pub mod view_model_queries {
     use std::collections::HashMap;
 
-    use domain::{Media, language::LanguageCode, series::EpisodeIdentifier};
+    use domain::{
+        DownloadHistoryEntry, Media,
+        language::LanguageCode,
+        search::{MediaSearchResults, RecentlyAdded},
+        series::EpisodeIdentifier,
+    };
 
     use crate::features::query::QueryState;
 
@@ -93,7 +98,10 @@ pub mod view_model_queries {
 
     query_state_type!(ActionState, ());
     query_state_type!(MediaItems, MediaItemsContent);
+    query_state_type!(SearchResults, MediaSearchResults);
+    query_state_type!(RecentlyAddedItems, Vec<RecentlyAdded>);
     query_state_type!(SubtitleSearchState, SubtitleSearchResults);
+    query_state_type!(DownloadHistory, Vec<DownloadHistoryEntry>);
 
     #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
     pub enum SubtitleSearchResults {
@@ -115,6 +123,8 @@ pub mod view_model_queries {
         pub id: usize,
         pub title: String,
         pub download_count: usize,
+        pub hash_match: bool,
+        pub metadata: domain::subtitles::SubtitleMetadata,
     }
 
     impl From<domain::subtitles::SubtitleDownloadOption<usize>> for SubtitleSearchResult {
@@ -123,6 +133,8 @@ pub mod view_model_queries {
                 id: value.id,
                 title: value.title,
                 download_count: value.download_count,
+                hash_match: value.hash_match,
+                metadata: value.metadata,
             }
         }
     }
@@ -1,11 +1,14 @@
 use std::collections::HashMap;
 
-use crux_core::Command;
+use crux_core::{Command, render::render};
 use domain::{
     SeriesContents,
     language::LanguageCode,
     series::EpisodeIdentifier,
-    subtitles::{SubtitleDownloadForm, SubtitleSearchForm, SubtitleSearchResponse},
+    subtitles::{
+        Retiming, Subtitle, SubtitleAutoSyncForm, SubtitleDownloadForm, SubtitleSearchFilters,
+        SubtitleSearchForm, SubtitleSearchResponse, SubtitleSyncForm,
+    },
 };
 
 use crate::{
@@ -32,7 +35,7 @@ pub enum SubtitleEvent {
     },
     /// Navigate to the subtitle search configuration screen
     Search {
-        media: domain::Media,
+        media: Box<domain::Media>,
         language: LanguageCode,
         /// `None` for movies, `Some` for series episodes
         episodes: Option<Vec<EpisodeIdentifier>>,
@@ -43,13 +46,35 @@ pub enum SubtitleEvent {
         language: LanguageCode,
         /// `None` for movies, `Some` for series episodes
         episodes: Option<Vec<EpisodeIdentifier>>,
+        filters: SubtitleSearchFilters,
     },
     Download {
         form: SubtitleDownloadForm,
     },
+    /// Navigate to the sync screen of a stored subtitle
+    Sync {
+        media: Box<domain::Media>,
+        subtitle: Subtitle,
+    },
+    SetRetiming(Retiming),
+    /// Shifts the offset of the current retiming, e.g. with +/- buttons
+    NudgeOffset {
+        millis: i64,
+    },
+    /// Saves a copy of the subtitle with the current retiming
+    SaveSync {
+        media_id: String,
+        subtitle_path: String,
+    },
+    /// Queues the subtitle to be synced against the audio by the server, the synced copy shows up
+    /// once the job is done
+    AutoSync {
+        media_id: String,
+        subtitle_path: String,
+    },
 }
 
-pub fn handle_subtitle_event(model: &Model, event: SubtitleEvent) -> crate::Command {
+pub fn handle_subtitle_event(model: &mut Model, event: SubtitleEvent) -> crate::Command {
     match event {
         SubtitleEvent::Select { media_id, season } => {
             let Some(media) = model
@@ -92,7 +117,7 @@ pub fn handle_subtitle_event(model: &Model, event: SubtitleEvent) -> crate::Comm
             episodes,
         } => Command::new(|ctx| async move {
             navigation::push(Screen::SubtitleSearchResult {
-                media,
+                media: *media,
                 language,
                 episodes,
             })
@@ -104,12 +129,55 @@ pub fn handle_subtitle_event(model: &Model, event: SubtitleEvent) -> crate::Comm
             media_id,
             language,
             episodes,
-        } => fetch_subtitle_results(model, media_id, language, episodes),
+            filters,
+        } => fetch_subtitle_results(model, media_id, language, episodes, filters),
 
         SubtitleEvent::Download { form } => download_subtitles(model, form).then(
             Command::new(async |ctx| navigation::pop(2).into_future(ctx).await)
                 .and(Command::event(Event::UpdateData(DataRequest::GetMedia))),
         ),
+
+        SubtitleEvent::Sync { media, subtitle } => Command::new(|ctx| async move {
+            navigation::push(Screen::SubtitleSync {
+                media: *media,
+                subtitle,
+            })
+            .into_future(ctx)
+            .await
+        }),
+
+        SubtitleEvent::SetRetiming(retiming) => {
+            model.subtitle_retiming = retiming;
+            render()
+        }
+
+        SubtitleEvent::NudgeOffset { millis } => {
+            model.subtitle_retiming.offset_millis += millis;
+            render()
+        }
+
+        SubtitleEvent::SaveSync {
+            media_id,
+            subtitle_path,
+        } => sync_subtitle(
+            model,
+            SubtitleSyncForm {
+                media_id,
+                subtitle_path,
+                retiming: model.subtitle_retiming,
+            },
+        ),
+
+        SubtitleEvent::AutoSync {
+            media_id,
+            subtitle_path,
+        } => auto_sync_subtitle(
+            model,
+            SubtitleAutoSyncForm {
+                media_id,
+                subtitle_path,
+            },
+        ),
     }
 }
 
@@ -140,6 +208,7 @@ fn fetch_subtitle_results(
     media_id: String,
     language: LanguageCode,
     episodes: Option<Vec<EpisodeIdentifier>>,
+    filters: SubtitleSearchFilters,
 ) -> crate::Command {
     let subtitles_search_endpoint = {
         let mut url = model
@@ -155,6 +224,7 @@ fn fetch_subtitle_results(
         media_id: media_id.clone(),
         language_code: language.clone(),
         episode_identifiers: episodes.clone(),
+        filters,
     };
 
     Command::new(|ctx| async move {
@@ -280,6 +350,104 @@ fn download_subtitles(model: &Model, form: SubtitleDownloadForm) -> crate::Comma
     })
 }
 
+fn sync_subtitle(model: &Model, form: SubtitleSyncForm) -> crate::Command {
+    let subtitles_sync_endpoint = {
+        let mut url = model
+            .base_url
+            .clone()
+            .expect("Base url to be defined at this stage");
+        url.set_path("subtitles/sync");
+        url
+    };
+
+    Command::new(|ctx| async move {
+        update_model(
+            &ctx,
+            PartialModel {
+                subtitle_sync_result: Some(Some(QueryState::Loading { data: None })),
+                ..Default::default()
+            },
+        );
+
+        let result = http::post(
+            subtitles_sync_endpoint,
+            serde_json::to_string(&form).expect("Form to be serializable"),
+        )
+        .into_future(ctx.clone())
+        .await;
+
+        let sync_result = match result {
+            http::HttpOutput::Success { .. } => QueryState::Success { data: () },
+            http::HttpOutput::Error => QueryState::Error {
+                message: "Couldn't sync subtitle".to_string(),
+            },
+        };
+        let is_success = sync_result.is_success();
+
+        update_model(
+            &ctx,
+            PartialModel {
+                subtitle_sync_result: Some(Some(sync_result)),
+                ..Default::default()
+            },
+        );
+
+        // The screen stays open on errors so the correction can be retried
+        if is_success {
+            navigation::pop(1).into_future(ctx.clone()).await;
+            ctx.send_event(Event::UpdateData(DataRequest::GetMedia));
+        }
+    })
+}
+
+fn auto_sync_subtitle(model: &Model, form: SubtitleAutoSyncForm) -> crate::Command {
+    let subtitles_auto_sync_endpoint = {
+        let mut url = model
+            .base_url
+            .clone()
+            .expect("Base url to be defined at this stage");
+        url.set_path("subtitles/auto-sync");
+        url
+    };
+
+    Command::new(|ctx| async move {
+        update_model(
+            &ctx,
+            PartialModel {
+                subtitle_sync_result: Some(Some(QueryState::Loading { data: None })),
+                ..Default::default()
+            },
+        );
+
+        let result = http::post(
+            subtitles_auto_sync_endpoint,
+            serde_json::to_string(&form).expect("Form to be serializable"),
+        )
+        .into_future(ctx.clone())
+        .await;
+
+        let sync_result = match result {
+            http::HttpOutput::Success { .. } => QueryState::Success { data: () },
+            http::HttpOutput::Error => QueryState::Error {
+                message: "Couldn't queue subtitle to be synced".to_string(),
+            },
+        };
+        let is_success = sync_result.is_success();
+
+        update_model(
+            &ctx,
+            PartialModel {
+                subtitle_sync_result: Some(Some(sync_result)),
+                ..Default::default()
+            },
+        );
+
+        if is_success {
+            navigation::pop(1).into_future(ctx).await;
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
@@ -294,11 +462,8 @@ mod tests {
         MediaPaths {
             media: String::new(),
             track_name: String::new(),
-            subtitles: Box::new([Subtitle {
-                language,
-                path: String::new(),
-                track_path: String::new(),
-            }]),
+            subtitles: vec![Subtitle::new(String::new(), language, String::new())],
+            ..Default::default()
         }
     }
 
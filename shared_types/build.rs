@@ -4,10 +4,11 @@ use shared::{
     CounterApp,
     capabilities::navigation::Screen,
     features::{
-        data::DataRequest,
+        data::{DataRequest, DownloadSummary},
         playback::{PlayEvent, PlaybackPosition},
         query::view_model_queries::{
-            ActionState, MediaItems, SubtitleSearchResults, SubtitleSearchState,
+            ActionState, DownloadHistory, MediaItems, RecentlyAddedItems, SearchResults,
+            SubtitleSearchResults, SubtitleSearchState,
         },
         server_communication::ServerCommunicationEvent,
         subtitle::SubtitleEvent,
@@ -23,23 +24,71 @@ fn main() -> anyhow::Result<()> {
     typegen.register_type::<Screen>()?;
     typegen.register_type::<ActionState>()?;
     typegen.register_type::<MediaItems>()?;
+    typegen.register_type::<SearchResults>()?;
+    typegen.register_type::<RecentlyAddedItems>()?;
+    typegen.register_type::<DownloadHistory>()?;
     typegen.register_type::<PlayEvent>()?;
     typegen.register_type::<SubtitleSearchState>()?;
     typegen.register_type::<SubtitleSearchResults>()?;
     typegen.register_type::<SubtitleSelection>()?;
     typegen.register_type::<ServerCommunicationEvent>()?;
     typegen.register_type::<DataRequest>()?;
+    typegen.register_type::<DownloadSummary>()?;
     typegen.register_type::<PlaybackPosition>()?;
     typegen.register_type::<SubtitleEvent>()?;
 
     // Domain
     typegen.register_type::<domain::Media>()?;
     typegen.register_type::<domain::MediaMetaData>()?;
+    typegen.register_type::<domain::ExternalIds>()?;
+    typegen.register_type::<domain::EpisodeMetaData>()?;
+    typegen.register_type::<domain::EditMediaMetaDataForm>()?;
+    typegen.register_type::<domain::EditEpisodeMetaDataForm>()?;
+    typegen.register_type::<domain::DeleteMediaForm>()?;
+    typegen.register_type::<domain::RenameMediaForm>()?;
+    typegen.register_type::<domain::collection::CollectionKind>()?;
+    typegen.register_type::<domain::collection::Collection>()?;
+    typegen.register_type::<domain::collection::CreateCollectionForm>()?;
+    typegen.register_type::<domain::collection::UpdateCollectionForm>()?;
+    typegen.register_type::<domain::collection::DeleteCollectionForm>()?;
+    typegen.register_type::<domain::collection::AddToCollectionForm>()?;
+    typegen.register_type::<domain::collection::RemoveFromCollectionForm>()?;
+    typegen.register_type::<domain::collection::MoveCollectionForm>()?;
+    typegen.register_type::<domain::library::LibraryKind>()?;
+    typegen.register_type::<domain::metadata::MediaKind>()?;
+    typegen.register_type::<domain::search::MediaSort>()?;
+    typegen.register_type::<domain::search::SortOrder>()?;
+    typegen.register_type::<domain::search::MediaQuery>()?;
+    typegen.register_type::<domain::search::MediaSearchResults>()?;
+    typegen.register_type::<domain::search::RecentlyAddedQuery>()?;
+    typegen.register_type::<domain::search::RecentlyAdded>()?;
+    typegen.register_type::<domain::library::Library>()?;
+    typegen.register_type::<domain::import::TransferMode>()?;
+    typegen.register_type::<domain::import::ImportForm>()?;
+    typegen.register_type::<domain::import::ImportOutcome>()?;
+    typegen.register_type::<domain::import::ImportedItem>()?;
     typegen.register_type::<domain::MediaContent>()?;
     typegen.register_type::<domain::Download>()?;
     typegen.register_type::<domain::DownloadState>()?;
+    typegen.register_type::<domain::Bytes>()?;
+    typegen.register_type::<domain::BytesPerSecond>()?;
+    typegen.register_type::<domain::DownloadHistoryEntry>()?;
+    typegen.register_type::<domain::DownloadOutcome>()?;
+    typegen.register_type::<domain::DownloadFailureReason>()?;
+    typegen.register_type::<domain::DownloadQueuePositionForm>()?;
+    typegen.register_type::<domain::QueuePositionChange>()?;
+    typegen.register_type::<domain::DownloadQueueSettings>()?;
     typegen.register_type::<domain::language::LanguageCode>()?;
     typegen.register_type::<domain::series::EpisodeIdentifier>()?;
+    typegen.register_type::<domain::subtitles::Subtitle>()?;
+    typegen.register_type::<domain::subtitles::Retiming>()?;
+    typegen.register_type::<domain::subtitles::FramerateCorrection>()?;
+    typegen.register_type::<domain::subtitles::Framerate>()?;
+    typegen.register_type::<domain::subtitles::SubtitleSyncForm>()?;
+    typegen.register_type::<domain::subtitles::SubtitleAutoSyncForm>()?;
+    typegen.register_type::<domain::subtitles::SubtitleMetadata>()?;
+    typegen.register_type::<domain::subtitles::SubtitleSearchFilters>()?;
+    typegen.register_type::<domain::subtitles::HearingImpairedFilter>()?;
 
     let output_root = PathBuf::from("./generated");
     typegen.swift("SharedTypes", output_root.join("swift"))?;
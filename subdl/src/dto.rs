@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct SearchResponse {
+    pub status: bool,
+    #[serde(default)]
+    pub subtitles: Box<[Subtitle]>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Subtitle {
+    pub release_name: String,
+    pub name: String,
+    /// Download path of a ZIP archive, like `/subtitle/3197651-3213944.zip`
+    pub url: String,
+    pub language: String,
+    pub season: Option<u32>,
+    pub episode: Option<u32>,
+    #[serde(default)]
+    pub full_season: bool,
+    /// Hearing impaired
+    #[serde(default)]
+    pub hi: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubdlError {
+    pub status: bool,
+    pub error: Option<String>,
+}
+
+impl std::fmt::Display for SubdlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#?}", self)
+    }
+}
+
+impl std::error::Error for SubdlError {}
@@ -0,0 +1,297 @@
+mod dto;
+
+use std::{
+    io::{Cursor, Read},
+    path::Path,
+    sync::LazyLock,
+};
+
+use domain::{
+    language::LanguageCode,
+    series::EpisodeIdentifier,
+    subtitles::{SubtitleDownloadOption, SubtitleMetadata, SubtitleProvider},
+};
+
+use dto::{SearchResponse, SubdlError};
+
+static SUBDL_BASE_URL: LazyLock<reqwest::Url> = LazyLock::new(|| {
+    reqwest::Url::parse("https://api.subdl.com/api/v1/").expect("SubDL base url should be valid")
+});
+static SUBDL_DOWNLOAD_BASE_URL: LazyLock<reqwest::Url> = LazyLock::new(|| {
+    reqwest::Url::parse("https://dl.subdl.com/").expect("SubDL download url should be valid")
+});
+/// Most the API returns in one page
+const SUBTITLES_PER_PAGE: &str = "30";
+
+#[derive(Debug, Clone)]
+pub struct SubdlClient {
+    http_client: reqwest::Client,
+    api_key: String,
+    base_url: reqwest::Url,
+    download_base_url: reqwest::Url,
+}
+
+impl SubdlClient {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            http_client: reqwest::Client::new(),
+            api_key: api_key.into(),
+            base_url: SUBDL_BASE_URL.clone(),
+            download_base_url: SUBDL_DOWNLOAD_BASE_URL.clone(),
+        }
+    }
+
+    pub fn with_base_urls(
+        mut self,
+        base_url: reqwest::Url,
+        download_base_url: reqwest::Url,
+    ) -> Self {
+        self.base_url = base_url;
+        self.download_base_url = download_base_url;
+        self
+    }
+
+    fn check_api_error(api_response_str: &str) -> Result<()> {
+        if let Ok(error) = serde_json::from_str::<SubdlError>(api_response_str)
+            && !error.status
+        {
+            return Err(error.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Ids are the archive name in the download URL, e.g. `1984093-2048876`
+fn into_download_options(
+    response: SearchResponse,
+    language: LanguageCode,
+    episode: Option<EpisodeIdentifier>,
+) -> impl Iterator<Item = SubtitleDownloadOption<String>> {
+    response
+        .subtitles
+        .into_iter()
+        // Season packs hold every episode in one archive
+        .filter(move |subtitle| episode.is_none() || !subtitle.full_season)
+        .filter_map(move |subtitle| {
+            let id = subtitle
+                .url
+                .rsplit('/')
+                .next()?
+                .strip_suffix(".zip")?
+                .to_string();
+
+            Some(SubtitleDownloadOption {
+                id,
+                title: subtitle.release_name.clone(),
+                // Not shared by the API
+                download_count: 0,
+                language: language.clone(),
+                hash_match: false,
+                metadata: SubtitleMetadata {
+                    release: Some(subtitle.release_name),
+                    hearing_impaired: subtitle.hi,
+                    ..Default::default()
+                },
+            })
+        })
+}
+
+/// The first SRT file in a downloaded archive
+fn extract_subtitle(archive_bytes: &[u8]) -> Result<String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(archive_bytes))?;
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        if !file.name().to_lowercase().ends_with(".srt") {
+            continue;
+        }
+
+        let mut subtitle_bytes = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut subtitle_bytes)
+            .map_err(|inner| Error::ArchiveReadError { inner })?;
+        return Ok(domain::subtitles::encoding::decode(&subtitle_bytes));
+    }
+
+    Err(Error::NoSubtitleInArchive)
+}
+
+impl SubtitleProvider for SubdlClient {
+    type SubtitleId = String;
+    type Error = Error;
+
+    async fn search(
+        &self,
+        query: &str,
+        language: LanguageCode,
+        episode: Option<EpisodeIdentifier>,
+        // SubDL can't search by file
+        _media_file: Option<&Path>,
+    ) -> Result<impl Iterator<Item = SubtitleDownloadOption<Self::SubtitleId>>> {
+        let url = self.base_url.join("subtitles")?;
+        let mut query_params = vec![
+            ("api_key", self.api_key.clone()),
+            ("film_name", query.to_string()),
+            ("languages", language.to_iso639_1().to_uppercase()),
+            ("subs_per_page", SUBTITLES_PER_PAGE.to_string()),
+        ];
+        match &episode {
+            Some(EpisodeIdentifier {
+                season_no,
+                episode_no,
+            }) => query_params.extend([
+                ("type", "tv".to_string()),
+                ("season_number", season_no.to_string()),
+                ("episode_number", episode_no.to_string()),
+            ]),
+            None => query_params.push(("type", "movie".to_string())),
+        }
+
+        let response_string = self
+            .http_client
+            .get(url)
+            .query(&query_params)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .send()
+            .await?
+            .text()
+            .await?;
+
+        Self::check_api_error(&response_string)?;
+
+        let response: SearchResponse = serde_json::from_str(&response_string)?;
+
+        Ok(into_download_options(response, language, episode))
+    }
+
+    async fn download(&self, id: &Self::SubtitleId) -> Result<String> {
+        let url = self.download_base_url.join(&format!("subtitle/{id}.zip"))?;
+
+        let archive_bytes = self
+            .http_client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+
+        extract_subtitle(&archive_bytes)
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    RequestError { inner: reqwest::Error },
+    SubdlAPIError { inner: SubdlError },
+    SubdlJSONParsingError { inner: serde_json::Error },
+    SubdlInvalidURLError { inner: url::ParseError },
+    ArchiveError { inner: zip::result::ZipError },
+    ArchiveReadError { inner: std::io::Error },
+    NoSubtitleInArchive,
+}
+
+type Result<T> = core::result::Result<T, Error>;
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#?}", self)
+    }
+}
+impl std::error::Error for Error {}
+
+impl From<reqwest::Error> for Error {
+    fn from(value: reqwest::Error) -> Self {
+        Self::RequestError { inner: value }
+    }
+}
+
+impl From<SubdlError> for Error {
+    fn from(value: SubdlError) -> Self {
+        Self::SubdlAPIError { inner: value }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        Self::SubdlJSONParsingError { inner: value }
+    }
+}
+
+impl From<url::ParseError> for Error {
+    fn from(value: url::ParseError) -> Self {
+        Self::SubdlInvalidURLError { inner: value }
+    }
+}
+
+impl From<zip::result::ZipError> for Error {
+    fn from(value: zip::result::ZipError) -> Self {
+        Self::ArchiveError { inner: value }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use domain::{language::LanguageCode, series::EpisodeIdentifier};
+
+    use crate::{SubdlClient, dto::SearchResponse, extract_subtitle, into_download_options};
+
+    #[test]
+    fn test_parse_search_results() {
+        let response: SearchResponse =
+            serde_json::from_str(include_str!("../fixtures/series_search.json")).unwrap();
+        let options: Vec<_> = into_download_options(
+            response.clone(),
+            LanguageCode::English,
+            Some(EpisodeIdentifier {
+                season_no: 1,
+                episode_no: 1,
+            }),
+        )
+        .collect();
+
+        assert_eq!(options.len(), 1);
+        assert_eq!(options[0].id, "1984093-2048876");
+        assert_eq!(
+            options[0].title,
+            "Rick.and.Morty.S01E01.720p.BluRay.x264-DEMAND"
+        );
+
+        // Season packs are fine when the whole thing is asked for
+        let options: Vec<_> =
+            into_download_options(response, LanguageCode::English, None).collect();
+        assert_eq!(options.len(), 2);
+        assert!(options[1].metadata.hearing_impaired);
+    }
+
+    #[test]
+    fn test_extract_subtitle() {
+        let mut archive = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        archive.start_file("readme.txt", options).unwrap();
+        archive.write_all(b"Not a subtitle").unwrap();
+        archive.start_file("Movie.2006.SRT", options).unwrap();
+        archive
+            .write_all(b"1\n00:00:01,000 --> 00:00:02,000\nHello\n")
+            .unwrap();
+        let archive_bytes = archive.finish().unwrap().into_inner();
+
+        assert_eq!(
+            extract_subtitle(&archive_bytes).unwrap(),
+            "1\n00:00:01,000 --> 00:00:02,000\nHello\n"
+        );
+        assert!(extract_subtitle(b"not a zip").is_err());
+    }
+
+    #[test]
+    fn test_api_error() {
+        assert!(
+            SubdlClient::check_api_error(r#"{"status":false,"error":"Invalid API key"}"#).is_err()
+        );
+        assert!(
+            SubdlClient::check_api_error(include_str!("../fixtures/series_search.json")).is_ok()
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct SearchResponse {
+    pub page: usize,
+    pub results: Box<[SearchResult]>,
+}
+
+/// Movies have `title` and `release_date`, series have `name` and `first_air_date`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct SearchResult {
+    pub id: u64,
+    #[serde(alias = "name")]
+    pub title: String,
+    #[serde(default, alias = "first_air_date")]
+    pub release_date: Option<String>,
+    pub poster_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Details {
+    #[serde(alias = "name")]
+    pub title: String,
+    pub overview: Option<String>,
+    #[serde(default, alias = "first_air_date")]
+    pub release_date: Option<String>,
+    #[serde(default)]
+    pub genres: Box<[Genre]>,
+    /// in minutes, movies only
+    pub runtime: Option<u64>,
+    /// in minutes, series only
+    #[serde(default)]
+    pub episode_run_time: Box<[u64]>,
+    pub poster_path: Option<String>,
+    pub backdrop_path: Option<String>,
+    pub credits: Option<Credits>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Genre {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Credits {
+    #[serde(default)]
+    pub cast: Box<[Cast]>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Cast {
+    pub name: String,
+    pub character: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TmdbError {
+    pub status_code: Option<usize>,
+    pub status_message: Option<String>,
+}
+
+impl std::fmt::Display for TmdbError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#?}", self)
+    }
+}
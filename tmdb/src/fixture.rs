@@ -0,0 +1,144 @@
+//! A metadata provider that serves a fixed set of media instead of calling TMDB.
+
+use std::{path::Path, sync::Arc};
+
+use domain::metadata::{MediaDetails, MediaKind, MetadataProvider, MetadataSearchResult};
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FixtureEntry {
+    pub id: String,
+    pub kind: MediaKind,
+    pub details: MediaDetails,
+}
+
+#[derive(Debug, Clone)]
+pub struct FixtureProvider {
+    entries: Arc<[FixtureEntry]>,
+}
+
+impl FixtureProvider {
+    pub fn new(entries: impl Into<Arc<[FixtureEntry]>>) -> Self {
+        Self {
+            entries: entries.into(),
+        }
+    }
+
+    /// Expects a JSON array of [`FixtureEntry`]
+    pub fn from_json(json: &str) -> Result<Self, FixtureError> {
+        let entries: Vec<FixtureEntry> =
+            serde_json::from_str(json).map_err(FixtureError::CantParse)?;
+        Ok(Self::new(entries))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, FixtureError> {
+        let json = std::fs::read_to_string(path).map_err(FixtureError::CantRead)?;
+        Self::from_json(&json)
+    }
+}
+
+impl MetadataProvider for FixtureProvider {
+    type MediaId = String;
+    type Error = FixtureError;
+
+    /// Matches titles that contain the query, ignoring case
+    async fn search(
+        &self,
+        query: &str,
+        kind: MediaKind,
+    ) -> Result<impl Iterator<Item = MetadataSearchResult<Self::MediaId>>, Self::Error> {
+        let query = query.to_lowercase();
+
+        Ok(self
+            .entries
+            .iter()
+            .filter(move |entry| {
+                entry.kind == kind && entry.details.title.to_lowercase().contains(&query)
+            })
+            .map(|entry| MetadataSearchResult {
+                id: entry.id.clone(),
+                kind: entry.kind.clone(),
+                title: entry.details.title.clone(),
+                year: entry.details.year,
+                poster: entry.details.poster.clone(),
+            })
+            .collect::<Vec<_>>()
+            .into_iter())
+    }
+
+    async fn details(
+        &self,
+        id: &Self::MediaId,
+        kind: MediaKind,
+    ) -> Result<MediaDetails, Self::Error> {
+        self.entries
+            .iter()
+            .find(|entry| &entry.id == id && entry.kind == kind)
+            .map(|entry| entry.details.clone())
+            .ok_or_else(|| FixtureError::NotFound(id.clone()))
+    }
+}
+
+#[derive(Debug)]
+pub enum FixtureError {
+    CantRead(std::io::Error),
+    CantParse(serde_json::Error),
+    NotFound(String),
+}
+
+impl std::fmt::Display for FixtureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#?}", self)
+    }
+}
+impl std::error::Error for FixtureError {}
+
+#[cfg(test)]
+mod tests {
+    use domain::metadata::{MediaKind, MetadataProvider};
+
+    use super::{FixtureError, FixtureProvider};
+
+    fn provider() -> FixtureProvider {
+        FixtureProvider::from_json(include_str!("../fixtures/library.json")).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_search() {
+        let provider = provider();
+
+        let results: Vec<_> = provider
+            .search("rick", MediaKind::Series)
+            .await
+            .unwrap()
+            .collect();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "rick-and-morty");
+        assert_eq!(results[0].year, Some(2013));
+
+        let results: Vec<_> = provider
+            .search("rick", MediaKind::Movie)
+            .await
+            .unwrap()
+            .collect();
+        assert!(results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_details() {
+        let provider = provider();
+
+        let details = provider
+            .details(&"idiocracy".to_string(), MediaKind::Movie)
+            .await
+            .unwrap();
+        assert_eq!(details.title, "Idiocracy");
+        assert_eq!(details.cast[0].character.as_deref(), Some("Joe Bauers"));
+
+        assert!(matches!(
+            provider
+                .details(&"missing".to_string(), MediaKind::Movie)
+                .await,
+            Err(FixtureError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,284 @@
+mod dto;
+#[cfg(feature = "fixture")]
+pub mod fixture;
+
+use std::{sync::LazyLock, time::Duration};
+
+use domain::metadata::{
+    CastMember, MediaDetails, MediaKind, MetadataProvider, MetadataSearchResult,
+};
+
+use dto::{Details, SearchResponse, TmdbError};
+
+static TMDB_BASE_URL: LazyLock<reqwest::Url> = LazyLock::new(|| {
+    reqwest::Url::parse("https://api.themoviedb.org/3/").expect("TMDB base url should be valid")
+});
+const POSTER_BASE_URL: &str = "https://image.tmdb.org/t/p/w500";
+const BACKDROP_BASE_URL: &str = "https://image.tmdb.org/t/p/w1280";
+const MAX_CAST_MEMBERS: usize = 10;
+
+#[derive(Debug, Clone)]
+pub struct TmdbClient {
+    http_client: reqwest::Client,
+    /// API Read Access Token
+    access_token: String,
+    base_url: reqwest::Url,
+}
+
+impl TmdbClient {
+    pub fn new(access_token: impl Into<String>) -> Self {
+        Self {
+            http_client: reqwest::Client::new(),
+            access_token: access_token.into(),
+            base_url: TMDB_BASE_URL.clone(),
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: reqwest::Url) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    async fn get(&self, path: &str, query: &[(&str, &str)]) -> Result<String> {
+        let url = self.base_url.join(path)?;
+
+        let response_string = self
+            .http_client
+            .get(url)
+            .query(query)
+            .bearer_auth(&self.access_token)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .send()
+            .await?
+            .text()
+            .await?;
+
+        Self::check_api_error(&response_string)?;
+
+        Ok(response_string)
+    }
+
+    fn check_api_error(api_response_str: &str) -> Result<()> {
+        if let Ok(error) = serde_json::from_str::<TmdbError>(api_response_str)
+            && error.status_code.is_some()
+        {
+            return Err(error.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// TMDB calls series "tv"
+fn kind_path(kind: &MediaKind) -> &'static str {
+    match kind {
+        MediaKind::Movie => "movie",
+        MediaKind::Series => "tv",
+    }
+}
+
+/// Dates look like `2006-09-01`, and are empty when unknown
+fn parse_year(date: Option<&str>) -> Option<u32> {
+    date?.split('-').next()?.parse().ok()
+}
+
+fn image_url(base_url: &str, path: Option<String>) -> Option<String> {
+    path.filter(|path| !path.is_empty())
+        .map(|path| format!("{base_url}{path}"))
+}
+
+impl From<Details> for MediaDetails {
+    fn from(details: Details) -> Self {
+        let runtime_minutes = details
+            .runtime
+            .or_else(|| details.episode_run_time.first().copied())
+            .filter(|minutes| *minutes > 0);
+
+        MediaDetails {
+            title: details.title,
+            overview: details.overview.filter(|overview| !overview.is_empty()),
+            year: parse_year(details.release_date.as_deref()),
+            genres: details.genres.into_iter().map(|genre| genre.name).collect(),
+            cast: details
+                .credits
+                .map(|credits| credits.cast)
+                .unwrap_or_default()
+                .into_iter()
+                .take(MAX_CAST_MEMBERS)
+                .map(|cast| CastMember {
+                    name: cast.name,
+                    character: cast.character.filter(|character| !character.is_empty()),
+                })
+                .collect(),
+            runtime: runtime_minutes.map(|minutes| Duration::from_secs(minutes * 60)),
+            poster: image_url(POSTER_BASE_URL, details.poster_path),
+            backdrop: image_url(BACKDROP_BASE_URL, details.backdrop_path),
+        }
+    }
+}
+
+fn into_search_results(
+    response: SearchResponse,
+    kind: MediaKind,
+) -> impl Iterator<Item = MetadataSearchResult<u64>> {
+    response
+        .results
+        .into_iter()
+        .map(move |result| MetadataSearchResult {
+            id: result.id,
+            kind: kind.clone(),
+            title: result.title,
+            year: parse_year(result.release_date.as_deref()),
+            poster: image_url(POSTER_BASE_URL, result.poster_path),
+        })
+}
+
+impl MetadataProvider for TmdbClient {
+    type MediaId = u64;
+    type Error = Error;
+
+    async fn search(
+        &self,
+        query: &str,
+        kind: MediaKind,
+    ) -> Result<impl Iterator<Item = MetadataSearchResult<Self::MediaId>>> {
+        let response_string = self
+            .get(&format!("search/{}", kind_path(&kind)), &[("query", query)])
+            .await?;
+
+        let response: SearchResponse = serde_json::from_str(&response_string)?;
+
+        Ok(into_search_results(response, kind))
+    }
+
+    async fn details(&self, id: &Self::MediaId, kind: MediaKind) -> Result<MediaDetails> {
+        let response_string = self
+            .get(
+                &format!("{}/{id}", kind_path(&kind)),
+                &[("append_to_response", "credits")],
+            )
+            .await?;
+
+        let details: Details = serde_json::from_str(&response_string)?;
+
+        Ok(details.into())
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    RequestError { inner: reqwest::Error },
+    TmdbAPIError { inner: TmdbError },
+    TmdbJSONParsingError { inner: serde_json::Error },
+    TmdbInvalidURLError { inner: url::ParseError },
+}
+
+type Result<T> = core::result::Result<T, Error>;
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#?}", self)
+    }
+}
+impl std::error::Error for Error {}
+
+impl From<reqwest::Error> for Error {
+    fn from(value: reqwest::Error) -> Self {
+        Self::RequestError { inner: value }
+    }
+}
+
+impl From<TmdbError> for Error {
+    fn from(value: TmdbError) -> Self {
+        Self::TmdbAPIError { inner: value }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        Self::TmdbJSONParsingError { inner: value }
+    }
+}
+
+impl From<url::ParseError> for Error {
+    fn from(value: url::ParseError) -> Self {
+        Self::TmdbInvalidURLError { inner: value }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use domain::metadata::{MediaDetails, MediaKind};
+
+    use crate::{
+        TmdbClient,
+        dto::{Details, SearchResponse},
+        into_search_results,
+    };
+
+    #[test]
+    fn test_parse_movie_details() {
+        let details: Details =
+            serde_json::from_str(include_str!("../fixtures/movie_details.json")).unwrap();
+        let details: MediaDetails = details.into();
+
+        assert_eq!(details.title, "Idiocracy");
+        assert_eq!(details.year, Some(2006));
+        assert_eq!(details.runtime, Some(Duration::from_secs(84 * 60)));
+        assert_eq!(details.genres, ["Comedy", "Science Fiction"]);
+        assert_eq!(details.cast[0].name, "Luke Wilson");
+        assert_eq!(
+            details.poster.as_deref(),
+            Some("https://image.tmdb.org/t/p/w500/uaHZ5S1IqHQwA6I6yrVb5TfJn8v.jpg")
+        );
+        assert!(
+            details
+                .backdrop
+                .unwrap()
+                .starts_with(super::BACKDROP_BASE_URL)
+        );
+    }
+
+    #[test]
+    fn test_parse_series_details() {
+        let details: Details =
+            serde_json::from_str(include_str!("../fixtures/series_details.json")).unwrap();
+        let details: MediaDetails = details.into();
+
+        assert_eq!(details.title, "Rick and Morty");
+        assert_eq!(details.year, Some(2013));
+        assert_eq!(details.runtime, Some(Duration::from_secs(22 * 60)));
+        assert_eq!(details.cast.len(), 2);
+        assert_eq!(details.cast[1].character, None);
+        assert_eq!(details.backdrop, None);
+    }
+
+    #[test]
+    fn test_parse_search_results() {
+        let response: SearchResponse =
+            serde_json::from_str(include_str!("../fixtures/series_search.json")).unwrap();
+        let results: Vec<_> = into_search_results(response, MediaKind::Series).collect();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].id, 60625);
+        assert_eq!(results[0].title, "Rick and Morty");
+        assert_eq!(results[0].year, Some(2013));
+        assert_eq!(results[1].year, None);
+        assert_eq!(results[1].poster, None);
+    }
+
+    #[test]
+    fn test_api_error() {
+        assert!(
+            TmdbClient::check_api_error(
+                r#"{"status_code":7,"status_message":"Invalid API key: You must be granted a valid key.","success":false}"#
+            )
+            .is_err()
+        );
+        assert!(
+            TmdbClient::check_api_error(include_str!("../fixtures/movie_details.json")).is_ok()
+        );
+    }
+}
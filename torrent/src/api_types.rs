@@ -72,6 +72,8 @@ pub struct TorrentInfo {
     pub hash: Box<str>,
     pub magnet_uri: Box<str>,
     pub num_seeds: usize,
+    /// 1 based queue position, 0 or -1 if the torrent isn't queued
+    pub priority: isize,
     /// percentage/100
     pub progress: f32,
     /// With torrent folder
@@ -263,11 +265,13 @@ pub struct TorrentContents {
 pub mod into_domain {
     use std::fmt::Display;
 
+    use std::time::{Duration, UNIX_EPOCH};
+
     use crate::{
         TorrentInfo,
         qbittorrent_client::{QBittorrentError, QBittorrentResult},
     };
-    use domain::{Download, DownloadState};
+    use domain::{Bytes, BytesPerSecond, Download, DownloadState};
 
     use super::{TorrentExtra, TorrentState};
 
@@ -357,6 +361,9 @@ pub mod into_domain {
         }
     }
 
+    /// qBittorrent reports this ETA when it can't estimate one.
+    const INFINITE_ETA: usize = 8640000;
+
     impl From<TorrentInfo> for Download {
         fn from(val: TorrentInfo) -> Self {
             let extra: Option<TorrentExtra> = val.as_ref().try_into().ok();
@@ -377,7 +384,79 @@ pub mod into_domain {
                 progress: val.progress,
                 state: val.state.into(),
                 needs_file_mapping,
+                queue_position: u32::try_from(val.priority)
+                    .ok()
+                    .filter(|position| *position > 0),
+                size: Bytes(val.size as u64),
+                download_speed: BytesPerSecond(val.dlspeed as u64),
+                upload_speed: BytesPerSecond(val.upspeed as u64),
+                eta: (val.eta < INFINITE_ETA).then(|| Duration::from_secs(val.eta as u64)),
+                seeds: u32::try_from(val.num_seeds).unwrap_or(u32::MAX),
+                added_on: UNIX_EPOCH + Duration::from_secs(val.added_on as u64),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        use domain::{Bytes, BytesPerSecond, Download};
+
+        use crate::{TorrentInfo, TorrentState};
+
+        fn torrent_info() -> TorrentInfo {
+            TorrentInfo {
+                added_on: 1_700_000_000,
+                name: "debian.iso".into(),
+                amount_left: 512,
+                category: "".into(),
+                completed: 512,
+                completion_on: -1,
+                content_path: "/downloads/debian.iso".into(),
+                dlspeed: 2048,
+                downloaded: 512,
+                eta: 90,
+                hash: "debianhash".into(),
+                magnet_uri: "".into(),
+                num_seeds: 7,
+                priority: 0,
+                progress: 0.5,
+                root_path: "/downloads/debian.iso".into(),
+                save_path: "/downloads".into(),
+                size: 1024,
+                state: TorrentState::Downloading,
+                tags: Box::new([]),
+                uploaded: 0,
+                upspeed: 128,
             }
         }
+
+        #[test]
+        fn test_download_from_torrent_info() {
+            let download: Download = torrent_info().into();
+
+            assert_eq!(download.size, Bytes(1024));
+            assert_eq!(download.download_speed, BytesPerSecond(2048));
+            assert_eq!(download.upload_speed, BytesPerSecond(128));
+            assert_eq!(download.eta, Some(Duration::from_secs(90)));
+            assert_eq!(download.seeds, 7);
+            assert_eq!(
+                download.added_on,
+                UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+            );
+            assert_eq!(download.queue_position, None);
+        }
+
+        #[test]
+        fn test_download_from_torrent_info_without_eta() {
+            let download: Download = TorrentInfo {
+                eta: super::INFINITE_ETA,
+                ..torrent_info()
+            }
+            .into();
+
+            assert_eq!(download.eta, None);
+        }
     }
 }
@@ -1,4 +1,6 @@
 mod api_types;
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod qbittorrent_client;
 mod qbittorrent_web_api;
 
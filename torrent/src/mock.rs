@@ -0,0 +1,471 @@
+//! An in-process fake of the qBittorrent WebUI.
+//!
+//! Only the `api/v2/torrents/*` and `api/v2/app/setPreferences` endpoints this crate calls are
+//! implemented. A torrent has to be registered with [`MockQBittorrent::register_torrent`] before
+//! it can be added, any other link is rejected the same way qBittorrent rejects a broken one.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    Form, Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+};
+
+use crate::{TorrentContents, TorrentInfo, TorrentState};
+
+const SAVE_PATH: &str = "/downloads";
+
+/// A torrent that can be added to the fake by posting its link.
+#[derive(Debug, Clone)]
+pub struct MockTorrent {
+    pub hash: Box<str>,
+    pub name: Box<str>,
+    /// File name and size in bytes pairs
+    pub files: Vec<(Box<str>, usize)>,
+}
+
+#[derive(Debug)]
+pub struct MockQBittorrent {
+    pub port: usize,
+    state: Arc<Mutex<MockState>>,
+    server_handle: tokio::task::JoinHandle<()>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    /// Link -> torrent that gets added when the link is posted
+    registry: HashMap<Box<str>, MockTorrent>,
+    /// Kept in insertion order, like qBittorrent does
+    torrents: Vec<MockEntry>,
+    categories: HashSet<Box<str>>,
+    /// Hashes in queue order. Only unfinished torrents get a queue position.
+    queue: Vec<Box<str>>,
+    preferences: MockPreferences,
+}
+
+/// The subset of qBittorrent preferences the mock understands.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+pub struct MockPreferences {
+    #[serde(default)]
+    pub queueing_enabled: bool,
+    #[serde(default)]
+    pub max_active_downloads: i64,
+}
+
+#[derive(Debug)]
+struct MockEntry {
+    info: TorrentInfo,
+    files: Vec<(Box<str>, usize)>,
+    /// States the torrent goes through, one per `info` call. The last one sticks.
+    scripted_states: VecDeque<TorrentState>,
+}
+
+impl MockQBittorrent {
+    /// Starts the fake WebUI on a random local port.
+    pub async fn spawn() -> std::io::Result<Self> {
+        let state = Arc::new(Mutex::new(MockState::default()));
+
+        let router = Router::new()
+            .route("/api/v2/torrents/add", post(handle_add))
+            .route("/api/v2/torrents/delete", post(handle_delete))
+            .route("/api/v2/torrents/info", get(handle_info))
+            .route("/api/v2/torrents/files", get(handle_files))
+            .route("/api/v2/torrents/setCategory", post(handle_set_category))
+            .route(
+                "/api/v2/torrents/createCategory",
+                post(handle_create_category),
+            )
+            // topPrio, bottomPrio, increasePrio and decreasePrio
+            .route("/api/v2/torrents/{queue_move}", post(handle_queue_move))
+            .route("/api/v2/app/setPreferences", post(handle_set_preferences))
+            .with_state(state.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port() as usize;
+
+        let server_handle = tokio::spawn(async move {
+            axum::serve(listener, router)
+                .await
+                .expect("Mock qBittorrent server stopped unexpectedly");
+        });
+
+        Ok(Self {
+            port,
+            state,
+            server_handle,
+        })
+    }
+
+    /// Makes `url` a valid link for `api/v2/torrents/add`.
+    pub fn register_torrent(&self, url: &str, torrent: MockTorrent) {
+        self.lock().registry.insert(url.into(), torrent);
+    }
+
+    /// Queues the states the torrent with `hash` goes through.
+    ///
+    /// Each `api/v2/torrents/info` call advances the torrent by one state, the last state sticks.
+    pub fn script_states(&self, hash: &str, states: impl IntoIterator<Item = TorrentState>) {
+        let mut state = self.lock();
+        let entry = state
+            .torrents
+            .iter_mut()
+            .find(|entry| *entry.info.hash == *hash)
+            .expect("Scripted a torrent that wasn't added to the mock");
+
+        entry.scripted_states = states.into_iter().collect();
+    }
+
+    /// Edits the torrent with `hash` in place. Returns `false` if there is no such torrent.
+    pub fn update_torrent(&self, hash: &str, update: impl FnOnce(&mut TorrentInfo)) -> bool {
+        let mut state = self.lock();
+        let Some(entry) = state
+            .torrents
+            .iter_mut()
+            .find(|entry| *entry.info.hash == *hash)
+        else {
+            return false;
+        };
+
+        update(&mut entry.info);
+        true
+    }
+
+    /// Current torrents, without advancing scripted states.
+    pub fn torrents(&self) -> Box<[TorrentInfo]> {
+        self.lock()
+            .torrents
+            .iter()
+            .map(|entry| entry.info.clone())
+            .collect()
+    }
+
+    pub fn categories(&self) -> Box<[Box<str>]> {
+        self.lock().categories.iter().cloned().collect()
+    }
+
+    pub fn preferences(&self) -> MockPreferences {
+        self.lock().preferences.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state
+            .lock()
+            .expect("Mock qBittorrent state was poisoned")
+    }
+}
+
+impl Drop for MockQBittorrent {
+    fn drop(&mut self) {
+        self.server_handle.abort();
+    }
+}
+
+type MockStateExtractor = State<Arc<Mutex<MockState>>>;
+
+#[derive(serde::Deserialize)]
+struct AddForm {
+    urls: Box<str>,
+    #[serde(default)]
+    category: Box<str>,
+}
+
+async fn handle_add(State(state): MockStateExtractor, Form(form): Form<AddForm>) -> &'static str {
+    let mut state = state.lock().unwrap();
+
+    let Some(torrent) = state.registry.get(&form.urls).cloned() else {
+        return "Fails.";
+    };
+
+    if state
+        .torrents
+        .iter()
+        .any(|entry| entry.info.hash == torrent.hash)
+    {
+        return "Fails.";
+    }
+
+    // qBittorrent creates missing categories on the fly
+    if !form.category.is_empty() {
+        state.categories.insert(form.category.clone());
+    }
+
+    state.queue.push(torrent.hash.clone());
+
+    let size = torrent.files.iter().map(|(_, size)| size).sum();
+    let content_path = PathBuf::from(SAVE_PATH).join(&*torrent.name);
+    let added_on = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as usize)
+        .unwrap_or_default();
+
+    state.torrents.push(MockEntry {
+        info: TorrentInfo {
+            added_on,
+            name: torrent.name,
+            amount_left: size,
+            category: form.category,
+            completed: 0,
+            completion_on: -1,
+            content_path: content_path.clone(),
+            dlspeed: 0,
+            downloaded: 0,
+            eta: 8640000,
+            hash: torrent.hash,
+            magnet_uri: form.urls,
+            num_seeds: 0,
+            priority: 0,
+            progress: 0.0,
+            root_path: content_path,
+            save_path: SAVE_PATH.into(),
+            size,
+            state: TorrentState::MetaDL,
+            tags: Box::new([]),
+            uploaded: 0,
+            upspeed: 0,
+        },
+        files: torrent.files,
+        scripted_states: VecDeque::new(),
+    });
+
+    "Ok."
+}
+
+#[derive(serde::Deserialize)]
+struct DeleteForm {
+    hashes: Box<str>,
+}
+
+async fn handle_delete(State(state): MockStateExtractor, Form(form): Form<DeleteForm>) {
+    let mut state = state.lock().unwrap();
+
+    if &*form.hashes == "all" {
+        state.torrents.clear();
+        state.queue.clear();
+        return;
+    }
+
+    let hashes: HashSet<&str> = form.hashes.split('|').collect();
+    state
+        .torrents
+        .retain(|entry| !hashes.contains(entry.info.hash.as_ref()));
+    state.queue.retain(|hash| !hashes.contains(hash.as_ref()));
+}
+
+async fn handle_info(State(state): MockStateExtractor) -> Json<Vec<TorrentInfo>> {
+    let mut state = state.lock().unwrap();
+
+    state.torrents.iter_mut().for_each(MockEntry::advance);
+    state.update_priorities();
+
+    Json(
+        state
+            .torrents
+            .iter()
+            .map(|entry| entry.info.clone())
+            .collect(),
+    )
+}
+
+#[derive(serde::Deserialize)]
+struct FilesQuery {
+    hash: Box<str>,
+}
+
+async fn handle_files(
+    State(state): MockStateExtractor,
+    Query(query): Query<FilesQuery>,
+) -> Result<Json<Vec<TorrentContents>>, StatusCode> {
+    let state = state.lock().unwrap();
+    let entry = state
+        .torrents
+        .iter()
+        .find(|entry| entry.info.hash == query.hash)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(
+        entry
+            .files
+            .iter()
+            .enumerate()
+            .map(|(index, (name, size))| TorrentContents {
+                index,
+                is_seed: None,
+                name: name.clone(),
+                piece_range: Box::new([0, 0]),
+                priority: 1,
+                progress: entry.info.progress,
+                size: *size,
+                availability: 0.0,
+            })
+            .collect(),
+    ))
+}
+
+#[derive(serde::Deserialize)]
+struct SetCategoryForm {
+    hashes: Box<str>,
+    category: Box<str>,
+}
+
+async fn handle_set_category(
+    State(state): MockStateExtractor,
+    Form(form): Form<SetCategoryForm>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+
+    if !form.category.is_empty() && !state.categories.contains(&form.category) {
+        return StatusCode::CONFLICT;
+    }
+
+    let hashes: HashSet<&str> = form.hashes.split('|').collect();
+    state
+        .torrents
+        .iter_mut()
+        .filter(|entry| hashes.contains("all") || hashes.contains(entry.info.hash.as_ref()))
+        .for_each(|entry| entry.info.category = form.category.clone());
+
+    StatusCode::OK
+}
+
+#[derive(serde::Deserialize)]
+struct CreateCategoryForm {
+    category: Box<str>,
+}
+
+async fn handle_create_category(
+    State(state): MockStateExtractor,
+    Form(form): Form<CreateCategoryForm>,
+) -> StatusCode {
+    if form.category.is_empty() {
+        return StatusCode::BAD_REQUEST;
+    }
+
+    if state.lock().unwrap().categories.insert(form.category) {
+        StatusCode::OK
+    } else {
+        StatusCode::CONFLICT
+    }
+}
+
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+enum QueueMove {
+    #[serde(rename = "topPrio")]
+    Top,
+    #[serde(rename = "bottomPrio")]
+    Bottom,
+    #[serde(rename = "increasePrio")]
+    Up,
+    #[serde(rename = "decreasePrio")]
+    Down,
+}
+
+#[derive(serde::Deserialize)]
+struct HashesForm {
+    hashes: Box<str>,
+}
+
+async fn handle_queue_move(
+    State(state): MockStateExtractor,
+    Path(queue_move): Path<QueueMove>,
+    Form(form): Form<HashesForm>,
+) -> StatusCode {
+    let mut state = state.lock().unwrap();
+
+    // qBittorrent refuses to touch priorities while queueing is off
+    if !state.preferences.queueing_enabled {
+        return StatusCode::CONFLICT;
+    }
+
+    // Finished torrents leave the queue, drop them before moving things around
+    state.update_priorities();
+
+    for hash in form.hashes.split('|') {
+        let Some(index) = state.queue.iter().position(|queued| &**queued == hash) else {
+            continue;
+        };
+
+        let hash = state.queue.remove(index);
+        let new_index = match queue_move {
+            QueueMove::Top => 0,
+            QueueMove::Bottom => state.queue.len(),
+            QueueMove::Up => index.saturating_sub(1),
+            QueueMove::Down => (index + 1).min(state.queue.len()),
+        };
+        state.queue.insert(new_index, hash);
+    }
+
+    state.update_priorities();
+    StatusCode::OK
+}
+
+#[derive(serde::Deserialize)]
+struct SetPreferencesForm {
+    json: Box<str>,
+}
+
+async fn handle_set_preferences(
+    State(state): MockStateExtractor,
+    Form(form): Form<SetPreferencesForm>,
+) -> StatusCode {
+    let Ok(preferences) = serde_json::from_str::<MockPreferences>(&form.json) else {
+        return StatusCode::BAD_REQUEST;
+    };
+
+    let mut state = state.lock().unwrap();
+    state.preferences = preferences;
+    state.update_priorities();
+
+    StatusCode::OK
+}
+
+impl MockState {
+    /// Mirrors the queue into each torrent's `priority` field.
+    fn update_priorities(&mut self) {
+        let finished: HashSet<Box<str>> = self
+            .torrents
+            .iter()
+            .filter(|entry| entry.info.state.is_done())
+            .map(|entry| entry.info.hash.clone())
+            .collect();
+        self.queue.retain(|hash| !finished.contains(hash));
+
+        let queueing_enabled = self.preferences.queueing_enabled;
+        let queue = &self.queue;
+        self.torrents.iter_mut().for_each(|entry| {
+            entry.info.priority = queue
+                .iter()
+                .position(|hash| *hash == entry.info.hash)
+                .filter(|_| queueing_enabled)
+                .map(|index| index as isize + 1)
+                .unwrap_or(0);
+        });
+    }
+}
+
+impl MockEntry {
+    /// Applies the current scripted state and moves on to the next one.
+    fn advance(&mut self) {
+        let Some(state) = self.scripted_states.front().cloned() else {
+            return;
+        };
+
+        if self.scripted_states.len() > 1 {
+            self.scripted_states.pop_front();
+        }
+
+        if state.is_done() {
+            self.info.progress = 1.0;
+            self.info.amount_left = 0;
+            self.info.completed = self.info.size;
+            self.info.downloaded = self.info.size;
+        }
+        self.info.state = state;
+    }
+}
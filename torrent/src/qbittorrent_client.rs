@@ -3,7 +3,7 @@ use std::fmt::Display;
 use std::path::PathBuf;
 use std::process::Stdio;
 
-use log::{debug, info};
+use log::{debug, info, warn};
 use tokio::fs::OpenOptions;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::Command;
@@ -13,17 +13,22 @@ use crate::TorrentExtra;
 use crate::api_types::{TorrentContents, TorrentInfo};
 use crate::qbittorrent_web_api::{
     QBittorrentWebApiResult, add_torrent, get_torrent_contents, get_torrent_list, remove_torrent,
-    set_torrent_category,
+    set_max_active_downloads, set_queue_position, set_torrent_category,
 };
 
 #[derive(Debug)]
 pub struct QBittorrentClient {
     pub profile_dir: PathBuf,
+    /// Port of an already running WebUI. When set, `qbittorrent-nox` is never spawned.
+    pub web_ui_port: Option<usize>,
+    /// Applied every time the WebUI is spawned. `None` disables queueing.
+    pub max_active_downloads: Option<u32>,
 }
 
 #[derive(Debug)]
 pub(crate) struct QBittorrentClientProcess {
-    pub process_handle: JoinHandle<()>,
+    /// `None` when we're attached to a WebUI we didn't spawn
+    pub process_handle: Option<JoinHandle<()>>,
     pub port: usize,
 }
 
@@ -43,16 +48,37 @@ impl QBittorrentClient {
     pub fn try_new(profile_dir: Option<PathBuf>) -> QBittorrentResult<Self> {
         Ok(Self {
             profile_dir: profile_dir.unwrap_or(env::temp_dir().join("streamy-qbittorrent")),
+            web_ui_port: None,
+            max_active_downloads: None,
         })
     }
 
+    pub fn with_max_active_downloads(self, max_active_downloads: Option<u32>) -> Self {
+        Self {
+            max_active_downloads,
+            ..self
+        }
+    }
+
+    /// Talks to the WebUI at `port` instead of spawning `qbittorrent-nox`.
+    pub fn with_web_ui_port(self, port: usize) -> Self {
+        Self {
+            web_ui_port: Some(port),
+            ..self
+        }
+    }
+
     pub async fn event_loop(
         &self,
         mut receiver: tokio::sync::mpsc::Receiver<QBittorrentClientMessage>,
         state_updater: tokio::sync::watch::Sender<Box<[TorrentInfo]>>,
     ) -> QBittorrentResult<()> {
-        let mut process_client = Some(self.spawn_qbittorrent_web().await?);
         let http_client = reqwest::Client::new();
+        let mut max_active_downloads = self.max_active_downloads;
+        let mut process_client = Some(
+            self.spawn_configured_qbittorrent_web(&http_client, max_active_downloads)
+                .await?,
+        );
         // Changes in qbittorrent dont immediately get reflected to the API
         // we use this to forcefully keep client alive
         let mut force_keep_client_alive = false;
@@ -70,7 +96,13 @@ impl QBittorrentClient {
                         client
                     } else {
                         debug!("Spawning QBittorrent to get file contents");
-                        process_client = Some(self.spawn_qbittorrent_web().await?);
+                        process_client = Some(
+                            self.spawn_configured_qbittorrent_web(
+                                &http_client,
+                                max_active_downloads,
+                            )
+                            .await?,
+                        );
                         process_client.as_ref().unwrap()
                     };
 
@@ -87,7 +119,13 @@ impl QBittorrentClient {
                         client
                     } else {
                         debug!("Spawning QBittorrent to set a category");
-                        process_client = Some(self.spawn_qbittorrent_web().await?);
+                        process_client = Some(
+                            self.spawn_configured_qbittorrent_web(
+                                &http_client,
+                                max_active_downloads,
+                            )
+                            .await?,
+                        );
                         process_client.as_ref().unwrap()
                     };
 
@@ -105,7 +143,13 @@ impl QBittorrentClient {
                         client
                     } else {
                         debug!("Spawning QBittorrent to add a new torrent");
-                        process_client = Some(self.spawn_qbittorrent_web().await?);
+                        process_client = Some(
+                            self.spawn_configured_qbittorrent_web(
+                                &http_client,
+                                max_active_downloads,
+                            )
+                            .await?,
+                        );
                         process_client.as_ref().unwrap()
                     };
 
@@ -122,7 +166,13 @@ impl QBittorrentClient {
                         client
                     } else {
                         debug!("Spawning QBittorrent to remove a torrent");
-                        process_client = Some(self.spawn_qbittorrent_web().await?);
+                        process_client = Some(
+                            self.spawn_configured_qbittorrent_web(
+                                &http_client,
+                                max_active_downloads,
+                            )
+                            .await?,
+                        );
                         process_client.as_ref().unwrap()
                     };
 
@@ -130,6 +180,54 @@ impl QBittorrentClient {
                     // TODO: add logging here
                     let _ = result_sender.send(result);
                 }
+                QBittorrentClientMessage::SetQueuePosition {
+                    id,
+                    change,
+                    result_sender,
+                } => {
+                    let process_client = if let Some(client) = &process_client {
+                        client
+                    } else {
+                        debug!("Spawning QBittorrent to change queue position of a torrent");
+                        process_client = Some(
+                            self.spawn_configured_qbittorrent_web(
+                                &http_client,
+                                max_active_downloads,
+                            )
+                            .await?,
+                        );
+                        process_client.as_ref().unwrap()
+                    };
+
+                    let result =
+                        set_queue_position(&http_client, process_client.port, &id, &change).await;
+                    let _ = result_sender.send(result);
+                }
+                QBittorrentClientMessage::SetMaxActiveDownloads {
+                    max_active_downloads: new_max_active_downloads,
+                    result_sender,
+                } => {
+                    // If QBittorrent is down, the new value is applied when it's spawned again
+                    let result = match &process_client {
+                        Some(process_client) => {
+                            set_max_active_downloads(
+                                &http_client,
+                                process_client.port,
+                                new_max_active_downloads,
+                            )
+                            .await
+                        }
+                        None => Ok(()),
+                    };
+
+                    if result.is_ok() {
+                        max_active_downloads = new_max_active_downloads;
+                    }
+                    let _ = result_sender.send(result);
+                }
+                QBittorrentClientMessage::GetMaxActiveDownloads { result_sender } => {
+                    let _ = result_sender.send(max_active_downloads);
+                }
                 QBittorrentClientMessage::UpdateTorrentList { result_sender } => {
                     let process_client_ref = if let Some(process_client) = &process_client {
                         process_client
@@ -173,9 +271,34 @@ impl QBittorrentClient {
         Ok(())
     }
 
+    /// Spawns the WebUI and applies our preferences to it.
+    async fn spawn_configured_qbittorrent_web(
+        &self,
+        http_client: &reqwest::Client,
+        max_active_downloads: Option<u32>,
+    ) -> QBittorrentResult<QBittorrentClientProcess> {
+        let process = self.spawn_qbittorrent_web().await?;
+
+        // Not fatal, downloads still work without a queue
+        if let Err(err) =
+            set_max_active_downloads(http_client, process.port, max_active_downloads).await
+        {
+            warn!("Couldn't apply queue preferences to QBittorrent. Reason: {err}");
+        }
+
+        Ok(process)
+    }
+
     pub(crate) async fn spawn_qbittorrent_web(
         &self,
     ) -> QBittorrentResult<QBittorrentClientProcess> {
+        if let Some(port) = self.web_ui_port {
+            return Ok(QBittorrentClientProcess {
+                process_handle: None,
+                port,
+            });
+        }
+
         self.create_profile().await?;
 
         let result = Command::new("qbittorrent-nox")
@@ -223,7 +346,7 @@ impl QBittorrentClient {
                 info!("Spawned QBitorrent web API at http://localhost:{port}");
 
                 return Ok(QBittorrentClientProcess {
-                    process_handle,
+                    process_handle: Some(process_handle),
                     port,
                 });
             }
@@ -345,11 +468,26 @@ pub enum QBittorrentClientMessage {
         result_sender:
             tokio::sync::oneshot::Sender<QBittorrentWebApiResult<Box<[TorrentContents]>>>,
     },
+    SetQueuePosition {
+        id: Box<str>,
+        change: domain::QueuePositionChange,
+        result_sender: tokio::sync::oneshot::Sender<QBittorrentWebApiResult<()>>,
+    },
+    /// `None` disables queueing
+    SetMaxActiveDownloads {
+        max_active_downloads: Option<u32>,
+        result_sender: tokio::sync::oneshot::Sender<QBittorrentWebApiResult<()>>,
+    },
+    GetMaxActiveDownloads {
+        result_sender: tokio::sync::oneshot::Sender<Option<u32>>,
+    },
 }
 
 impl Drop for QBittorrentClientProcess {
     fn drop(&mut self) {
-        self.process_handle.abort();
+        if let Some(process_handle) = &self.process_handle {
+            process_handle.abort();
+        }
     }
 }
 
@@ -383,6 +521,7 @@ mod tests {
     use crate::qbittorrent_client::QBittorrentClient;
 
     #[tokio::test]
+    #[ignore = "needs qbittorrent-nox to be installed"]
     async fn test_spawn_process() {
         let client = QBittorrentClient::try_new(None).unwrap();
         let client_process = client.spawn_qbittorrent_web().await.unwrap();
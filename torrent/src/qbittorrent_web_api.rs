@@ -1,5 +1,6 @@
 use std::fmt::Display;
 
+use domain::QueuePositionChange;
 use reqwest::{Client, StatusCode, Url};
 
 use crate::{
@@ -171,10 +172,105 @@ async fn add_torrent_category(
     Ok(())
 }
 
+pub(crate) async fn set_queue_position(
+    client: &Client,
+    port: usize,
+    id: &str,
+    change: &QueuePositionChange,
+) -> QBittorrentWebApiResult<()> {
+    let url: Url = {
+        let mut url: Url = BASE_URL.parse().unwrap();
+        url.set_port(Some(port as u16))
+            .expect("Invalid port was passed");
+        url.set_path(match change {
+            QueuePositionChange::Top => "api/v2/torrents/topPrio",
+            QueuePositionChange::Bottom => "api/v2/torrents/bottomPrio",
+            QueuePositionChange::Up => "api/v2/torrents/increasePrio",
+            QueuePositionChange::Down => "api/v2/torrents/decreasePrio",
+        });
+        url
+    };
+
+    let response = client
+        .post(url)
+        .form(&HashesForm { hashes: id })
+        .send()
+        .await
+        .map_err(|err| QBittorrentWebApiError::CouldntCallApi(err.to_string().into()))?;
+
+    // QBittorrent returns 409 if queueing is disabled
+    if response.status() == StatusCode::CONFLICT {
+        return Err(QBittorrentWebApiError::QueueingDisabled(
+            format!(
+                "Couldn't move torrent with id {id} {change:?} in the queue. Queueing is disabled"
+            )
+            .into(),
+        ));
+    }
+    if !response.status().is_success() {
+        return Err(QBittorrentWebApiError::CantChangeQueuePosition(
+            format!(
+                "Couldn't move torrent with id {id} {change:?} in the queue. Status: {}",
+                response.status()
+            )
+            .into(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// `None` disables queueing altogether.
+pub(crate) async fn set_max_active_downloads(
+    client: &Client,
+    port: usize,
+    max_active_downloads: Option<u32>,
+) -> QBittorrentWebApiResult<()> {
+    let url: Url = {
+        let mut url: Url = BASE_URL.parse().unwrap();
+        url.set_port(Some(port as u16))
+            .expect("Invalid port was passed");
+        url.set_path("api/v2/app/setPreferences");
+        url
+    };
+
+    let preferences = match max_active_downloads {
+        // Only downloads are limited, -1 means unlimited
+        Some(max_active_downloads) => serde_json::json!({
+            "queueing_enabled": true,
+            "max_active_downloads": max_active_downloads,
+            "max_active_uploads": -1,
+            "max_active_torrents": -1,
+        }),
+        None => serde_json::json!({ "queueing_enabled": false }),
+    };
+
+    let response = client
+        .post(url)
+        .form(&SetPreferencesForm {
+            json: &preferences.to_string(),
+        })
+        .send()
+        .await
+        .map_err(|err| QBittorrentWebApiError::CouldntCallApi(err.to_string().into()))?;
+
+    if !response.status().is_success() {
+        return Err(QBittorrentWebApiError::CantSetPreferences(
+            format!(
+                "Couldn't set max active downloads to {max_active_downloads:?}. Status: {}",
+                response.status()
+            )
+            .into(),
+        ));
+    }
+
+    Ok(())
+}
+
 fn encode_extra(extra: &TorrentExtra) -> QBittorrentWebApiResult<String> {
     let json_string = serde_json::to_string(extra).map_err(|err| {
         QBittorrentWebApiError::CantAddTorrent(
-            format!("Can't serialize metadata {:?}. Reason: {err}", &extra).into(),
+            format!("Can't serialize metadata {:?}. Reason: {err}", extra).into(),
         )
     })?;
 
@@ -206,6 +302,16 @@ struct SetCategoryForm<'a> {
     category: &'a str,
 }
 
+#[derive(serde::Serialize)]
+struct HashesForm<'a> {
+    hashes: &'a str,
+}
+
+#[derive(serde::Serialize)]
+struct SetPreferencesForm<'a> {
+    json: &'a str,
+}
+
 pub(crate) async fn get_torrent_list(
     client: &Client,
     port: usize,
@@ -241,6 +347,9 @@ pub enum QBittorrentWebApiError {
     CantDeserialize(Box<str>),
     CantAddTorrent(Box<str>),
     CantDeleteTorrent(Box<str>),
+    CantChangeQueuePosition(Box<str>),
+    QueueingDisabled(Box<str>),
+    CantSetPreferences(Box<str>),
 }
 
 impl Display for QBittorrentWebApiError {
@@ -252,232 +361,352 @@ impl Display for QBittorrentWebApiError {
             QBittorrentWebApiError::CantDeserialize(msg) => msg,
             QBittorrentWebApiError::CantAddTorrent(msg) => msg,
             QBittorrentWebApiError::CantDeleteTorrent(msg) => msg,
+            QBittorrentWebApiError::CantChangeQueuePosition(msg) => msg,
+            QBittorrentWebApiError::QueueingDisabled(msg) => msg,
+            QBittorrentWebApiError::CantSetPreferences(msg) => msg,
         })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashMap, marker::PhantomData, time::Duration};
+    use std::{collections::HashMap, marker::PhantomData};
 
-    use domain::{MediaMetaData, series::EditSeriesFileMappingForm};
+    use domain::{MediaMetaData, QueuePositionChange, series::EditSeriesFileMappingForm};
 
     use crate::{
-        TorrentExtra,
-        qbittorrent_client::QBittorrentClient,
+        TorrentExtra, TorrentState,
+        mock::{MockQBittorrent, MockTorrent},
         qbittorrent_web_api::{
             QBittorrentWebApiError, add_torrent, get_torrent_contents, get_torrent_list,
-            remove_torrent, set_torrent_category,
+            remove_torrent, set_max_active_downloads, set_queue_position, set_torrent_category,
         },
     };
 
-    #[tokio::test]
-    async fn test_add_torrent() {
-        let client = QBittorrentClient::try_new(None).unwrap();
-        let client_process = client.spawn_qbittorrent_web().await.unwrap();
-
-        dbg!(&client_process);
+    const DEBIAN_TORRENT: &str = "https://cdimage.debian.org/debian-cd/current/arm64/bt-cd/debian-13.1.0-arm64-netinst.iso.torrent";
 
-        let http_client = reqwest::Client::new();
+    async fn spawn_mock() -> MockQBittorrent {
+        let mock = MockQBittorrent::spawn().await.unwrap();
+        mock.register_torrent(
+            DEBIAN_TORRENT,
+            MockTorrent {
+                hash: "debianhash".into(),
+                name: "debian-13.1.0-arm64-netinst.iso".into(),
+                files: vec![("debian-13.1.0-arm64-netinst.iso".into(), 1024)],
+            },
+        );
+        mock
+    }
 
-        let metadata = MediaMetaData {
+    fn metadata() -> MediaMetaData {
+        MediaMetaData {
             title: "My Movie".to_string(),
             thumbnail: "https://image.com".to_string(),
-        };
-        add_torrent(&http_client, client_process.port, "https://cdimage.debian.org/debian-cd/current/arm64/bt-cd/debian-13.1.0-arm64-netinst.iso.torrent", &TorrentExtra::new(metadata, false)).await.unwrap();
+            ..Default::default()
+        }
+    }
 
-        tokio::time::sleep(Duration::from_secs(5)).await;
+    #[tokio::test]
+    async fn test_add_torrent() {
+        let mock = spawn_mock().await;
+        let http_client = reqwest::Client::new();
 
-        let torrent_list = get_torrent_list(&http_client, client_process.port)
+        let extra = TorrentExtra::new(metadata(), false);
+        add_torrent(&http_client, mock.port, DEBIAN_TORRENT, &extra)
             .await
             .unwrap();
 
-        assert!(!torrent_list.is_empty());
+        let torrent_list = get_torrent_list(&http_client, mock.port).await.unwrap();
 
-        dbg!(&torrent_list);
+        assert_eq!(torrent_list.len(), 1);
+        assert_eq!(
+            TorrentExtra::try_from(&torrent_list[0]).unwrap(),
+            extra,
+            "Extra should be stored in the category"
+        );
     }
 
     #[tokio::test]
     async fn test_set_torrent_category() {
-        let client = QBittorrentClient::try_new(None).unwrap();
-        let client_process = client.spawn_qbittorrent_web().await.unwrap();
-
-        dbg!(&client_process);
-
+        let mock = spawn_mock().await;
         let http_client = reqwest::Client::new();
 
-        remove_torrent(&http_client, client_process.port, "all")
-            .await
-            .unwrap();
-
-        let metadata = MediaMetaData {
-            title: "My Movie".to_string(),
-            thumbnail: "https://image.com".to_string(),
-        };
-        add_torrent(&http_client, client_process.port, "https://cdimage.debian.org/debian-cd/current/arm64/bt-cd/debian-13.1.0-arm64-netinst.iso.torrent", &TorrentExtra::new(metadata.clone(), false)).await.unwrap();
-
-        tokio::time::sleep(Duration::from_secs(5)).await;
-
-        let torrent_list = get_torrent_list(&http_client, client_process.port)
-            .await
-            .unwrap();
-
-        assert!(torrent_list.len() == 1);
-
-        dbg!(&torrent_list);
-
-        let first_id = &torrent_list[0].hash;
-
-        set_torrent_category(
+        add_torrent(
             &http_client,
-            client_process.port,
-            first_id,
-            &TorrentExtra::Series {
-                metadata,
-                files_mapping_form: Some(EditSeriesFileMappingForm {
-                    id: "hey".into(),
-                    phantom: PhantomData,
-                    file_mapping: HashMap::new(),
-                }),
-            },
+            mock.port,
+            DEBIAN_TORRENT,
+            &TorrentExtra::new(metadata(), true),
         )
         .await
         .unwrap();
 
-        tokio::time::sleep(Duration::from_secs(5)).await;
+        let torrent_list = get_torrent_list(&http_client, mock.port).await.unwrap();
+        assert_eq!(torrent_list.len(), 1);
+
+        let first_id = &torrent_list[0].hash;
+        let new_extra = TorrentExtra::Series {
+            metadata: metadata(),
+            files_mapping_form: Some(EditSeriesFileMappingForm {
+                id: "hey".into(),
+                phantom: PhantomData,
+                file_mapping: HashMap::new(),
+            }),
+        };
 
-        let torrent_list = get_torrent_list(&http_client, client_process.port)
+        set_torrent_category(&http_client, mock.port, first_id, &new_extra)
             .await
             .unwrap();
 
-        assert!(torrent_list.len() == 1);
-
-        assert_eq!(torrent_list[0].category, "Hello World".into());
+        let torrent_list = get_torrent_list(&http_client, mock.port).await.unwrap();
+        assert_eq!(torrent_list.len(), 1);
+        assert_eq!(TorrentExtra::try_from(&torrent_list[0]).unwrap(), new_extra);
+        assert!(mock.categories().contains(&torrent_list[0].category));
     }
 
     #[tokio::test]
     async fn test_add_faulty_torrent() {
-        let client = QBittorrentClient::try_new(None).unwrap();
-        let client_process = client.spawn_qbittorrent_web().await.unwrap();
-
-        dbg!(&client_process);
-
+        let mock = spawn_mock().await;
         let http_client = reqwest::Client::new();
 
-        let metadata = MediaMetaData {
-            title: "My Movie".to_string(),
-            thumbnail: "https://image.com".to_string(),
-        };
         assert!(matches!(
             add_torrent(
                 &http_client,
-                client_process.port,
+                mock.port,
                 "non_existent_link_for_torrent",
-                &TorrentExtra::new(metadata, false)
+                &TorrentExtra::new(metadata(), false)
             )
             .await,
             Err(QBittorrentWebApiError::CantAddTorrent(_))
         ));
+        assert!(mock.torrents().is_empty());
     }
 
     #[tokio::test]
     async fn test_remove_torrent() {
-        let client = QBittorrentClient::try_new(None).unwrap();
-        let client_process = client.spawn_qbittorrent_web().await.unwrap();
-
-        dbg!(&client_process);
-
+        let mock = spawn_mock().await;
         let http_client = reqwest::Client::new();
 
-        let metadata = MediaMetaData {
-            title: "My Movie".to_string(),
-            thumbnail: "https://image.com".to_string(),
-        };
-        add_torrent(&http_client, client_process.port, "https://cdimage.debian.org/debian-cd/current/arm64/bt-cd/debian-13.1.0-arm64-netinst.iso.torrent", &TorrentExtra::new(metadata, false)).await.unwrap();
+        add_torrent(
+            &http_client,
+            mock.port,
+            DEBIAN_TORRENT,
+            &TorrentExtra::new(metadata(), false),
+        )
+        .await
+        .unwrap();
 
-        tokio::time::sleep(Duration::from_secs(5)).await;
+        let torrent_list = get_torrent_list(&http_client, mock.port).await.unwrap();
+        let first_item_hash = &torrent_list.first().unwrap().hash;
 
-        let torrent_list = get_torrent_list(&http_client, client_process.port)
+        remove_torrent(&http_client, mock.port, first_item_hash)
             .await
             .unwrap();
 
-        assert!(!torrent_list.is_empty());
-
-        dbg!(&torrent_list);
-
-        let first_item_hash = &torrent_list.first().unwrap().hash;
+        let torrent_list = get_torrent_list(&http_client, mock.port).await.unwrap();
+        assert!(
+            !torrent_list
+                .iter()
+                .any(|torrent| torrent.hash == *first_item_hash)
+        );
+    }
 
-        remove_torrent(&http_client, client_process.port, first_item_hash)
-            .await
-            .unwrap();
+    #[tokio::test]
+    async fn test_remove_all_torrents() {
+        let mock = spawn_mock().await;
+        let http_client = reqwest::Client::new();
 
-        tokio::time::sleep(Duration::from_secs(5)).await;
+        add_torrent(
+            &http_client,
+            mock.port,
+            DEBIAN_TORRENT,
+            &TorrentExtra::new(metadata(), false),
+        )
+        .await
+        .unwrap();
 
-        let torrent_list = get_torrent_list(&http_client, client_process.port)
+        remove_torrent(&http_client, mock.port, "all")
             .await
             .unwrap();
 
         assert!(
-            !torrent_list
-                .iter()
-                .any(|torrent| torrent.hash == *first_item_hash)
+            get_torrent_list(&http_client, mock.port)
+                .await
+                .unwrap()
+                .is_empty()
         );
-
-        dbg!(&torrent_list);
     }
 
     #[tokio::test]
     async fn test_get_torrent_list() {
-        let client = QBittorrentClient::try_new(None).unwrap();
-        let client_process = client.spawn_qbittorrent_web().await.unwrap();
+        let mock = spawn_mock().await;
+        let http_client = reqwest::Client::new();
 
-        dbg!(&client_process);
+        let torrent_list = get_torrent_list(&http_client, mock.port).await.unwrap();
 
+        assert!(torrent_list.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_torrent_list_follows_scripted_states() {
+        let mock = spawn_mock().await;
         let http_client = reqwest::Client::new();
 
-        let torrent_list = get_torrent_list(&http_client, client_process.port)
-            .await
-            .unwrap();
+        add_torrent(
+            &http_client,
+            mock.port,
+            DEBIAN_TORRENT,
+            &TorrentExtra::new(metadata(), false),
+        )
+        .await
+        .unwrap();
+        mock.script_states(
+            "debianhash",
+            [TorrentState::Downloading, TorrentState::Uploading],
+        );
+
+        let torrent_list = get_torrent_list(&http_client, mock.port).await.unwrap();
+        assert_eq!(torrent_list[0].state, TorrentState::Downloading);
+
+        let torrent_list = get_torrent_list(&http_client, mock.port).await.unwrap();
+        assert_eq!(torrent_list[0].state, TorrentState::Uploading);
+        assert_eq!(torrent_list[0].progress, 1.0);
 
-        dbg!(&torrent_list);
+        // Last state sticks
+        let torrent_list = get_torrent_list(&http_client, mock.port).await.unwrap();
+        assert_eq!(torrent_list[0].state, TorrentState::Uploading);
     }
 
     #[tokio::test]
     async fn test_get_torrent_contents() {
-        let client = QBittorrentClient::try_new(None).unwrap();
-        let client_process = client.spawn_qbittorrent_web().await.unwrap();
+        let mock = spawn_mock().await;
+        let http_client = reqwest::Client::new();
 
-        dbg!(&client_process);
+        add_torrent(
+            &http_client,
+            mock.port,
+            DEBIAN_TORRENT,
+            &TorrentExtra::new(metadata(), false),
+        )
+        .await
+        .unwrap();
 
-        let http_client = reqwest::Client::new();
+        let torrent_list = get_torrent_list(&http_client, mock.port).await.unwrap();
 
-        remove_torrent(&http_client, client_process.port, "all")
+        let contents = get_torrent_contents(&http_client, mock.port, &torrent_list[0].hash)
             .await
             .unwrap();
 
-        let metadata = MediaMetaData {
-            title: "My Movie".to_string(),
-            thumbnail: "https://image.com".to_string(),
-        };
-        add_torrent(&http_client, client_process.port, "https://cdimage.debian.org/debian-cd/current/arm64/bt-cd/debian-13.1.0-arm64-netinst.iso.torrent", &TorrentExtra::new(metadata.clone(), false)).await.unwrap();
+        assert_eq!(contents.len(), 1);
+        assert_eq!(&*contents[0].name, "debian-13.1.0-arm64-netinst.iso");
+    }
+
+    #[tokio::test]
+    async fn test_set_max_active_downloads() {
+        let mock = spawn_mock().await;
+        let http_client = reqwest::Client::new();
 
-        tokio::time::sleep(Duration::from_secs(5)).await;
+        set_max_active_downloads(&http_client, mock.port, Some(2))
+            .await
+            .unwrap();
+        let preferences = mock.preferences();
+        assert!(preferences.queueing_enabled);
+        assert_eq!(preferences.max_active_downloads, 2);
 
-        let torrent_list = get_torrent_list(&http_client, client_process.port)
+        set_max_active_downloads(&http_client, mock.port, None)
             .await
             .unwrap();
+        assert!(!mock.preferences().queueing_enabled);
+    }
+
+    #[tokio::test]
+    async fn test_set_queue_position() {
+        let mock = spawn_mock().await;
+        mock.register_torrent(
+            "https://example.com/second.torrent",
+            MockTorrent {
+                hash: "secondhash".into(),
+                name: "second".into(),
+                files: vec![("second.mkv".into(), 2048)],
+            },
+        );
+        let http_client = reqwest::Client::new();
 
-        assert!(!torrent_list.is_empty());
+        // Queue can't be changed while queueing is disabled
+        add_torrent(
+            &http_client,
+            mock.port,
+            DEBIAN_TORRENT,
+            &TorrentExtra::new(metadata(), false),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            set_queue_position(
+                &http_client,
+                mock.port,
+                "debianhash",
+                &QueuePositionChange::Down
+            )
+            .await,
+            Err(QBittorrentWebApiError::QueueingDisabled(_))
+        ));
 
-        dbg!(&torrent_list);
+        set_max_active_downloads(&http_client, mock.port, Some(1))
+            .await
+            .unwrap();
+        add_torrent(
+            &http_client,
+            mock.port,
+            "https://example.com/second.torrent",
+            &TorrentExtra::new(metadata(), false),
+        )
+        .await
+        .unwrap();
 
-        let contents =
-            get_torrent_contents(&http_client, client_process.port, &torrent_list[0].hash)
+        let queue_positions = async || {
+            get_torrent_list(&http_client, mock.port)
                 .await
-                .unwrap();
+                .unwrap()
+                .iter()
+                .map(|torrent| (torrent.hash.clone(), torrent.priority))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            queue_positions().await,
+            vec![("debianhash".into(), 1), ("secondhash".into(), 2)]
+        );
 
-        assert!(!contents.is_empty());
+        set_queue_position(
+            &http_client,
+            mock.port,
+            "secondhash",
+            &QueuePositionChange::Top,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            queue_positions().await,
+            vec![("debianhash".into(), 2), ("secondhash".into(), 1)]
+        );
+
+        set_queue_position(
+            &http_client,
+            mock.port,
+            "secondhash",
+            &QueuePositionChange::Down,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            queue_positions().await,
+            vec![("debianhash".into(), 1), ("secondhash".into(), 2)]
+        );
 
-        dbg!(&contents);
+        // Finished torrents leave the queue
+        mock.script_states("debianhash", [TorrentState::Uploading]);
+        assert_eq!(
+            queue_positions().await,
+            vec![("debianhash".into(), 0), ("secondhash".into(), 1)]
+        );
     }
 }
@@ -1,23 +1,44 @@
-use std::time::Duration;
-
 use domain::MediaMetaData;
 use torrent::{
-    TorrentExtra, TorrentInfo,
+    TorrentExtra, TorrentInfo, TorrentState,
+    mock::{MockQBittorrent, MockTorrent},
     qbittorrent_client::{QBittorrentClient, QBittorrentClientMessage},
 };
 
-#[tokio::test]
-async fn test_event_loop() {
-    // 1. Generate client
-    let client = QBittorrentClient::try_new(None).unwrap();
+const DEBIAN_TORRENT: &str = "https://cdimage.debian.org/debian-cd/current/arm64/bt-cd/debian-13.1.0-arm64-netinst.iso.torrent";
+
+async fn update_torrent_list(sender: &tokio::sync::mpsc::Sender<QBittorrentClientMessage>) {
+    let (update_torrent_list_result_sender, update_torrent_list_result_receiver) =
+        tokio::sync::oneshot::channel();
 
-    // 2. Remove what's on profile dir
-    tokio::fs::remove_dir_all(&client.profile_dir)
+    sender
+        .send(QBittorrentClientMessage::UpdateTorrentList {
+            result_sender: update_torrent_list_result_sender,
+        })
         .await
         .unwrap();
 
-    // 3. Spawn event loop
-    let (torrent_list_sender, torrent_list_receiver): (
+    update_torrent_list_result_receiver.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_event_loop() {
+    // 1. Spawn a fake qBittorrent and point the client to it
+    let mock = MockQBittorrent::spawn().await.unwrap();
+    mock.register_torrent(
+        DEBIAN_TORRENT,
+        MockTorrent {
+            hash: "debianhash".into(),
+            name: "debian-13.1.0-arm64-netinst.iso".into(),
+            files: vec![("debian-13.1.0-arm64-netinst.iso".into(), 1024)],
+        },
+    );
+    let client = QBittorrentClient::try_new(None)
+        .unwrap()
+        .with_web_ui_port(mock.port);
+
+    // 2. Spawn event loop
+    let (torrent_list_sender, mut torrent_list_receiver): (
         tokio::sync::watch::Sender<Box<[TorrentInfo]>>,
         _,
     ) = tokio::sync::watch::channel(Box::new([]));
@@ -33,9 +54,10 @@ async fn test_event_loop() {
     let metadata = MediaMetaData {
         title: "My Movie".to_string(),
         thumbnail: "https://image.com".to_string(),
+        ..Default::default()
     };
 
-    // 4. Try adding a faulty torrent
+    // 3. Try adding a faulty torrent
     {
         let (add_torrent_result_sender, add_torrent_result_receiver) =
             tokio::sync::oneshot::channel();
@@ -51,84 +73,67 @@ async fn test_event_loop() {
         assert!(add_torrent_result_receiver.await.unwrap().is_err());
     }
 
-    // 5. Add new torrent
+    // 4. Add new torrent
     {
         let (add_torrent_result_sender, add_torrent_result_receiver) =
             tokio::sync::oneshot::channel();
 
-        torrent_event_loop_sender.send(
-            QBittorrentClientMessage::AddTorrent {
-                hash: "https://cdimage.debian.org/debian-cd/current/arm64/bt-cd/debian-13.1.0-arm64-netinst.iso.torrent".into(),
+        torrent_event_loop_sender
+            .send(QBittorrentClientMessage::AddTorrent {
+                hash: DEBIAN_TORRENT.into(),
                 result_sender: add_torrent_result_sender,
                 extra: Box::new(TorrentExtra::new(metadata.clone(), false)),
-            }
-         ).await.unwrap();
-
-        add_torrent_result_receiver.await.unwrap().unwrap();
-    }
-
-    // 6. Wait a bit because QBittorrent doesn't immediately add the torrent.
-    tokio::time::sleep(Duration::from_secs(5)).await;
-
-    // 7. Ask client to update its torrent list
-    {
-        let (update_torrent_list_result_sender, update_torrent_list_result_receiver) =
-            tokio::sync::oneshot::channel();
-
-        torrent_event_loop_sender
-            .send(QBittorrentClientMessage::UpdateTorrentList {
-                result_sender: update_torrent_list_result_sender,
             })
             .await
             .unwrap();
 
-        update_torrent_list_result_receiver.await.unwrap().unwrap();
+        add_torrent_result_receiver.await.unwrap().unwrap();
     }
 
-    // 8. Make sure the list is not empty
-    let value = torrent_list_receiver.borrow();
-    dbg!(&value);
-    assert!(!value.is_empty());
+    // 5. Let the torrent download, then finish
+    mock.script_states(
+        "debianhash",
+        [TorrentState::Downloading, TorrentState::Uploading],
+    );
 
-    // 9. Remove torrent
+    // 6. Ask client to update its torrent list, it should see the download in progress
+    update_torrent_list(&torrent_event_loop_sender).await;
     {
-        let (update_torrent_list_result_sender, update_torrent_list_result_receiver) =
-            tokio::sync::oneshot::channel();
-
-        torrent_event_loop_sender
-            .send(QBittorrentClientMessage::RemoveTorrent {
-                id: value.first().unwrap().hash.clone(),
-                result_sender: update_torrent_list_result_sender,
-            })
-            .await
-            .unwrap();
-
-        update_torrent_list_result_receiver.await.unwrap().unwrap();
+        let value = torrent_list_receiver.borrow_and_update();
+        assert_eq!(value.len(), 1);
+        assert_eq!(value[0].state, TorrentState::Downloading);
     }
 
-    // 10. Wait a bit because QBittorrent doesn't immediately remove a torrent.
-    tokio::time::sleep(Duration::from_secs(5)).await;
+    // 7. Next update sees the finished torrent
+    update_torrent_list(&torrent_event_loop_sender).await;
+    let finished_hash = {
+        let value = torrent_list_receiver.borrow_and_update();
+        assert_eq!(value.len(), 1);
+        assert!(value[0].state.is_done());
+        value[0].hash.clone()
+    };
 
-    // 11. Ask client to update its torrent list
+    // 8. Remove torrent
     {
-        let (update_torrent_list_result_sender, update_torrent_list_result_receiver) =
+        let (remove_torrent_result_sender, remove_torrent_result_receiver) =
             tokio::sync::oneshot::channel();
 
         torrent_event_loop_sender
-            .send(QBittorrentClientMessage::UpdateTorrentList {
-                result_sender: update_torrent_list_result_sender,
+            .send(QBittorrentClientMessage::RemoveTorrent {
+                id: finished_hash,
+                result_sender: remove_torrent_result_sender,
             })
             .await
             .unwrap();
 
-        update_torrent_list_result_receiver.await.unwrap().unwrap();
+        remove_torrent_result_receiver.await.unwrap().unwrap();
     }
 
-    // 12. Make sure the list is empty
-    let value = torrent_list_receiver.borrow();
-    dbg!(&value);
-    assert!(value.is_empty());
+    // 9. Ask client to update its torrent list, make sure the list is empty
+    update_torrent_list(&torrent_event_loop_sender).await;
+    assert!(torrent_list_receiver.borrow_and_update().is_empty());
+    assert!(mock.torrents().is_empty());
 
-    // 13. Clean up the event loop explicitly
+    // 10. Clean up the event loop explicitly
     event_loop_handle.abort();
 }